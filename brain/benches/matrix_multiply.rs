@@ -0,0 +1,55 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use std::hint::black_box;
+use brain::math::matrix::Matrix;
+use brain::math::random::RandomGenerator;
+
+// the previous nested vector storage with the naive i-j-k multiplication, kept for comparison
+fn multiply_nested(a: &[Vec<f64>], b: &[Vec<f64>]) -> Vec<Vec<f64>> {
+    let mut result = vec![vec![0.0; b[0].len()]; a.len()];
+    for i in 0..a.len() {
+        for j in 0..b[0].len() {
+            let mut sum = 0.0;
+            for k in 0..b.len() {
+                sum += a[i][k] * b[k][j];
+            }
+            result[i][j] = sum;
+        }
+    }
+    result
+}
+
+fn random_matrix(rows: usize, cols: usize, rng: &mut RandomGenerator<f64>) -> Matrix<f64> {
+    let mut m = Matrix::new(rows, cols);
+    for row in 0..rows {
+        for col in 0..cols {
+            m[row][col] = rng.generate(-1.0, 1.0);
+        }
+    }
+    m
+}
+
+fn to_nested(m: &Matrix<f64>) -> Vec<Vec<f64>> {
+    (0..m.rows()).map(|row| m[row].to_vec()).collect()
+}
+
+fn bench_multiply(c: &mut Criterion) {
+    let mut rng = RandomGenerator::with_seed(7);
+    let mut group = c.benchmark_group("matrix_multiply");
+    // (rows of the left operand, shared dimension, columns of the right operand)
+    for &(n, m, p) in [(1, 30, 100), (30, 100, 10), (100, 100, 100), (256, 256, 256)].iter() {
+        let a = random_matrix(n, m, &mut rng);
+        let b = random_matrix(m, p, &mut rng);
+        let (a_nested, b_nested) = (to_nested(&a), to_nested(&b));
+        let label = format!("{}x{}x{}", n, m, p);
+        group.bench_with_input(BenchmarkId::new("nested", &label), &(), |bencher, _| {
+            bencher.iter(|| multiply_nested(black_box(&a_nested), black_box(&b_nested)))
+        });
+        group.bench_with_input(BenchmarkId::new("contiguous_blocked", &label), &(), |bencher, _| {
+            bencher.iter(|| black_box(&a) * black_box(&b))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_multiply);
+criterion_main!(benches);
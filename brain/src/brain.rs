@@ -0,0 +1,163 @@
+use std::fmt;
+
+use crate::math::matrix::Matrix;
+use crate::network::network::Network;
+use crate::neat::genome::{Genome, NodeKind};
+
+/*
+    common interface of everything that maps inputs to outputs, so games can hold a Box<dyn Brain<T>>
+    and swap layered networks, NEAT genomes or scripted baselines without touching the game loop
+    brains are Send, so the game can let its players think on several threads
+*/
+
+pub trait Brain<T>: Send {
+    // input.len() must be the input size, the result has the output size
+    fn forward(&self, input: &[T]) -> Vec<T>;
+    fn input_size(&self) -> usize;
+    fn output_size(&self) -> usize;
+    // number of trainable values (weights and biases)
+    fn parameter_count(&self) -> usize;
+    fn clone_box(&self) -> Box<dyn Brain<T>>;
+}
+
+impl<T> Clone for Box<dyn Brain<T>> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+// 1xn matrix holding the values
+fn input_row<T: Default + Copy>(input: &[T]) -> Matrix<T> {
+    let mut row = Matrix::new(1, input.len());
+    row[0].copy_from_slice(input);
+    row
+}
+
+impl<T> Brain<T> for Network<T>
+where
+    T: num::Float + num::Signed + Default + fmt::Debug + Send + 'static
+{
+    fn forward(&self, input: &[T]) -> Vec<T> {
+        Network::forward(self, &input_row(input))[0].to_vec()
+    }
+
+    fn input_size(&self) -> usize {
+        self.get_weights_dimension(0).rows
+    }
+
+    fn output_size(&self) -> usize {
+        self.get_biases_dimension(self.get_num_bias_layers() - 1).cols
+    }
+
+    fn parameter_count(&self) -> usize {
+        self.get_num_parameters()
+    }
+
+    fn clone_box(&self) -> Box<dyn Brain<T>> {
+        Box::new(self.clone())
+    }
+}
+
+impl<T> Brain<T> for Genome<T>
+where
+    T: num::Float + Default + fmt::Debug + Send + 'static
+{
+    fn forward(&self, input: &[T]) -> Vec<T> {
+        Genome::forward(self, &input_row(input))[0].to_vec()
+    }
+
+    fn input_size(&self) -> usize {
+        self.get_input_size()
+    }
+
+    fn output_size(&self) -> usize {
+        self.get_output_size()
+    }
+
+    // enabled connections and the biases of the non-input nodes
+    fn parameter_count(&self) -> usize {
+        self.connections().iter().filter(|c| c.enabled).count()
+            + self.nodes().iter().filter(|n| n.kind != NodeKind::Input).count()
+    }
+
+    fn clone_box(&self) -> Box<dyn Brain<T>> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::activation::Activation;
+    use crate::network::network_builder::NetworkBuilder;
+    use crate::network::network_initializer::NetworkInitializer;
+    use crate::neat::innovation::InnovationTracker;
+
+    // scripted baseline: outputs the sum of the inputs
+    #[derive(Clone)]
+    struct Sum {
+        inputs: usize
+    }
+
+    impl Brain<f32> for Sum {
+        fn forward(&self, input: &[f32]) -> Vec<f32> {
+            vec![input.iter().sum()]
+        }
+
+        fn input_size(&self) -> usize {
+            self.inputs
+        }
+
+        fn output_size(&self) -> usize {
+            1
+        }
+
+        fn parameter_count(&self) -> usize {
+            0
+        }
+
+        fn clone_box(&self) -> Box<dyn Brain<f32>> {
+            Box::new(self.clone())
+        }
+    }
+
+    #[test]
+    fn network_brain_test() {
+        let mut network = NetworkBuilder::<f32>::new()
+            .input(4)
+            .hidden(3, Activation::ReLU)
+            .output(2, Activation::Sigmoid)
+            .build();
+        NetworkInitializer::new(5, -1.0, 1.0).initialize(&mut network);
+        let input = [0.1, -0.2, 0.3, 0.4];
+        let expected = network.forward(&input_row(&input));
+        let brain: Box<dyn Brain<f32>> = Box::new(network);
+        assert_eq!(brain.input_size(), 4);
+        assert_eq!(brain.output_size(), 2);
+        assert_eq!(brain.parameter_count(), 4 * 3 + 3 + 3 * 2 + 2);
+        assert_eq!(brain.forward(&input), expected[0].to_vec());
+        let copy = brain.clone();
+        assert_eq!(copy.forward(&input), expected[0].to_vec());
+    }
+
+    #[test]
+    fn genome_brain_test() {
+        let mut tracker = InnovationTracker::new(3, 2);
+        let genome = Genome::<f32>::new(3, 2, Activation::Identity, &mut tracker);
+        let brain: Box<dyn Brain<f32>> = Box::new(genome);
+        assert_eq!(brain.input_size(), 3);
+        assert_eq!(brain.output_size(), 2);
+        assert_eq!(brain.parameter_count(), 6 + 2);
+        assert_eq!(brain.forward(&[1.0, 2.0, 3.0]), vec![0.0, 0.0]);
+    }
+
+    #[test]
+    fn swap_brains_test() {
+        let brains: Vec<Box<dyn Brain<f32>>> = vec![
+            Box::new(Sum { inputs: 2 }),
+            Box::new(NetworkBuilder::<f32>::new().input(2).hidden(2, Activation::ReLU).output(1, Activation::Identity).build())
+        ];
+        let outputs: Vec<Vec<f32>> = brains.iter().map(|brain| brain.forward(&[1.0, 2.0])).collect();
+        assert_eq!(outputs, vec![vec![3.0], vec![0.0]]);
+    }
+}
@@ -0,0 +1,131 @@
+use std::fmt;
+use crate::math::dimensions::Dimensions;
+
+/*
+    errors returned by the try_ variants of the brain API
+    the panicking variants panic with the Display text of these errors
+*/
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    ZeroDimension,
+    DimensionMismatch { operation: &'static str, left: Dimensions, right: Dimensions },
+    IndexOutOfRange { row: usize, col: usize, dimensions: Dimensions },
+    DivisionByZero,
+    ElementCountMismatch { expected: usize, actual: usize },
+    TooFewLayers(usize),
+    LayerOutOfRange(usize),
+    EmptyLayer(usize),
+    NotHiddenLayer(usize),
+    NeuronOutOfRange { layer: usize, neuron: usize },
+    WeightLayerOutOfRange(usize),
+    BiasLayerOutOfRange(usize),
+    ActivationLayerOutOfRange(usize),
+    InputSizeMismatch { expected: usize, actual: usize },
+    ParameterCountMismatch { expected: usize, actual: usize },
+    InputNotSingleRow(usize),
+    TargetSizeMismatch { expected: Dimensions, actual: Dimensions },
+    InvalidNetwork,
+    NoCachedForwardPass,
+    GradientShapeMismatch,
+    CyclicGenome,
+    UnknownNode(usize),
+    InvalidNode(usize),
+    InvalidRange,
+    InvalidParameter(&'static str)
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::ZeroDimension => write!(f, "matrix dimensions cannot be zero"),
+            Error::DimensionMismatch { operation, left, right } => write!(
+                f, "matrix dimensions must match for {} ({}x{} and {}x{})", operation, left.rows, left.cols, right.rows, right.cols
+            ),
+            Error::IndexOutOfRange { row, col, dimensions } => write!(
+                f, "Invalid matrix indices [{}][{}] for a {}x{} matrix", row, col, dimensions.rows, dimensions.cols
+            ),
+            Error::DivisionByZero => write!(f, "cannot divide by zero!"),
+            Error::ElementCountMismatch { expected, actual } => write!(
+                f, "number of elements does not fit the matrix dimensions (expected {}, got {})", expected, actual
+            ),
+            Error::TooFewLayers(num_layers) => write!(f, "Less than 3 layers makes no sense (got {})", num_layers),
+            Error::LayerOutOfRange(index) => write!(f, "Layer does not exist (index {})", index),
+            Error::EmptyLayer(index) => write!(f, "Layer {} must have at least one neuron", index),
+            Error::NotHiddenLayer(index) => write!(f, "Layer {} is not a hidden layer", index),
+            Error::NeuronOutOfRange { layer, neuron } => write!(f, "Neuron {} does not exist in layer {}", neuron, layer),
+            Error::WeightLayerOutOfRange(index) => write!(f, "Invalid weight layer index {}", index),
+            Error::BiasLayerOutOfRange(index) => write!(f, "Invalid bias layer index {}", index),
+            Error::ActivationLayerOutOfRange(index) => write!(f, "Invalid activation layer index {}", index),
+            Error::InputSizeMismatch { expected, actual } => write!(
+                f, "Input size mismatch with input layer (expected {}, got {})", expected, actual
+            ),
+            Error::ParameterCountMismatch { expected, actual } => write!(
+                f, "Parameter count mismatch (expected {}, got {})", expected, actual
+            ),
+            Error::InputNotSingleRow(rows) => write!(
+                f, "Input must be a single row, use forward_batch for multiple inputs (got {} rows)", rows
+            ),
+            Error::TargetSizeMismatch { expected, actual } => write!(
+                f, "Target size mismatch with output layer (expected {}x{}, got {}x{})", expected.rows, expected.cols, actual.rows, actual.cols
+            ),
+            Error::InvalidNetwork => write!(f, "Network is invalid"),
+            Error::NoCachedForwardPass => write!(
+                f, "backward needs the layer values of a forward_cached or forward_batch with the current parameters"
+            ),
+            Error::GradientShapeMismatch => write!(f, "Gradient shapes must match the weights and biases of the network"),
+            Error::CyclicGenome => write!(f, "Genome connections form a cycle, only feed-forward genomes can be evaluated"),
+            Error::UnknownNode(id) => write!(f, "Genome has no node with id {}", id),
+            Error::InvalidNode(id) => write!(
+                f, "Node {} does not fit the genome, inputs and outputs are fixed and hidden ids follow them", id
+            ),
+            Error::InvalidRange => write!(f, "Invalid range: lower limit must be less than upper limit"),
+            Error::InvalidParameter(message) => write!(f, "{}", message)
+        }
+    }
+}
+
+impl std::error::Error for Error { }
+
+// used by the panicking wrappers around the try_ functions
+pub(crate) trait OrPanic<V> {
+    fn or_panic(self) -> V;
+}
+
+impl<V> OrPanic<V> for Result<V, Error> {
+    fn or_panic(self) -> V {
+        match self {
+            Ok(value) => value,
+            Err(e) => panic!("{}", e)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        let error = Error::DimensionMismatch {
+            operation: "addition",
+            left: Dimensions { rows: 2, cols: 2 },
+            right: Dimensions { rows: 3, cols: 3 }
+        };
+        assert_eq!(format!("{}", error), "matrix dimensions must match for addition (2x2 and 3x3)");
+        assert_eq!(format!("{}", Error::TooFewLayers(2)), "Less than 3 layers makes no sense (got 2)");
+    }
+
+    #[test]
+    fn test_or_panic_ok() {
+        let result: Result<i32, Error> = Ok(3);
+        assert_eq!(result.or_panic(), 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "Network is invalid")]
+    fn test_or_panic_err() {
+        let result: Result<i32, Error> = Err(Error::InvalidNetwork);
+        result.or_panic();
+    }
+}
@@ -0,0 +1,413 @@
+use std::fmt;
+
+use crate::math::matrix::Matrix;
+use crate::math::random::RandomGenerator;
+use crate::network::network::Network;
+use crate::evolution::fitness::Fitness;
+use crate::evolution::population::Individual;
+use crate::evolution::statistics::GenerationStats;
+use crate::evolution::parallel::parallel_map;
+use crate::evolution::evolution_strategy::{to_f64, with_parameters};
+use crate::error::{Error, OrPanic};
+
+/*
+    covariance matrix adaptation evolution strategy over the flattened parameters of a single network
+    (Hansen, "The CMA Evolution Strategy: A Tutorial"), maximizing the fitness
+
+    every generation samples lambda candidates x_i = m + sigma * B * D * z_i with z_i ~ N(0, I), where C = B * D^2 * B^T,
+    the mu fittest move the mean, and the evolution paths p_sigma and p_c adapt the step size sigma and the covariance C
+
+    the eigen decomposition of C costs O(n^3), it is only refreshed every few generations
+*/
+
+#[derive(Debug, Clone)]
+pub struct CmaEsConfig {
+    pub seed: u64,
+    // lambda, None picks the default 4 + 3 ln(n)
+    pub population_size: Option<usize>,
+    // initial step size
+    pub sigma: f64
+}
+
+impl Default for CmaEsConfig {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            population_size: None,
+            sigma: 0.5
+        }
+    }
+}
+
+pub struct CmaEs<T> {
+    template: Network<T>,
+    mean: Vec<f64>,
+    sigma: f64,
+    covariance: Matrix<f64>,
+    // eigenvectors (columns) and square roots of the eigenvalues of the covariance
+    eigenvectors: Matrix<f64>,
+    scales: Vec<f64>,
+    path_sigma: Vec<f64>,
+    path_c: Vec<f64>,
+    weights: Vec<f64>,
+    mu_eff: f64,
+    c_sigma: f64,
+    d_sigma: f64,
+    c_c: f64,
+    c_1: f64,
+    c_mu: f64,
+    // expected length of a N(0, I) vector
+    chi_n: f64,
+    lambda: usize,
+    // generation of the last eigen decomposition
+    decomposed: usize,
+    // z_i of the current generation, empty until ask is called
+    samples: Vec<Vec<f64>>,
+    candidates: Vec<Network<T>>,
+    generator: RandomGenerator<f64>,
+    generation: usize,
+    best: Option<Individual<T>>,
+    history: Vec<GenerationStats>
+}
+
+impl<T> CmaEs<T>
+where
+    T: num::Float + num::Signed + Default + fmt::Debug
+{
+    pub fn new(config: CmaEsConfig, network: Network<T>) -> Self {
+        Self::try_new(config, network).or_panic()
+    }
+
+    // network is the initial mean, its topology and activations stay fixed
+    pub fn try_new(config: CmaEsConfig, network: Network<T>) -> Result<Self, Error> {
+        if config.sigma <= 0.0 {
+            return Err(Error::InvalidParameter("sigma must be positive"));
+        }
+        let mean = to_f64(&network.get_parameters());
+        let n = mean.len() as f64;
+        let lambda = config.population_size.unwrap_or(4 + (3.0 * n.ln()) as usize);
+        if lambda < 2 {
+            return Err(Error::InvalidParameter("CMA-ES needs a population of at least two"));
+        }
+        let mu = lambda / 2;
+        let mut weights: Vec<f64> = (1..=mu).map(|i| ((lambda as f64 + 1.0) / 2.0).ln() - (i as f64).ln()).collect();
+        let sum: f64 = weights.iter().sum();
+        weights.iter_mut().for_each(|w| *w /= sum);
+        let mu_eff = 1.0 / weights.iter().map(|w| w * w).sum::<f64>();
+
+        let c_sigma = (mu_eff + 2.0) / (n + mu_eff + 5.0);
+        let d_sigma = 1.0 + 2.0 * (((mu_eff - 1.0) / (n + 1.0)).sqrt() - 1.0).max(0.0) + c_sigma;
+        let c_c = (4.0 + mu_eff / n) / (n + 4.0 + 2.0 * mu_eff / n);
+        let c_1 = 2.0 / ((n + 1.3) * (n + 1.3) + mu_eff);
+        let c_mu = (1.0 - c_1).min(2.0 * (mu_eff - 2.0 + 1.0 / mu_eff) / ((n + 2.0) * (n + 2.0) + mu_eff));
+        Ok(Self {
+            template: network,
+            sigma: config.sigma,
+            covariance: Matrix::identity(mean.len()),
+            eigenvectors: Matrix::identity(mean.len()),
+            scales: vec![1.0; mean.len()],
+            path_sigma: vec![0.0; mean.len()],
+            path_c: vec![0.0; mean.len()],
+            mean,
+            weights,
+            mu_eff,
+            c_sigma,
+            d_sigma,
+            c_c,
+            c_1,
+            c_mu,
+            chi_n: n.sqrt() * (1.0 - 1.0 / (4.0 * n) + 1.0 / (21.0 * n * n)),
+            lambda,
+            decomposed: 0,
+            samples: Vec::new(),
+            candidates: Vec::new(),
+            generator: RandomGenerator::with_seed(config.seed),
+            generation: 0,
+            best: None,
+            history: Vec::new()
+        })
+    }
+
+    // network with the current mean as parameters
+    pub fn get_network(&self) -> Network<T> {
+        with_parameters(&self.template, &self.mean)
+    }
+
+    pub fn get_sigma(&self) -> f64 {
+        self.sigma
+    }
+
+    pub fn get_population_size(&self) -> usize {
+        self.lambda
+    }
+
+    pub fn get_generation(&self) -> usize {
+        self.generation
+    }
+
+    // the fittest candidate of all evaluated generations
+    pub fn get_best(&self) -> Option<&Individual<T>> {
+        self.best.as_ref()
+    }
+
+    pub fn get_history(&self) -> &[GenerationStats] {
+        &self.history
+    }
+
+    // the lambda networks of the current generation, asking again before tell returns the same candidates
+    pub fn ask(&mut self) -> &[Network<T>] {
+        if self.samples.is_empty() {
+            for _ in 0..self.lambda {
+                let z: Vec<f64> = self.mean.iter().map(|_| self.generator.generate_normal(0.0, 1.0)).collect();
+                let y = self.transform(&z);
+                let x: Vec<f64> = self.mean.iter().zip(y.iter()).map(|(m, y)| m + self.sigma * y).collect();
+                self.candidates.push(with_parameters(&self.template, &x));
+                self.samples.push(z);
+            }
+        }
+        &self.candidates
+    }
+
+    pub fn tell(&mut self, fitness: &[f64]) -> GenerationStats {
+        self.try_tell(fitness).or_panic()
+    }
+
+    // fitness holds one value per candidate of ask, in the same order
+    pub fn try_tell(&mut self, fitness: &[f64]) -> Result<GenerationStats, Error> {
+        if self.samples.is_empty() {
+            return Err(Error::InvalidParameter("ask must be called before tell"));
+        }
+        if fitness.len() != self.lambda {
+            return Err(Error::InvalidParameter("expected one fitness value per candidate"));
+        }
+        if fitness.iter().any(|f| f.is_nan()) {
+            return Err(Error::InvalidParameter("fitness cannot be NaN"));
+        }
+        let stats = GenerationStats::from_fitness(self.generation, fitness);
+        self.history.push(stats);
+        let mut ranking: Vec<usize> = (0..fitness.len()).collect();
+        ranking.sort_by(|a, b| fitness[*b].total_cmp(&fitness[*a]));
+        let fittest = ranking[0];
+        if self.best.as_ref().is_none_or(|best| Some(fitness[fittest]) > best.fitness) {
+            self.best = Some(Individual { network: self.candidates[fittest].clone(), fitness: Some(fitness[fittest]) });
+        }
+
+        let n = self.mean.len();
+        // weighted recombination of the mu fittest, in z and in y = B * D * z
+        let selected: Vec<Vec<f64>> = ranking.iter().take(self.weights.len()).map(|i| self.transform(&self.samples[*i])).collect();
+        let mut z_w = vec![0.0; n];
+        let mut y_w = vec![0.0; n];
+        for (weight, (index, y)) in self.weights.iter().zip(ranking.iter().zip(selected.iter())) {
+            for j in 0..n {
+                z_w[j] += weight * self.samples[*index][j];
+                y_w[j] += weight * y[j];
+            }
+        }
+        for (m, y) in self.mean.iter_mut().zip(y_w.iter()) {
+            *m += self.sigma * y;
+        }
+
+        // C^(-1/2) * y_w = B * z_w
+        let whitened = self.rotate(&z_w);
+        let factor = (self.c_sigma * (2.0 - self.c_sigma) * self.mu_eff).sqrt();
+        for (p, w) in self.path_sigma.iter_mut().zip(whitened) {
+            *p = (1.0 - self.c_sigma) * *p + factor * w;
+        }
+        let norm = self.path_sigma.iter().map(|p| p * p).sum::<f64>().sqrt();
+        let correction = (1.0 - (1.0 - self.c_sigma).powi(2 * (self.generation as i32 + 1))).sqrt();
+        // stalls the update of p_c while the step size grows fast
+        let h_sigma = norm / correction < (1.4 + 2.0 / (n as f64 + 1.0)) * self.chi_n;
+        let factor = if h_sigma { (self.c_c * (2.0 - self.c_c) * self.mu_eff).sqrt() } else { 0.0 };
+        for (p, y) in self.path_c.iter_mut().zip(y_w.iter()) {
+            *p = (1.0 - self.c_c) * *p + factor * y;
+        }
+
+        let delta = if h_sigma { 0.0 } else { self.c_c * (2.0 - self.c_c) };
+        let decay = 1.0 - self.c_1 - self.c_mu;
+        for row in 0..n {
+            for col in 0..=row {
+                let rank_one = self.path_c[row] * self.path_c[col] + delta * self.covariance[row][col];
+                let rank_mu: f64 = self.weights.iter().zip(selected.iter()).map(|(w, y)| w * y[row] * y[col]).sum();
+                let value = decay * self.covariance[row][col] + self.c_1 * rank_one + self.c_mu * rank_mu;
+                self.covariance[row][col] = value;
+                self.covariance[col][row] = value;
+            }
+        }
+        self.sigma *= ((self.c_sigma / self.d_sigma) * (norm / self.chi_n - 1.0)).exp();
+
+        self.generation += 1;
+        if (self.generation - self.decomposed) as f64 > 1.0 / ((self.c_1 + self.c_mu) * n as f64 * 10.0) {
+            self.decompose();
+        }
+        self.samples.clear();
+        self.candidates.clear();
+        Ok(stats)
+    }
+
+    // evaluates every candidate with the fitness function and updates the distribution
+    pub fn step<F: Fitness<T>>(&mut self, fitness: &F) -> GenerationStats {
+        self.ask();
+        let values: Vec<f64> = self.candidates.iter().map(|candidate| fitness.evaluate(candidate)).collect();
+        self.tell(&values)
+    }
+
+    // step evaluating the candidates on several threads (0 uses every core), the result does not depend on the thread count
+    pub fn step_parallel<F>(&mut self, fitness: &F, threads: usize) -> GenerationStats
+    where
+        F: Fitness<T> + Sync,
+        T: Send
+    {
+        self.ask();
+        let values = parallel_map(&mut self.candidates, threads, |candidate| fitness.evaluate(candidate));
+        self.tell(&values)
+    }
+
+    // B * D * z
+    fn transform(&self, z: &[f64]) -> Vec<f64> {
+        let scaled: Vec<f64> = z.iter().zip(self.scales.iter()).map(|(z, d)| z * d).collect();
+        self.rotate(&scaled)
+    }
+
+    // B * v
+    fn rotate(&self, v: &[f64]) -> Vec<f64> {
+        (0..v.len()).map(|row| self.eigenvectors[row].iter().zip(v.iter()).map(|(b, v)| b * v).sum()).collect()
+    }
+
+    fn decompose(&mut self) {
+        let (values, vectors) = symmetric_eigen(&self.covariance);
+        // rounding can push tiny eigenvalues below zero
+        self.scales = values.into_iter().map(|v| v.max(1e-20).sqrt()).collect();
+        self.eigenvectors = vectors;
+        self.decomposed = self.generation;
+    }
+}
+
+// cyclic Jacobi rotations, returns the eigenvalues and the eigenvectors as columns
+fn symmetric_eigen(matrix: &Matrix<f64>) -> (Vec<f64>, Matrix<f64>) {
+    const MAX_SWEEPS: usize = 50;
+    let n = matrix.rows();
+    let mut a = matrix.clone();
+    let mut v = Matrix::identity(n);
+    let scale: f64 = a.as_slice().iter().map(|x| x * x).sum();
+    for _ in 0..MAX_SWEEPS {
+        let off_diagonal: f64 = (0..n).flat_map(|p| (p + 1..n).map(move |q| (p, q))).map(|(p, q)| a[p][q] * a[p][q]).sum();
+        if off_diagonal <= 1e-30 * scale {
+            break;
+        }
+        for p in 0..n {
+            for q in p + 1..n {
+                if a[p][q] == 0.0 {
+                    continue;
+                }
+                let theta = (a[q][q] - a[p][p]) / (2.0 * a[p][q]);
+                let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+                let c = 1.0 / (t * t + 1.0).sqrt();
+                let s = t * c;
+                for k in 0..n {
+                    let (kp, kq) = (a[k][p], a[k][q]);
+                    a[k][p] = c * kp - s * kq;
+                    a[k][q] = s * kp + c * kq;
+                }
+                for k in 0..n {
+                    let (pk, qk) = (a[p][k], a[q][k]);
+                    a[p][k] = c * pk - s * qk;
+                    a[q][k] = s * pk + c * qk;
+                }
+                for k in 0..n {
+                    let (kp, kq) = (v[k][p], v[k][q]);
+                    v[k][p] = c * kp - s * kq;
+                    v[k][q] = s * kp + c * kq;
+                }
+            }
+        }
+    }
+    ((0..n).map(|i| a[i][i]).collect(), v)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::activation::Activation;
+    use crate::network::network_builder::NetworkBuilder;
+
+    fn create_network() -> Network<f64> {
+        NetworkBuilder::new().input(2).hidden(3, Activation::Tanh).output(1, Activation::Identity).build()
+    }
+
+    // the closer every parameter is to 0.5 the better
+    fn sphere(network: &Network<f64>) -> f64 {
+        -network.get_parameters().iter().map(|p| (p - 0.5) * (p - 0.5)).sum::<f64>()
+    }
+
+    #[test]
+    fn try_new_test() {
+        assert!(CmaEs::try_new(CmaEsConfig { sigma: 0.0, ..Default::default() }, create_network()).is_err());
+        assert!(CmaEs::try_new(CmaEsConfig { population_size: Some(1), ..Default::default() }, create_network()).is_err());
+        // 13 parameters
+        let cma = CmaEs::new(CmaEsConfig::default(), create_network());
+        assert_eq!(cma.get_population_size(), 4 + (3.0 * 13f64.ln()) as usize);
+    }
+
+    #[test]
+    fn symmetric_eigen_test() {
+        let mut matrix = Matrix::new(3, 3);
+        let values = [[4.0, 1.0, -2.0], [1.0, 2.0, 0.5], [-2.0, 0.5, 3.0]];
+        for row in 0..3 {
+            matrix[row].copy_from_slice(&values[row]);
+        }
+        let (eigenvalues, vectors) = symmetric_eigen(&matrix);
+        // A * v_i = lambda_i * v_i
+        for i in 0..3 {
+            for row in 0..3 {
+                let av: f64 = (0..3).map(|k| matrix[row][k] * vectors[k][i]).sum();
+                assert!((av - eigenvalues[i] * vectors[row][i]).abs() < 1e-10);
+            }
+        }
+        let trace: f64 = eigenvalues.iter().sum();
+        assert!((trace - 9.0).abs() < 1e-10);
+    }
+
+    #[test]
+    fn tell_test() {
+        let mut cma = CmaEs::new(CmaEsConfig { population_size: Some(4), ..Default::default() }, create_network());
+        assert!(cma.try_tell(&[1.0, 2.0, 3.0, 4.0]).is_err());
+        assert_eq!(cma.ask().len(), 4);
+        assert!(cma.try_tell(&[1.0, 2.0]).is_err());
+        let stats = cma.tell(&[1.0, 5.0, 3.0, 4.0]);
+        assert_eq!(stats.best, 5.0);
+        assert_eq!(cma.get_generation(), 1);
+        assert_eq!(cma.get_best().unwrap().fitness, Some(5.0));
+    }
+
+    #[test]
+    fn step_converges_test() {
+        let mut cma = CmaEs::new(CmaEsConfig { seed: 4, population_size: None, sigma: 0.3 }, create_network());
+        for _ in 0..300 {
+            cma.step(&sphere);
+        }
+        assert!(sphere(&cma.get_network()) > -1e-6);
+        assert!(cma.get_sigma() < 0.3);
+    }
+
+    #[test]
+    fn reproducible_test() {
+        let config = CmaEsConfig { seed: 9, ..Default::default() };
+        let mut a = CmaEs::new(config.clone(), create_network());
+        let mut b = CmaEs::new(config, create_network());
+        for _ in 0..5 {
+            a.step(&sphere);
+            b.step(&sphere);
+        }
+        assert!(a.get_network() == b.get_network());
+    }
+
+    #[test]
+    fn step_parallel_test() {
+        let mut serial = CmaEs::new(CmaEsConfig { seed: 2, ..Default::default() }, create_network());
+        let mut parallel = CmaEs::new(CmaEsConfig { seed: 2, ..Default::default() }, create_network());
+        for threads in [2, 3, 0] {
+            assert_eq!(serial.step(&sphere), parallel.step_parallel(&sphere, threads));
+        }
+        assert!(serial.get_network() == parallel.get_network());
+    }
+}
@@ -0,0 +1,317 @@
+use std::fmt;
+
+use crate::math::random::RandomGenerator;
+use crate::network::network::Network;
+use crate::evolution::fitness::Fitness;
+use crate::evolution::population::Individual;
+use crate::evolution::statistics::GenerationStats;
+use crate::evolution::parallel::parallel_map;
+use crate::error::{Error, OrPanic};
+
+/*
+    OpenAI-style evolution strategy over the flattened parameters of a single network
+
+    every generation samples n noise vectors eps_i ~ N(0, I) and evaluates the antithetic pairs
+    theta + sigma * eps_i and theta - sigma * eps_i, the fitness values are replaced by their centered ranks
+    (from -0.5 for the worst to 0.5 for the best) and the center moves along the estimated gradient
+
+    theta += learning_rate * (sum_i (rank(+eps_i) - rank(-eps_i)) * eps_i / (2 * n * sigma) - weight_decay * theta)
+
+    games that evaluate a whole generation at once use ask and tell, otherwise step does both with a fitness function
+*/
+
+#[derive(Debug, Clone)]
+pub struct EvolutionStrategyConfig {
+    pub seed: u64,
+    // number of antithetic pairs, every generation evaluates twice as many networks
+    pub pairs: usize,
+    // standard deviation of the noise
+    pub sigma: f64,
+    pub learning_rate: f64,
+    // pulls the parameters towards zero, 0 disables it
+    pub weight_decay: f64
+}
+
+impl Default for EvolutionStrategyConfig {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            pairs: 50,
+            sigma: 0.1,
+            learning_rate: 0.01,
+            weight_decay: 0.0
+        }
+    }
+}
+
+pub struct EvolutionStrategy<T> {
+    center: Network<T>,
+    theta: Vec<f64>,
+    // noise of the current generation, empty until ask is called
+    noise: Vec<Vec<f64>>,
+    candidates: Vec<Network<T>>,
+    generator: RandomGenerator<f64>,
+    config: EvolutionStrategyConfig,
+    generation: usize,
+    best: Option<Individual<T>>,
+    history: Vec<GenerationStats>
+}
+
+impl<T> EvolutionStrategy<T>
+where
+    T: num::Float + num::Signed + Default + fmt::Debug
+{
+    pub fn new(config: EvolutionStrategyConfig, network: Network<T>) -> Self {
+        Self::try_new(config, network).or_panic()
+    }
+
+    // network is the starting point, its topology and activations stay fixed
+    pub fn try_new(config: EvolutionStrategyConfig, network: Network<T>) -> Result<Self, Error> {
+        if config.pairs == 0 {
+            return Err(Error::InvalidParameter("evolution strategies need at least one antithetic pair"));
+        }
+        if config.sigma <= 0.0 || config.learning_rate <= 0.0 {
+            return Err(Error::InvalidParameter("sigma and learning rate must be positive"));
+        }
+        if config.weight_decay < 0.0 {
+            return Err(Error::InvalidParameter("weight decay cannot be negative"));
+        }
+        Ok(Self {
+            theta: to_f64(&network.get_parameters()),
+            center: network,
+            noise: Vec::new(),
+            candidates: Vec::new(),
+            generator: RandomGenerator::with_seed(config.seed),
+            config,
+            generation: 0,
+            best: None,
+            history: Vec::new()
+        })
+    }
+
+    // the current center of the search distribution
+    pub fn get_network(&self) -> &Network<T> {
+        &self.center
+    }
+
+    pub fn get_generation(&self) -> usize {
+        self.generation
+    }
+
+    // the fittest candidate of all evaluated generations
+    pub fn get_best(&self) -> Option<&Individual<T>> {
+        self.best.as_ref()
+    }
+
+    pub fn get_history(&self) -> &[GenerationStats] {
+        &self.history
+    }
+
+    // the networks of the current generation, [theta + sigma * eps_0, theta - sigma * eps_0, theta + sigma * eps_1, ...]
+    // asking again before tell returns the same candidates
+    pub fn ask(&mut self) -> &[Network<T>] {
+        if self.noise.is_empty() {
+            let sigma = self.config.sigma;
+            for _ in 0..self.config.pairs {
+                let eps: Vec<f64> = self.theta.iter().map(|_| self.generator.generate_normal(0.0, 1.0)).collect();
+                let plus: Vec<f64> = self.theta.iter().zip(eps.iter()).map(|(t, e)| t + sigma * e).collect();
+                let minus: Vec<f64> = self.theta.iter().zip(eps.iter()).map(|(t, e)| t - sigma * e).collect();
+                self.candidates.push(with_parameters(&self.center, &plus));
+                self.candidates.push(with_parameters(&self.center, &minus));
+                self.noise.push(eps);
+            }
+        }
+        &self.candidates
+    }
+
+    pub fn tell(&mut self, fitness: &[f64]) -> GenerationStats {
+        self.try_tell(fitness).or_panic()
+    }
+
+    // fitness holds one value per candidate of ask, in the same order
+    pub fn try_tell(&mut self, fitness: &[f64]) -> Result<GenerationStats, Error> {
+        if self.noise.is_empty() {
+            return Err(Error::InvalidParameter("ask must be called before tell"));
+        }
+        if fitness.len() != self.candidates.len() {
+            return Err(Error::InvalidParameter("expected one fitness value per candidate"));
+        }
+        if fitness.iter().any(|f| f.is_nan()) {
+            return Err(Error::InvalidParameter("fitness cannot be NaN"));
+        }
+        let stats = GenerationStats::from_fitness(self.generation, fitness);
+        self.history.push(stats);
+        let fittest = (0..fitness.len()).max_by(|a, b| fitness[*a].total_cmp(&fitness[*b])).unwrap();
+        if self.best.as_ref().is_none_or(|best| Some(fitness[fittest]) > best.fitness) {
+            self.best = Some(Individual { network: self.candidates[fittest].clone(), fitness: Some(fitness[fittest]) });
+        }
+
+        let ranks = centered_ranks(fitness);
+        let scale = 1.0 / (fitness.len() as f64 * self.config.sigma);
+        let mut gradient = vec![0.0; self.theta.len()];
+        for (pair, eps) in self.noise.iter().enumerate() {
+            let weight = ranks[2 * pair] - ranks[2 * pair + 1];
+            for (g, e) in gradient.iter_mut().zip(eps.iter()) {
+                *g += weight * e * scale;
+            }
+        }
+        for (t, g) in self.theta.iter_mut().zip(gradient) {
+            *t += self.config.learning_rate * (g - self.config.weight_decay * *t);
+        }
+        self.center = with_parameters(&self.center, &self.theta);
+        self.noise.clear();
+        self.candidates.clear();
+        self.generation += 1;
+        Ok(stats)
+    }
+
+    // evaluates every candidate with the fitness function and updates the center
+    pub fn step<F: Fitness<T>>(&mut self, fitness: &F) -> GenerationStats {
+        self.ask();
+        let values: Vec<f64> = self.candidates.iter().map(|candidate| fitness.evaluate(candidate)).collect();
+        self.tell(&values)
+    }
+
+    // step evaluating the candidates on several threads (0 uses every core), the result does not depend on the thread count
+    pub fn step_parallel<F>(&mut self, fitness: &F, threads: usize) -> GenerationStats
+    where
+        F: Fitness<T> + Sync,
+        T: Send
+    {
+        self.ask();
+        let values = parallel_map(&mut self.candidates, threads, |candidate| fitness.evaluate(candidate));
+        self.tell(&values)
+    }
+}
+
+// rank of every value mapped to [-0.5, 0.5], ties keep their order
+pub(crate) fn centered_ranks(values: &[f64]) -> Vec<f64> {
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_by(|a, b| values[*a].total_cmp(&values[*b]));
+    let mut ranks = vec![0.0; values.len()];
+    if values.len() > 1 {
+        for (rank, index) in order.into_iter().enumerate() {
+            ranks[index] = rank as f64 / (values.len() - 1) as f64 - 0.5;
+        }
+    }
+    ranks
+}
+
+pub(crate) fn to_f64<T: num::Float>(parameters: &[T]) -> Vec<f64> {
+    parameters.iter().map(|p| p.to_f64().unwrap()).collect()
+}
+
+// copy of the network with the given flat parameters
+pub(crate) fn with_parameters<T>(network: &Network<T>, parameters: &[f64]) -> Network<T>
+where
+    T: num::Float + Default
+{
+    let mut candidate = network.clone();
+    let parameters: Vec<T> = parameters.iter().map(|p| T::from(*p).unwrap()).collect();
+    candidate.set_parameters(&parameters);
+    candidate
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::activation::Activation;
+    use crate::network::network_builder::NetworkBuilder;
+
+    fn create_network() -> Network<f64> {
+        NetworkBuilder::new().input(2).hidden(3, Activation::Tanh).output(1, Activation::Identity).build()
+    }
+
+    // the closer every parameter is to 0.5 the better
+    fn sphere(network: &Network<f64>) -> f64 {
+        -network.get_parameters().iter().map(|p| (p - 0.5) * (p - 0.5)).sum::<f64>()
+    }
+
+    #[test]
+    fn try_new_test() {
+        let invalid = [
+            EvolutionStrategyConfig { pairs: 0, ..Default::default() },
+            EvolutionStrategyConfig { sigma: 0.0, ..Default::default() },
+            EvolutionStrategyConfig { learning_rate: -1.0, ..Default::default() },
+            EvolutionStrategyConfig { weight_decay: -0.1, ..Default::default() }
+        ];
+        for config in invalid {
+            assert!(EvolutionStrategy::try_new(config, create_network()).is_err());
+        }
+        assert!(EvolutionStrategy::try_new(EvolutionStrategyConfig::default(), create_network()).is_ok());
+    }
+
+    #[test]
+    fn centered_ranks_test() {
+        assert_eq!(centered_ranks(&[3.0, -1.0, 10.0]), vec![0.0, -0.5, 0.5]);
+        assert_eq!(centered_ranks(&[1.0, 2.0, 3.0, 100.0, 4.0]), vec![-0.5, -0.25, 0.0, 0.5, 0.25]);
+        assert_eq!(centered_ranks(&[7.0]), vec![0.0]);
+    }
+
+    #[test]
+    fn antithetic_test() {
+        let network = create_network();
+        let center = network.get_parameters();
+        let mut strategy = EvolutionStrategy::new(EvolutionStrategyConfig { pairs: 4, ..Default::default() }, network);
+        let candidates = strategy.ask().to_vec();
+        assert_eq!(candidates.len(), 8);
+        for pair in candidates.chunks(2) {
+            let (plus, minus) = (pair[0].get_parameters(), pair[1].get_parameters());
+            for i in 0..center.len() {
+                assert!((plus[i] + minus[i] - 2.0 * center[i]).abs() < 1e-12);
+            }
+            assert!(plus != center);
+        }
+        // asking twice samples nothing new
+        assert!(strategy.ask() == candidates.as_slice());
+    }
+
+    #[test]
+    fn tell_test() {
+        let mut strategy = EvolutionStrategy::new(EvolutionStrategyConfig { pairs: 2, ..Default::default() }, create_network());
+        assert!(strategy.try_tell(&[1.0, 2.0, 3.0, 4.0]).is_err());
+        strategy.ask();
+        assert!(strategy.try_tell(&[1.0, 2.0]).is_err());
+        assert!(strategy.try_tell(&[1.0, f64::NAN, 3.0, 4.0]).is_err());
+        let stats = strategy.tell(&[1.0, 2.0, 4.0, 3.0]);
+        assert_eq!(stats.best, 4.0);
+        assert_eq!(strategy.get_generation(), 1);
+        assert_eq!(strategy.get_best().unwrap().fitness, Some(4.0));
+    }
+
+    #[test]
+    fn step_improves_test() {
+        let config = EvolutionStrategyConfig { seed: 3, pairs: 20, sigma: 0.05, learning_rate: 0.05, weight_decay: 0.0 };
+        let mut strategy = EvolutionStrategy::new(config, create_network());
+        let initial = sphere(strategy.get_network());
+        for _ in 0..100 {
+            strategy.step(&sphere);
+        }
+        let last = sphere(strategy.get_network());
+        assert!(last > initial * 0.1, "{} {}", initial, last);
+        assert!(strategy.get_best().unwrap().fitness.unwrap() >= strategy.get_history()[0].best);
+    }
+
+    #[test]
+    fn reproducible_test() {
+        let config = EvolutionStrategyConfig { seed: 9, pairs: 5, ..Default::default() };
+        let mut a = EvolutionStrategy::new(config.clone(), create_network());
+        let mut b = EvolutionStrategy::new(config, create_network());
+        for _ in 0..5 {
+            a.step(&sphere);
+            b.step(&sphere);
+        }
+        assert!(a.get_network() == b.get_network());
+    }
+
+    #[test]
+    fn step_parallel_test() {
+        let config = EvolutionStrategyConfig { seed: 2, pairs: 7, ..Default::default() };
+        let mut serial = EvolutionStrategy::new(config.clone(), create_network());
+        let mut parallel = EvolutionStrategy::new(config, create_network());
+        for threads in [2, 3, 0] {
+            assert_eq!(serial.step(&sphere), parallel.step_parallel(&sphere, threads));
+        }
+        assert!(serial.get_network() == parallel.get_network());
+    }
+}
@@ -0,0 +1,19 @@
+use crate::network::network::Network;
+
+/*
+    implemented by the games, scores a single network, higher is better
+*/
+
+pub trait Fitness<T> {
+    fn evaluate(&self, network: &Network<T>) -> f64;
+}
+
+// plain closures can be used as fitness functions as well
+impl<T, F> Fitness<T> for F
+where
+    F: Fn(&Network<T>) -> f64
+{
+    fn evaluate(&self, network: &Network<T>) -> f64 {
+        self(network)
+    }
+}
@@ -0,0 +1,7 @@
+pub mod fitness;
+pub mod selection;
+pub mod statistics;
+pub mod population;
+pub mod evolution_strategy;
+pub mod cma_es;
+pub mod parallel;
@@ -0,0 +1,171 @@
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Barrier, Mutex, RwLock};
+use std::thread;
+
+/*
+    evaluates independent items on several threads
+
+    the items are split into contiguous chunks, one scoped thread per chunk, and every thread only touches
+    its own items, so the results are the same for any number of threads as long as func only depends on its item
+
+    parallel_map spawns its threads on every call, which is fine once per generation, but too slow for loops
+    that step every item many times (e.g. every tick of a game), parallel_rounds keeps its threads and chunks
+    for all rounds of such a loop and only hands them the state of the next round
+*/
+
+// number of threads the machine can run in parallel, at least one
+pub fn available_threads() -> usize {
+    thread::available_parallelism().map(|n| n.get()).unwrap_or(1)
+}
+
+// func applied to every item, the results keep the order of the items, 0 threads uses available_threads
+pub fn parallel_map<I, R, F>(items: &mut [I], threads: usize, func: F) -> Vec<R>
+where
+    I: Send,
+    R: Send,
+    F: Fn(&mut I) -> R + Sync
+{
+    let threads = if threads == 0 { available_threads() } else { threads };
+    if threads == 1 || items.len() < 2 {
+        return items.iter_mut().map(func).collect();
+    }
+    let chunk_size = items.len().div_ceil(threads);
+    let func = &func;
+    thread::scope(|scope| {
+        let handles: Vec<_> = items.chunks_mut(chunk_size)
+            .map(|chunk| scope.spawn(move || chunk.iter_mut().map(func).collect::<Vec<R>>()))
+            .collect();
+        handles.into_iter().flat_map(|handle| handle.join().expect("evaluation thread panicked")).collect()
+    })
+}
+
+// the threads of parallel_rounds wait at the barrier for the state of the next round, None ends them
+struct Shared<S, R> {
+    state: RwLock<Option<S>>,
+    // results of the last round per chunk, a panic of func is passed on to the caller of run
+    results: Vec<Mutex<Option<thread::Result<Vec<R>>>>>,
+    barrier: Barrier,
+    stopped: AtomicBool
+}
+
+impl<S, R> Shared<S, R> {
+    fn stop(&self) {
+        if !self.stopped.swap(true, Ordering::SeqCst) {
+            *self.state.write().unwrap() = None;
+            self.barrier.wait();
+        }
+    }
+}
+
+// handle of the threads of parallel_rounds, stops them when dropped
+pub struct Rounds<'a, S, R> {
+    shared: &'a Shared<S, R>
+}
+
+impl<S, R> Rounds<'_, S, R> {
+    // func applied to every item with the given state, the results keep the order of the items
+    pub fn run(&mut self, state: S) -> Vec<R> {
+        *self.shared.state.write().unwrap() = Some(state);
+        // start of the round, then its end
+        self.shared.barrier.wait();
+        self.shared.barrier.wait();
+        let mut results = Vec::new();
+        for slot in self.shared.results.iter() {
+            match slot.lock().unwrap().take() {
+                Some(Ok(chunk)) => results.extend(chunk),
+                Some(Err(payload)) => {
+                    self.shared.stop();
+                    panic::resume_unwind(payload);
+                },
+                None => {}
+            }
+        }
+        results
+    }
+}
+
+impl<S, R> Drop for Rounds<'_, S, R> {
+    fn drop(&mut self) {
+        self.shared.stop();
+    }
+}
+
+// splits the items into chunks once and starts one thread per chunk, then calls body, which can run any number
+// of rounds of func over all items, 0 threads uses available_threads
+pub fn parallel_rounds<I, S, R, F, B, T>(items: &mut [I], threads: usize, func: F, body: B) -> T
+where
+    I: Send,
+    S: Send + Sync,
+    R: Send,
+    F: Fn(&mut I, &S) -> R + Sync,
+    B: FnOnce(&mut Rounds<S, R>) -> T
+{
+    let threads = if threads == 0 { available_threads() } else { threads };
+    let chunk_size = items.len().div_ceil(threads).max(1);
+    let chunks: Vec<&mut [I]> = items.chunks_mut(chunk_size).collect();
+    let shared = Shared {
+        state: RwLock::new(None),
+        results: chunks.iter().map(|_| Mutex::new(None)).collect(),
+        barrier: Barrier::new(chunks.len() + 1),
+        stopped: AtomicBool::new(false)
+    };
+    let (func, shared) = (&func, &shared);
+    thread::scope(|scope| {
+        for (index, chunk) in chunks.into_iter().enumerate() {
+            scope.spawn(move || loop {
+                shared.barrier.wait();
+                let state = shared.state.read().unwrap();
+                let Some(state) = state.as_ref() else { break };
+                let result = panic::catch_unwind(AssertUnwindSafe(|| chunk.iter_mut().map(|item| func(item, state)).collect()));
+                *shared.results[index].lock().unwrap() = Some(result);
+                shared.barrier.wait();
+            });
+        }
+        let mut rounds = Rounds { shared };
+        body(&mut rounds)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parallel_map_test() {
+        let mut items: Vec<u64> = (0..103).collect();
+        let expected: Vec<u64> = items.iter().map(|i| i * i).collect();
+        for threads in [0, 1, 2, 7, 200] {
+            assert_eq!(parallel_map(&mut items, threads, |i| *i * *i), expected);
+        }
+        // every item is visited exactly once
+        parallel_map(&mut items, 4, |i| *i += 1);
+        assert_eq!(items, (1..104).collect::<Vec<u64>>());
+        assert!(parallel_map(&mut Vec::<u64>::new(), 4, |i| *i).is_empty());
+    }
+
+    #[test]
+    fn parallel_rounds_test() {
+        let mut items: Vec<u64> = (0..103).collect();
+        for threads in [0, 1, 2, 7, 200] {
+            let sums = parallel_rounds(&mut items, threads, |i, add: &u64| { *i += add; *i }, |rounds| {
+                (1..=3).map(|round| rounds.run(round).iter().sum::<u64>()).collect::<Vec<u64>>()
+            });
+            // every round sees the items changed by the previous ones
+            assert_eq!(sums, vec![5253 + 103, 5253 + 3 * 103, 5253 + 6 * 103]);
+            for i in items.iter_mut() {
+                *i -= 6;
+            }
+        }
+        assert_eq!(items, (0..103).collect::<Vec<u64>>());
+        let results = parallel_rounds(&mut Vec::<u64>::new(), 4, |i, _: &()| *i, |rounds| rounds.run(()));
+        assert!(results.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "item 5")]
+    fn parallel_rounds_panic_test() {
+        let mut items: Vec<u64> = (0..10).collect();
+        parallel_rounds(&mut items, 3, |i, _: &()| if *i == 5 { panic!("item 5") }, |rounds| rounds.run(()));
+    }
+}
@@ -0,0 +1,339 @@
+use std::fmt;
+
+use crate::math::random::RandomGenerator;
+use crate::network::network::Network;
+use crate::network::network_creator::NetworkCreator;
+use crate::network::network_mutator::NetworkMutator;
+use crate::network::network_crossover::NetworkCrossover;
+use crate::evolution::fitness::Fitness;
+use crate::evolution::selection::Selection;
+use crate::evolution::statistics::GenerationStats;
+use crate::evolution::parallel::parallel_map;
+use crate::error::{Error, OrPanic};
+
+/*
+    a generation of networks evolved by selection and mutation
+
+    after every individual received a fitness (evaluate or set_fitness), evolve creates the next generation:
+
+    [ elites | offspring                                | fresh ]
+      the fittest networks, unchanged
+                 mutated copies of selected parents (or of their crossover, if set)
+                                                          newly created networks
+*/
+
+pub struct PopulationConfig {
+    // number of individuals in every generation
+    pub size: usize,
+    // number of the fittest individuals copied unchanged into the next generation
+    pub elitism: usize,
+    // number of newly created individuals in every generation
+    pub fresh: usize,
+    pub selection: Selection,
+    // seed for the selection
+    pub seed: u64,
+    pub input_size: usize,
+    pub output_size: usize
+}
+
+#[derive(Clone)]
+pub struct Individual<T> {
+    pub network: Network<T>,
+    // None until the individual is evaluated
+    pub fitness: Option<f64>
+}
+
+impl<T> Individual<T> {
+    pub fn new(network: Network<T>) -> Self {
+        Self { network, fitness: None }
+    }
+}
+
+pub struct Population<T> {
+    individuals: Vec<Individual<T>>,
+    creator: NetworkCreator<T>,
+    mutator: NetworkMutator<T>,
+    crossover: Option<NetworkCrossover<T>>,
+    selection: Selection,
+    elitism: usize,
+    fresh: usize,
+    selection_generator: RandomGenerator<f64>,
+    input_size: usize,
+    output_size: usize,
+    generation: usize,
+    best: Option<Individual<T>>,
+    history: Vec<GenerationStats>
+}
+
+impl<T> Population<T>
+where
+    T: rand::distr::uniform::SampleUniform + num::Float + num::Signed + Default + fmt::Debug
+{
+    pub fn new(config: PopulationConfig, creator: NetworkCreator<T>, mutator: NetworkMutator<T>) -> Self {
+        Self::try_new(config, creator, mutator).or_panic()
+    }
+
+    pub fn try_new(config: PopulationConfig, mut creator: NetworkCreator<T>, mutator: NetworkMutator<T>) -> Result<Self, Error> {
+        if config.size == 0 {
+            return Err(Error::InvalidParameter("population size cannot be zero"));
+        }
+        if config.elitism + config.fresh > config.size {
+            return Err(Error::InvalidParameter("elites and fresh individuals cannot outnumber the population"));
+        }
+        config.selection.validate(config.size)?;
+        let mut individuals = Vec::with_capacity(config.size);
+        for _ in 0..config.size {
+            individuals.push(Individual::new(creator.try_create(config.input_size, config.output_size)?));
+        }
+        Ok(Self {
+            individuals,
+            creator,
+            mutator,
+            crossover: None,
+            selection: config.selection,
+            elitism: config.elitism,
+            fresh: config.fresh,
+            selection_generator: RandomGenerator::with_seed(config.seed),
+            input_size: config.input_size,
+            output_size: config.output_size,
+            generation: 0,
+            best: None,
+            history: Vec::new()
+        })
+    }
+
+    pub fn individuals(&self) -> &[Individual<T>] {
+        &self.individuals
+    }
+
+    pub fn individuals_mut(&mut self) -> &mut [Individual<T>] {
+        &mut self.individuals
+    }
+
+    pub fn size(&self) -> usize {
+        self.individuals.len()
+    }
+
+    // number of generations evolved so far
+    pub fn get_generation(&self) -> usize {
+        self.generation
+    }
+
+    // the fittest individual of all evaluated generations
+    pub fn get_best(&self) -> Option<&Individual<T>> {
+        self.best.as_ref()
+    }
+
+    // statistics of every evolved generation, oldest first
+    pub fn get_history(&self) -> &[GenerationStats] {
+        &self.history
+    }
+
+    pub fn get_mutator_mut(&mut self) -> &mut NetworkMutator<T> {
+        &mut self.mutator
+    }
+
+    // offspring are bred from two selected parents instead of copying one
+    pub fn set_crossover(&mut self, crossover: NetworkCrossover<T>) {
+        self.crossover = Some(crossover);
+    }
+
+    // for games evaluating all individuals at once, e.g. every bird in the same run
+    pub fn set_fitness(&mut self, index: usize, fitness: f64) {
+        self.individuals[index].fitness = Some(fitness);
+    }
+
+    pub fn evaluate<F: Fitness<T>>(&mut self, fitness: &F) {
+        for individual in self.individuals.iter_mut() {
+            individual.fitness = Some(fitness.evaluate(&individual.network));
+        }
+    }
+
+    // evaluate on several threads (0 uses every core), gives the same fitness values as evaluate
+    pub fn evaluate_parallel<F>(&mut self, fitness: &F, threads: usize)
+    where
+        F: Fitness<T> + Sync,
+        T: Send
+    {
+        parallel_map(&mut self.individuals, threads, |individual| {
+            individual.fitness = Some(fitness.evaluate(&individual.network));
+        });
+    }
+
+    pub fn evolve(&mut self) -> GenerationStats {
+        self.try_evolve().or_panic()
+    }
+
+    // replaces the current generation with the next one and returns the statistics of the current one
+    pub fn try_evolve(&mut self) -> Result<GenerationStats, Error> {
+        let mut fitness = Vec::with_capacity(self.individuals.len());
+        for individual in self.individuals.iter() {
+            match individual.fitness {
+                Some(value) if value.is_nan() => return Err(Error::InvalidParameter("fitness cannot be NaN")),
+                Some(value) => fitness.push(value),
+                None => return Err(Error::InvalidParameter("every individual must be evaluated before evolving"))
+            }
+        }
+        let stats = GenerationStats::from_fitness(self.generation, &fitness);
+        self.history.push(stats);
+
+        let mut ranking: Vec<usize> = (0..fitness.len()).collect();
+        ranking.sort_by(|a, b| fitness[*b].total_cmp(&fitness[*a]));
+        let fittest = &self.individuals[ranking[0]];
+        if self.best.as_ref().is_none_or(|best| fittest.fitness > best.fitness) {
+            self.best = Some(fittest.clone());
+        }
+
+        let size = self.individuals.len();
+        let mut next = Vec::with_capacity(size);
+        for &index in ranking.iter().take(self.elitism) {
+            next.push(Individual::new(self.individuals[index].network.clone()));
+        }
+        while next.len() < size - self.fresh {
+            let parent = self.selection.select(&fitness, &ranking, &mut self.selection_generator);
+            let mut network = match self.crossover.as_mut() {
+                Some(crossover) => {
+                    let other = self.selection.select(&fitness, &ranking, &mut self.selection_generator);
+                    crossover.crossover(&self.individuals[parent].network, &self.individuals[other].network)
+                },
+                None => self.individuals[parent].network.clone()
+            };
+            self.mutator.mutate(&mut network);
+            next.push(Individual::new(network));
+        }
+        while next.len() < size {
+            next.push(Individual::new(self.creator.try_create(self.input_size, self.output_size)?));
+        }
+        self.individuals = next;
+        self.generation += 1;
+        Ok(stats)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::matrix::Matrix;
+    use crate::network::network_mutator::NetworkMutatorConfig;
+    use crate::network::network_crossover::CrossoverKind;
+
+    fn create_population(size: usize, elitism: usize, fresh: usize, selection: Selection) -> Result<Population<f32>, Error> {
+        let config = PopulationConfig { size, elitism, fresh, selection, seed: 5, input_size: 2, output_size: 1 };
+        let creator = NetworkCreator::<f32>::new(1, -1.0, 1.0, 2, 1, 3, 3, 2, 6);
+        let mutator = NetworkMutator::new(NetworkMutatorConfig {
+            random_generator_seed: 4,
+            weight_mutation_probability: 0.3,
+            weight_change_upper_limit: 0.3,
+            weight_change_lower_limit: -0.3,
+            ..Default::default()
+        });
+        Population::try_new(config, creator, mutator)
+    }
+
+    // rewards networks whose output for (1, 1) is close to 0.5
+    fn target_fitness(network: &Network<f32>) -> f64 {
+        let mut input = Matrix::<f32>::new(1, 2);
+        input[0][0] = 1.0;
+        input[0][1] = 1.0;
+        let output = network.forward(&input);
+        -((output[0][0] - 0.5).abs() as f64)
+    }
+
+    #[test]
+    fn try_new_test() {
+        assert!(create_population(0, 0, 0, Selection::Rank).is_err());
+        assert!(create_population(10, 6, 5, Selection::Rank).is_err());
+        assert!(create_population(10, 2, 2, Selection::Truncation(11)).is_err());
+        let population = create_population(10, 2, 2, Selection::Tournament(3)).unwrap();
+        assert_eq!(population.size(), 10);
+        assert!(population.individuals().iter().all(|i| i.fitness.is_none()));
+    }
+
+    #[test]
+    fn evolve_without_fitness_test() {
+        let mut population = create_population(4, 1, 1, Selection::Rank).unwrap();
+        population.set_fitness(0, 1.0);
+        assert!(population.try_evolve().is_err());
+        for i in 0..4 {
+            population.set_fitness(i, f64::NAN);
+        }
+        assert!(population.try_evolve().is_err());
+    }
+
+    #[test]
+    fn elitism_test() {
+        let mut population = create_population(6, 2, 1, Selection::Truncation(3)).unwrap();
+        for i in 0..6 {
+            population.set_fitness(i, i as f64);
+        }
+        let best = population.individuals()[5].network.clone();
+        let second = population.individuals()[4].network.clone();
+        let stats = population.evolve();
+        assert_eq!(stats.best, 5.0);
+        assert_eq!(stats.generation, 0);
+        assert!(population.individuals()[0].network == best);
+        assert!(population.individuals()[1].network == second);
+        assert!(population.get_best().unwrap().network == best);
+        assert_eq!(population.get_generation(), 1);
+        assert_eq!(population.size(), 6);
+    }
+
+    #[test]
+    fn evolution_improves_test() {
+        for selection in [Selection::Truncation(5), Selection::Tournament(3), Selection::Roulette, Selection::Rank] {
+            let mut population = create_population(30, 2, 3, selection).unwrap();
+            for _ in 0..25 {
+                population.evaluate(&target_fitness);
+                population.evolve();
+            }
+            let history = population.get_history();
+            assert_eq!(history.len(), 25);
+            // elitism keeps the best fitness from getting worse
+            for window in history.windows(2) {
+                assert!(window[1].best >= window[0].best, "{:?}", selection);
+            }
+            assert!(history[24].best > history[0].best || history[0].best > -1e-3, "{:?}", selection);
+        }
+    }
+
+    #[test]
+    fn crossover_test() {
+        let mut population = create_population(20, 2, 2, Selection::Tournament(3)).unwrap();
+        population.set_crossover(NetworkCrossover::new(6, CrossoverKind::Uniform));
+        for _ in 0..10 {
+            population.evaluate(&target_fitness);
+            population.evolve();
+        }
+        let history = population.get_history();
+        assert!(history[9].best >= history[0].best);
+        assert_eq!(population.size(), 20);
+    }
+
+    #[test]
+    fn reproducible_test() {
+        let mut first = create_population(10, 1, 2, Selection::Tournament(2)).unwrap();
+        let mut second = create_population(10, 1, 2, Selection::Tournament(2)).unwrap();
+        for _ in 0..5 {
+            first.evaluate(&target_fitness);
+            second.evaluate(&target_fitness);
+            assert_eq!(first.evolve(), second.evolve());
+        }
+        for (a, b) in first.individuals().iter().zip(second.individuals().iter()) {
+            assert!(a.network == b.network);
+        }
+    }
+
+    #[test]
+    fn evaluate_parallel_test() {
+        let mut serial = create_population(13, 1, 2, Selection::Tournament(2)).unwrap();
+        let mut parallel: Vec<Population<f32>> = (0..3).map(|_| create_population(13, 1, 2, Selection::Tournament(2)).unwrap()).collect();
+        for _ in 0..3 {
+            serial.evaluate(&target_fitness);
+            let expected = serial.evolve();
+            for (population, threads) in parallel.iter_mut().zip([0, 2, 5]) {
+                population.evaluate_parallel(&target_fitness, threads);
+                assert_eq!(population.evolve(), expected);
+            }
+        }
+    }
+}
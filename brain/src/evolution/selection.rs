@@ -0,0 +1,155 @@
+use crate::math::random::RandomGenerator;
+use crate::error::Error;
+
+/*
+    parent selection strategies, all of them prefer individuals with higher fitness
+
+    Truncation(n)   uniformly from the n fittest
+    Tournament(k)   the fittest of k uniformly drawn individuals (with replacement)
+    Roulette        proportional to the fitness (shifted to be non-negative)
+    Rank            proportional to the rank, the worst has weight 1 and the best weight n
+*/
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Selection {
+    Truncation(usize),
+    Tournament(usize),
+    Roulette,
+    Rank
+}
+
+impl Selection {
+    pub fn validate(&self, population_size: usize) -> Result<(), Error> {
+        match self {
+            Selection::Truncation(n) if *n == 0 || *n > population_size => {
+                Err(Error::InvalidParameter("truncation size must be in [1, population size]"))
+            },
+            Selection::Tournament(0) => Err(Error::InvalidParameter("tournament size cannot be zero")),
+            _ => Ok(())
+        }
+    }
+
+    // returns the index of the selected individual
+    // ranking holds the indices of the individuals sorted from the fittest to the least fit
+    pub fn select(&self, fitness: &[f64], ranking: &[usize], generator: &mut RandomGenerator<f64>) -> usize {
+        let n = fitness.len();
+        match self {
+            Selection::Truncation(size) => ranking[random_index((*size).min(n), generator)],
+            Selection::Tournament(size) => {
+                let mut winner = random_index(n, generator);
+                for _ in 1..*size {
+                    let contender = random_index(n, generator);
+                    if fitness[contender] > fitness[winner] {
+                        winner = contender;
+                    }
+                }
+                winner
+            },
+            Selection::Roulette => {
+                let min = fitness.iter().cloned().fold(f64::INFINITY, f64::min);
+                let offset = if min < 0.0 { -min } else { 0.0 };
+                let weights: Vec<f64> = fitness.iter().map(|f| f + offset).collect();
+                spin(&weights, generator)
+            },
+            Selection::Rank => {
+                let mut weights = vec![0.0; n];
+                for (position, &index) in ranking.iter().enumerate() {
+                    weights[index] = (n - position) as f64;
+                }
+                spin(&weights, generator)
+            }
+        }
+    }
+}
+
+fn random_index(n: usize, generator: &mut RandomGenerator<f64>) -> usize {
+    ((generator.generate(0.0, 1.0) * n as f64) as usize).min(n - 1)
+}
+
+// picks an index with probability proportional to its weight, uniformly if every weight is zero
+fn spin(weights: &[f64], generator: &mut RandomGenerator<f64>) -> usize {
+    let total: f64 = weights.iter().sum();
+    if total <= 0.0 {
+        return random_index(weights.len(), generator);
+    }
+    let mut target = generator.generate(0.0, 1.0) * total;
+    for (index, weight) in weights.iter().enumerate() {
+        if target < *weight {
+            return index;
+        }
+        target -= weight;
+    }
+    // rounding errors can leave a tiny remainder
+    weights.iter().rposition(|w| *w > 0.0).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ranking(fitness: &[f64]) -> Vec<usize> {
+        let mut ranking: Vec<usize> = (0..fitness.len()).collect();
+        ranking.sort_by(|a, b| fitness[*b].total_cmp(&fitness[*a]));
+        ranking
+    }
+
+    fn histogram(selection: Selection, fitness: &[f64], draws: usize) -> Vec<usize> {
+        let ranking = ranking(fitness);
+        let mut generator = RandomGenerator::with_seed(9);
+        let mut counts = vec![0; fitness.len()];
+        for _ in 0..draws {
+            counts[selection.select(fitness, &ranking, &mut generator)] += 1;
+        }
+        counts
+    }
+
+    #[test]
+    fn truncation_test() {
+        let counts = histogram(Selection::Truncation(2), &[1.0, 5.0, 3.0, 4.0], 1000);
+        assert_eq!(counts[0], 0);
+        assert_eq!(counts[2], 0);
+        assert!(counts[1] > 400 && counts[3] > 400);
+    }
+
+    #[test]
+    fn tournament_test() {
+        let counts = histogram(Selection::Tournament(3), &[1.0, 2.0, 3.0, 4.0], 4000);
+        // the worst individual only wins if it is drawn three times
+        assert!(counts[0] < 150);
+        assert!(counts[3] > counts[2] && counts[2] > counts[1] && counts[1] > counts[0]);
+        let counts = histogram(Selection::Tournament(1), &[1.0, 2.0, 3.0, 4.0], 4000);
+        assert!(counts.iter().all(|c| *c > 850));
+    }
+
+    #[test]
+    fn roulette_test() {
+        let counts = histogram(Selection::Roulette, &[0.0, 1.0, 3.0], 4000);
+        assert_eq!(counts[0], 0);
+        assert!((counts[2] as f64 / counts[1] as f64 - 3.0).abs() < 0.4);
+        // negative values are shifted, the worst one never gets picked
+        let counts = histogram(Selection::Roulette, &[-2.0, -1.0, 1.0], 1000);
+        assert_eq!(counts[0], 0);
+        // all equal to zero falls back to uniform
+        let counts = histogram(Selection::Roulette, &[0.0, 0.0], 1000);
+        assert!(counts[0] > 400 && counts[1] > 400);
+    }
+
+    #[test]
+    fn rank_test() {
+        // the fitness differences do not matter, only the order
+        let counts = histogram(Selection::Rank, &[1000.0, 1.0, 2.0], 6000);
+        assert!((counts[0] as f64 / 6000.0 - 0.5).abs() < 0.03);
+        assert!((counts[2] as f64 / 6000.0 - 1.0 / 3.0).abs() < 0.03);
+        assert!((counts[1] as f64 / 6000.0 - 1.0 / 6.0).abs() < 0.03);
+    }
+
+    #[test]
+    fn validate_test() {
+        assert!(Selection::Truncation(0).validate(10).is_err());
+        assert!(Selection::Truncation(11).validate(10).is_err());
+        assert!(Selection::Truncation(10).validate(10).is_ok());
+        assert!(Selection::Tournament(0).validate(10).is_err());
+        assert!(Selection::Tournament(20).validate(10).is_ok());
+        assert!(Selection::Roulette.validate(1).is_ok());
+    }
+}
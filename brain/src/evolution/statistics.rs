@@ -0,0 +1,54 @@
+/*
+    summary of the fitness values of one generation
+*/
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GenerationStats {
+    pub generation: usize,
+    pub best: f64,
+    pub worst: f64,
+    pub mean: f64,
+    pub median: f64,
+    pub std_dev: f64
+}
+
+impl GenerationStats {
+    // fitness must not be empty
+    pub fn from_fitness(generation: usize, fitness: &[f64]) -> Self {
+        assert!(!fitness.is_empty(), "cannot compute statistics of an empty generation");
+        let mut sorted = fitness.to_vec();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        let n = sorted.len();
+        let mean = sorted.iter().sum::<f64>() / n as f64;
+        let variance = sorted.iter().map(|f| (f - mean) * (f - mean)).sum::<f64>() / n as f64;
+        let median = if n.is_multiple_of(2) { (sorted[n / 2 - 1] + sorted[n / 2]) / 2.0 } else { sorted[n / 2] };
+        Self {
+            generation,
+            best: sorted[n - 1],
+            worst: sorted[0],
+            mean,
+            median,
+            std_dev: variance.sqrt()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_fitness_test() {
+        let stats = GenerationStats::from_fitness(3, &[4.0, 1.0, 3.0, 2.0]);
+        assert_eq!(stats.generation, 3);
+        assert_eq!(stats.best, 4.0);
+        assert_eq!(stats.worst, 1.0);
+        assert_eq!(stats.mean, 2.5);
+        assert_eq!(stats.median, 2.5);
+        assert!((stats.std_dev - 1.25_f64.sqrt()).abs() < 1e-12);
+
+        let stats = GenerationStats::from_fitness(0, &[5.0, -1.0, 2.0]);
+        assert_eq!(stats.median, 2.0);
+        assert_eq!(stats.best, 5.0);
+    }
+}
@@ -1,6 +1,34 @@
 pub mod math; // include the math/mod.rs
+pub mod error;
 pub mod network;
+pub mod training;
+pub mod evolution;
+pub mod neat;
+pub mod brain;
 
+pub use error::Error;
+pub use brain::Brain;
 pub use math::matrix::Matrix;
+pub use math::activation::Activation;
 pub use math::random::{RandomGenerator, RandomGeneratorConfig};
-pub use network::network::Network;
\ No newline at end of file
+pub use network::network::{Network, ForwardScratch, ParameterKind, ParameterIndex, ParameterMut};
+pub use network::network_builder::{NetworkBuilder, LayerSpec};
+pub use network::network_initializer::{NetworkInitializer, InitScheme};
+pub use network::network_crossover::{NetworkCrossover, CrossoverKind};
+pub use network::network_mutator::{NetworkMutator, NetworkMutatorConfig};
+pub use network::network_creator::{NetworkCreator, TopologyLimits};
+pub use network::network_serializer::{NetworkSerializer, SerializationError};
+pub use training::loss::Loss;
+pub use training::gradients::Gradients;
+pub use training::optimizer::{Optimizer, Sgd, Adam};
+pub use training::trainer::Trainer;
+pub use evolution::fitness::Fitness;
+pub use evolution::selection::Selection;
+pub use evolution::statistics::GenerationStats;
+pub use evolution::population::{Population, PopulationConfig, Individual};
+pub use evolution::evolution_strategy::{EvolutionStrategy, EvolutionStrategyConfig};
+pub use evolution::cma_es::{CmaEs, CmaEsConfig};
+pub use neat::innovation::InnovationTracker;
+pub use neat::genome::{Genome, NodeGene, NodeKind, ConnectionGene, CompatibilityCoefficients};
+pub use neat::genome_mutator::{GenomeMutator, GenomeMutatorConfig};
+pub use neat::speciation::{Speciation, Species};
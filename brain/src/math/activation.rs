@@ -0,0 +1,259 @@
+use crate::math::matrix::Matrix;
+use crate::error::{Error, OrPanic};
+
+/*
+    activation functions applied to the output of a layer (x * W + b)
+    most of them are element-wise, softmax is applied to each row as a whole
+*/
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Activation {
+    #[default]
+    ReLU,
+    LeakyReLU(f64), // slope for negative inputs, e.g. 0.01
+    Sigmoid,
+    Tanh,
+    Identity,
+    Softmax,
+    GELU
+}
+
+impl Activation {
+    // applies the activation function to a single value
+    pub fn compute<T: num::Float>(&self, x: T) -> T {
+        self.try_compute(x).or_panic()
+    }
+
+    // softmax needs the whole row and cannot be computed element-wise, use apply instead
+    pub fn try_compute<T: num::Float>(&self, x: T) -> Result<T, Error> {
+        Ok(match self {
+            Activation::ReLU => x.max(T::zero()),
+            Activation::LeakyReLU(alpha) => {
+                if x > T::zero() { x } else { x * T::from(*alpha).unwrap() }
+            },
+            Activation::Sigmoid => T::one() / (T::one() + (-x).exp()),
+            Activation::Tanh => x.tanh(),
+            Activation::Identity => x,
+            Activation::Softmax => return Err(Error::InvalidParameter("softmax cannot be applied element-wise")),
+            Activation::GELU => {
+                // tanh approximation: 0.5 * x * (1 + tanh(sqrt(2 / pi) * (x + 0.044715 * x^3)))
+                let half = T::from(0.5).unwrap();
+                let coefficient = T::from((2.0 / std::f64::consts::PI).sqrt()).unwrap();
+                let cubic = T::from(0.044715).unwrap();
+                half * x * (T::one() + (coefficient * (x + cubic * x * x * x)).tanh())
+            }
+        })
+    }
+
+    // derivative of the activation function at the (pre-activation) value x
+    pub fn derivative<T: num::Float>(&self, x: T) -> T {
+        self.try_derivative(x).or_panic()
+    }
+
+    // the softmax derivative is a jacobian of the whole row, use backpropagate instead
+    pub fn try_derivative<T: num::Float>(&self, x: T) -> Result<T, Error> {
+        Ok(match self {
+            Activation::ReLU => if x > T::zero() { T::one() } else { T::zero() },
+            Activation::LeakyReLU(alpha) => {
+                if x > T::zero() { T::one() } else { T::from(*alpha).unwrap() }
+            },
+            Activation::Sigmoid => {
+                let s = self.try_compute(x)?;
+                s * (T::one() - s)
+            },
+            Activation::Tanh => T::one() - x.tanh() * x.tanh(),
+            Activation::Identity => T::one(),
+            Activation::Softmax => return Err(Error::InvalidParameter("softmax cannot be applied element-wise")),
+            Activation::GELU => {
+                let half = T::from(0.5).unwrap();
+                let coefficient = T::from((2.0 / std::f64::consts::PI).sqrt()).unwrap();
+                let cubic = T::from(0.044715).unwrap();
+                let three = T::from(3.0).unwrap();
+                let t = (coefficient * (x + cubic * x * x * x)).tanh();
+                half * (T::one() + t) + half * x * (T::one() - t * t) * coefficient * (T::one() + three * cubic * x * x)
+            }
+        })
+    }
+
+    // applies the activation function to every row of the matrix in place
+    pub fn apply<T: num::Float>(&self, matrix: &mut Matrix<T>) {
+        match self {
+            Activation::Softmax => {
+                for row in 0..matrix.rows() {
+                    // subtract the maximum for numerical stability
+                    let max = matrix[row].iter().fold(T::neg_infinity(), |acc, &x| acc.max(x));
+                    let mut sum = T::zero();
+                    for elem in matrix[row].iter_mut() {
+                        *elem = (*elem - max).exp();
+                        sum = sum + *elem;
+                    }
+                    for elem in matrix[row].iter_mut() {
+                        *elem = *elem / sum;
+                    }
+                }
+            },
+            _ => matrix.apply(|x| self.compute(x))
+        }
+    }
+
+    // chain rule through the activation: takes the gradient of the loss with respect to the activated values
+    // and returns the gradient with respect to the pre-activation values
+    pub fn backpropagate<T: num::Float + Default>(&self, pre_activation: &Matrix<T>, activated: &Matrix<T>, gradient: &Matrix<T>) -> Matrix<T> {
+        let mut result = Matrix::new(gradient.rows(), gradient.cols());
+        match self {
+            Activation::Softmax => {
+                // jacobian-vector product of softmax: s_i * (g_i - sum_j(g_j * s_j))
+                for row in 0..gradient.rows() {
+                    let mut dot = T::zero();
+                    for col in 0..gradient.cols() {
+                        dot = dot + gradient[row][col] * activated[row][col];
+                    }
+                    for col in 0..gradient.cols() {
+                        result[row][col] = activated[row][col] * (gradient[row][col] - dot);
+                    }
+                }
+            },
+            _ => {
+                for row in 0..gradient.rows() {
+                    for col in 0..gradient.cols() {
+                        result[row][col] = gradient[row][col] * self.derivative(pre_activation[row][col]);
+                    }
+                }
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_relu() {
+        assert_eq!(Activation::ReLU.compute(-2.0_f64), 0.0);
+        assert_eq!(Activation::ReLU.compute(3.0_f64), 3.0);
+    }
+
+    #[test]
+    fn test_leaky_relu() {
+        assert!((Activation::LeakyReLU(0.1).compute(-2.0_f64) - (-0.2)).abs() < 1e-12);
+        assert_eq!(Activation::LeakyReLU(0.1).compute(3.0_f64), 3.0);
+    }
+
+    #[test]
+    fn test_sigmoid() {
+        assert!((Activation::Sigmoid.compute(0.0_f64) - 0.5).abs() < 1e-12);
+        assert!(Activation::Sigmoid.compute(100.0_f64) <= 1.0);
+        assert!(Activation::Sigmoid.compute(-100.0_f64) >= 0.0);
+    }
+
+    #[test]
+    fn test_tanh() {
+        assert_eq!(Activation::Tanh.compute(0.0_f64), 0.0);
+        assert!((Activation::Tanh.compute(1.0_f64) - 0.7615941559557649).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_identity() {
+        assert_eq!(Activation::Identity.compute(-7.5_f64), -7.5);
+    }
+
+    #[test]
+    fn test_gelu() {
+        assert_eq!(Activation::GELU.compute(0.0_f64), 0.0);
+        assert!((Activation::GELU.compute(1.0_f64) - 0.8411919906082768).abs() < 1e-9);
+        assert!((Activation::GELU.compute(-1.0_f64) - (-0.15880800939172324)).abs() < 1e-9);
+    }
+
+    #[test]
+    #[should_panic(expected = "softmax cannot be applied element-wise")]
+    fn test_softmax_element_wise() {
+        Activation::Softmax.compute(1.0_f64);
+    }
+
+    #[test]
+    fn test_try_softmax_element_wise() {
+        assert!(matches!(Activation::Softmax.try_compute(1.0_f64), Err(Error::InvalidParameter(_))));
+        assert!(matches!(Activation::Softmax.try_derivative(1.0_f64), Err(Error::InvalidParameter(_))));
+        assert_eq!(Activation::Tanh.try_compute(0.0_f64), Ok(0.0));
+        assert_eq!(Activation::Identity.try_derivative(2.0_f64), Ok(1.0));
+    }
+
+    #[test]
+    fn test_softmax() {
+        let mut m = Matrix::<f64>::new(2, 3);
+        m[0][0] = 1.0;
+        m[0][1] = 2.0;
+        m[0][2] = 3.0;
+        m[1][0] = 1000.0;
+        m[1][1] = 1000.0;
+        m[1][2] = 1000.0;
+        Activation::Softmax.apply(&mut m);
+        assert!((m[0][0] - 0.09003057317038046).abs() < 1e-12);
+        assert!((m[0][1] - 0.24472847105479764).abs() < 1e-12);
+        assert!((m[0][2] - 0.6652409557748219).abs() < 1e-12);
+        for col in 0..3 {
+            assert!((m[1][col] - 1.0 / 3.0).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_apply_element_wise() {
+        let mut m = Matrix::<f32>::new(1, 2);
+        m[0][0] = -1.0;
+        m[0][1] = 2.0;
+        Activation::ReLU.apply(&mut m);
+        assert_eq!(m[0][0], 0.0);
+        assert_eq!(m[0][1], 2.0);
+    }
+
+    #[test]
+    fn test_derivatives() {
+        // compare against central differences
+        let h = 1e-6;
+        let activations = [Activation::ReLU, Activation::LeakyReLU(0.1), Activation::Sigmoid, Activation::Tanh, Activation::Identity, Activation::GELU];
+        for activation in activations.iter() {
+            for &x in [-1.5_f64, -0.3, 0.4, 2.0].iter() {
+                let numeric = (activation.compute(x + h) - activation.compute(x - h)) / (2.0 * h);
+                assert!((activation.derivative(x) - numeric).abs() < 1e-6, "{:?} at {}", activation, x);
+            }
+        }
+    }
+
+    #[test]
+    fn test_softmax_backpropagate() {
+        let mut z = Matrix::<f64>::new(1, 3);
+        z[0][0] = 0.5;
+        z[0][1] = -1.0;
+        z[0][2] = 2.0;
+        let mut s = z.clone();
+        Activation::Softmax.apply(&mut s);
+        let mut g = Matrix::<f64>::new(1, 3);
+        g[0][0] = 1.0;
+        g[0][1] = -2.0;
+        g[0][2] = 0.5;
+        let result = Activation::Softmax.backpropagate(&z, &s, &g);
+        // the gradients of the softmax inputs sum up to zero
+        assert!((result[0][0] + result[0][1] + result[0][2]).abs() < 1e-12);
+        let h = 1e-6;
+        for col in 0..3 {
+            let mut plus = z.clone();
+            plus[0][col] += h;
+            Activation::Softmax.apply(&mut plus);
+            let mut minus = z.clone();
+            minus[0][col] -= h;
+            Activation::Softmax.apply(&mut minus);
+            let mut numeric = 0.0;
+            for k in 0..3 {
+                numeric += g[0][k] * (plus[0][k] - minus[0][k]) / (2.0 * h);
+            }
+            assert!((result[0][col] - numeric).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_default() {
+        assert_eq!(Activation::default(), Activation::ReLU);
+    }
+}
@@ -1,5 +1,5 @@
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Dimensions {
     pub rows: usize,
     pub cols: usize
@@ -1,16 +1,27 @@
 use std::ops::{Add, Sub, Mul, Div, Index, IndexMut, MulAssign, DivAssign}; // +, -, *, /, [], *=, /=
 use std::fmt;
 use crate::math::dimensions::Dimensions;
+use crate::math::random::RandomGenerator;
+use crate::error::{Error, OrPanic};
 
 /*
 nxm = 2x3
     a_00 a_01 a_02
     a_10 a_11 a_12
+
+the elements are stored in a single contiguous buffer, row by row (row-major)
+    data = [a_00 a_01 a_02 a_10 a_11 a_12]
+element [row][col] lives at data[row * stride + col], where the stride is the number of columns
 */
 
+// edge length of the tiles used by the matrix multiplication, chosen so that a few tiles fit into the L1 cache
+const BLOCK_SIZE: usize = 64;
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct Matrix<T> {
-    data: Vec<Vec<T>>,
+    data: Vec<T>,
+    rows: usize,
+    cols: usize,
 }
 
 impl<T> Matrix<T>
@@ -19,12 +30,34 @@ where
 {
     // constructor
     pub fn new(rows: usize, cols: usize) -> Self {
+        Self::try_new(rows, cols).or_panic()
+    }
+
+    pub fn try_new(rows: usize, cols: usize) -> Result<Self, Error> {
         if rows == 0 || cols == 0 {
-            panic!("matrix dimensions cannot be zero");
-        }
-        Self {
-            data: vec![vec![T::default(); cols]; rows],
+            return Err(Error::ZeroDimension);
         }
+        Ok(Self {
+            data: vec![T::default(); rows * cols],
+            rows,
+            cols,
+        })
+    }
+}
+
+impl<T> Matrix<T> {
+    // offset of the first element of a row in the buffer
+    fn offset(&self, row: usize) -> usize {
+        row * self.cols
+    }
+
+    // the underlying row-major buffer
+    pub fn as_slice(&self) -> &[T] {
+        &self.data
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        &mut self.data
     }
 }
 
@@ -34,17 +67,12 @@ where
 {
     // getter for the number of rows
     pub fn rows(&self) -> usize {
-        self.data.len()
+        self.rows
     }
 
     // getter for the number of columns
     pub fn cols(&self) -> usize {
-        if self.data.is_empty() {
-            0
-        }
-        else {
-            self.data[0].len()
-        }
+        self.cols
     }
 
     pub fn get_dimensions(&self) -> Dimensions {
@@ -53,16 +81,68 @@ where
 
     // get element [row][col]
     pub fn get(&self, row: usize, col: usize) -> T {
-        self.data[row][col]
+        self.try_get(row, col).or_panic()
+    }
+
+    pub fn try_get(&self, row: usize, col: usize) -> Result<T, Error> {
+        self.check_valid_element(row, col)?;
+        Ok(self.data[self.offset(row) + col])
     }
 
     // set element [row][col]
     pub fn set(&mut self, row: usize, col: usize, value: T) {
-        self.data[row][col] = value;
+        self.try_set(row, col, value).or_panic()
+    }
+
+    pub fn try_set(&mut self, row: usize, col: usize, value: T) -> Result<(), Error> {
+        self.check_valid_element(row, col)?;
+        let offset = self.offset(row);
+        self.data[offset + col] = value;
+        Ok(())
     }
 
     pub fn assert_valid_element(&self, row: usize, col: usize) {
-        assert!(row < self.rows() && col < self.cols(), "Invalid matrix indices");
+        self.check_valid_element(row, col).or_panic()
+    }
+
+    pub fn check_valid_element(&self, row: usize, col: usize) -> Result<(), Error> {
+        if row < self.rows() && col < self.cols() {
+            Ok(())
+        } else {
+            Err(Error::IndexOutOfRange { row, col, dimensions: self.get_dimensions() })
+        }
+    }
+
+    // both operands must have the same dimensions
+    fn check_same_dimensions(&self, other: &Matrix<T>, operation: &'static str) -> Result<(), Error> {
+        if self.rows() != other.rows() || self.cols() != other.cols() {
+            return Err(Error::DimensionMismatch { operation, left: self.get_dimensions(), right: other.get_dimensions() });
+        }
+        Ok(())
+    }
+
+    // element-wise combination of two matrices of the same size
+    fn zip_with<F>(&self, other: &Matrix<T>, func: F) -> Matrix<T>
+    where
+        F: Fn(T, T) -> T,
+    {
+        Matrix {
+            data: self.data.iter().zip(&other.data).map(|(a, b)| func(*a, *b)).collect(),
+            rows: self.rows,
+            cols: self.cols,
+        }
+    }
+
+    // applies the function to every element and returns the result as a new matrix
+    fn map_elements<F>(&self, func: F) -> Matrix<T>
+    where
+        F: Fn(T) -> T,
+    {
+        Matrix {
+            data: self.data.iter().map(|a| func(*a)).collect(),
+            rows: self.rows,
+            cols: self.cols,
+        }
     }
 }
 
@@ -70,14 +150,18 @@ where
 impl<T> Index<usize> for Matrix<T> {
     type Output = [T];
     fn index(&self, row: usize) -> &Self::Output {
-        &self.data[row]
+        assert!(row < self.rows, "Invalid matrix indices [{}] for a matrix with {} rows", row, self.rows);
+        let offset = self.offset(row);
+        &self.data[offset..offset + self.cols]
     }
 }
 
 // IndexMut trait []
 impl<T> IndexMut<usize> for Matrix<T> {
     fn index_mut(&mut self, row: usize) -> &mut[T] {
-        &mut self.data[row]
+        assert!(row < self.rows, "Invalid matrix indices [{}] for a matrix with {} rows", row, self.rows);
+        let offset = self.offset(row);
+        &mut self.data[offset..offset + self.cols]
     }
 }
 
@@ -87,15 +171,7 @@ where T: Add<Output=T> + Default + Copy
 {
     type Output = Matrix<T>;
     fn add(self, other: Self) -> Self::Output {
-        assert_eq!(self.rows(), other.rows(), "matrix dimensions must match for addition");
-        assert_eq!(self.cols(), other.cols(), "matrix dimensions must match for addition");
-        let mut result = Matrix::new(self.rows(), self.cols());
-        for i in 0..self.rows() {
-            for j in 0..self.cols() {
-                result.data[i][j] = self.data[i][j] + other.data[i][j];
-            }
-        }
-        result
+        &self + &other
     }
 }
 impl<T> Add<&Matrix<T>> for &Matrix<T>
@@ -103,15 +179,7 @@ where T: Add<Output=T> + Default + Copy
 {
     type Output = Matrix<T>;
     fn add(self, other: &Matrix<T>) -> Self::Output {
-        assert_eq!(self.rows(), other.rows(), "matrix dimensions must match for addition");
-        assert_eq!(self.cols(), other.cols(), "matrix dimensions must match for addition");
-        let mut result = Matrix::new(self.rows(), self.cols());
-        for i in 0..self.rows() {
-            for j in 0..self.cols() {
-                result.data[i][j] = self.data[i][j] + other.data[i][j];
-            }
-        }
-        result
+        self.try_add(other).or_panic()
     }
 }
 
@@ -122,15 +190,7 @@ where T : Sub<Output=T> + Default + Copy
 {
     type Output = Matrix<T>;
     fn sub(self, other: Self) -> Self::Output {
-        assert_eq!(self.rows(), other.rows(), "matrix dimensions must match for subtraction");
-        assert_eq!(self.cols(), other.cols(), "matrix dimensions must match for subtraction");
-        let mut result = Matrix::new(self.rows(), self.cols());
-        for i in 0..self.rows() {
-            for j in 0..self.cols() {
-                result.data[i][j] = self.data[i][j] - other.data[i][j];
-            }
-        }
-        result
+        &self - &other
     }
 }
 impl<T> Sub<&Matrix<T>> for &Matrix<T>
@@ -138,16 +198,7 @@ where T : Sub<Output=T> + Default + Copy
 {
     type Output = Matrix<T>;
     fn sub(self, other: &Matrix<T>) -> Self::Output {
-        assert_eq!(self.rows(), other.rows(), "matrix dimensions must match for subtraction");
-        assert_eq!(self.cols(), other.cols(), "matrix dimensions must match for subtraction");
-        let mut result = Matrix::new(self.rows(), self.cols());
-        for i in 0..self.rows() {
-            for j in 0..self.cols() {
-                result.data[i][j] = self.data[i][j] - other.data[i][j];
-            }
-        }
-        result
-        //Matrix { data: self.data.iter().zip(&other.data).map(|(a, b)| *a - *b).collect() }
+        self.try_sub(other).or_panic()
     }
 }
 
@@ -157,20 +208,8 @@ where T: Mul<Output=T> + Default + Copy
 {
     type Output = Matrix<T>;
     fn mul(self, scalar: T) -> Self::Output {
-        let mut result = Matrix::new(self.rows(), self.cols());
-        for i in 0..self.rows() {
-            for j in 0..self.cols() {
-                result.data[i][j] = self.data[i][j] * scalar;
-            }
-        }
-        result
+        self.map_elements(|a| a * scalar)
     }
-    //fn mul(self, scalar: T) -> Self::Output {
-    //    let data = self.data.into_iter()
-    //        .map(|row| row.into_iter().map(|x| x * scalar).collect())
-    //        .collect();
-    //    Matrix { data }
-    //}
 }
 // Mul trait Matrix * Matrix
 impl<T> Mul for Matrix<T>
@@ -180,18 +219,7 @@ where
 {
     type Output = Matrix<T>;
     fn mul(self, other: Self) -> Self::Output {
-        assert_eq!(self.cols(), other.rows(), "matrix dimensions must match for multiplication");
-        let mut result = Matrix::new(self.rows(), other.cols());
-        for i in 0..self.rows() {
-            for j in 0..other.cols() {
-                let mut sum = T::default();
-                for k in 0..self.cols() {
-                    sum = sum + (self.data[i][k] * other.data[k][j]);
-                }
-                result.data[i][j] = sum;
-            }
-        }
-        result
+        &self * &other
     }
 }
 impl<T> Mul<&Matrix<T>> for &Matrix<T>
@@ -201,18 +229,85 @@ where
 {
     type Output = Matrix<T>;
     fn mul(self, other: &Matrix<T>) -> Self::Output {
-        assert_eq!(self.cols(), other.rows(), "matrix dimensions must match for multiplication");
-        let mut result = Matrix::new(self.rows(), other.cols());
-        for i in 0..self.rows() {
-            for j in 0..other.cols() {
-                let mut sum = T::default();
-                for k in 0..self.cols() {
-                    sum = sum + (self.data[i][k] * other.data[k][j]);
+        self.try_mul(other).or_panic()
+    }
+}
+
+impl<T> Matrix<T>
+where
+    T: Add<Output=T> + Default + Copy
+{
+    pub fn try_add(&self, other: &Matrix<T>) -> Result<Matrix<T>, Error> {
+        self.check_same_dimensions(other, "addition")?;
+        Ok(self.zip_with(other, |a, b| a + b))
+    }
+}
+
+impl<T> Matrix<T>
+where
+    T: Sub<Output=T> + Default + Copy
+{
+    pub fn try_sub(&self, other: &Matrix<T>) -> Result<Matrix<T>, Error> {
+        self.check_same_dimensions(other, "subtraction")?;
+        Ok(self.zip_with(other, |a, b| a - b))
+    }
+}
+
+impl<T> Matrix<T>
+where
+    T: Div<Output=T> + Default + Copy + PartialEq
+{
+    // Matrix / scalar
+    pub fn try_div(&self, scalar: T) -> Result<Matrix<T>, Error> {
+        if scalar == T::default() {
+            return Err(Error::DivisionByZero);
+        }
+        Ok(self.map_elements(|a| a / scalar))
+    }
+}
+
+impl<T> Matrix<T>
+where
+    T: Mul<Output=T> + Add<Output=T>,
+    T: Default + Copy
+{
+    // Matrix * Matrix
+    pub fn try_mul(&self, other: &Matrix<T>) -> Result<Matrix<T>, Error> {
+        let mut result = Matrix::try_new(self.rows, other.cols)?;
+        self.try_mul_into(other, &mut result)?;
+        Ok(result)
+    }
+
+    // writes self * other into result, which is only reallocated if it has the wrong dimensions
+    pub fn try_mul_into(&self, other: &Matrix<T>, result: &mut Matrix<T>) -> Result<(), Error> {
+        if self.cols() != other.rows() {
+            return Err(Error::DimensionMismatch { operation: "multiplication", left: self.get_dimensions(), right: other.get_dimensions() });
+        }
+        let (n, m, p) = (self.rows, self.cols, other.cols);
+        if result.rows != n || result.cols != p {
+            *result = Matrix::new(n, p);
+        } else {
+            result.data.fill(T::default());
+        }
+        // tiled i-k-j loop: the innermost loop walks along contiguous rows of the right operand and the result
+        // every element still accumulates its products in increasing k order, exactly like the naive i-j-k loop
+        for k_block in (0..m).step_by(BLOCK_SIZE) {
+            let k_end = (k_block + BLOCK_SIZE).min(m);
+            for j_block in (0..p).step_by(BLOCK_SIZE) {
+                let j_end = (j_block + BLOCK_SIZE).min(p);
+                for i in 0..n {
+                    let result_row = &mut result.data[i * p + j_block..i * p + j_end];
+                    for k in k_block..k_end {
+                        let a_ik = self.data[i * m + k];
+                        let other_row = &other.data[k * p + j_block..k * p + j_end];
+                        for (r, b_kj) in result_row.iter_mut().zip(other_row) {
+                            *r = *r + (a_ik * *b_kj);
+                        }
+                    }
                 }
-                result.data[i][j] = sum;
             }
         }
-        result
+        Ok(())
     }
 }
 
@@ -222,14 +317,7 @@ where T: Div<Output=T> + Default + Copy + PartialEq,
 {
     type Output = Matrix<T>;
     fn div(self, scalar: T) -> Self::Output {
-        assert!(scalar != T::default(), "cannot divide by zero!");
-        let mut result = Matrix::new(self.rows(), self.cols());
-        for i in 0..self.rows() {
-            for j in 0..self.cols() {
-                result.data[i][j] = self.data[i][j] / scalar;
-            }
-        }
-        result
+        self.try_div(scalar).or_panic()
     }
 }
 
@@ -239,11 +327,25 @@ where
     T: MulAssign + Copy,
 {
     fn mul_assign(&mut self, scalar: T) {
-        for row in &mut self.data {
-            for elem in row {
-                *elem *= scalar;
-            }
+        for elem in self.data.iter_mut() {
+            *elem *= scalar;
+        }
+    }
+}
+
+impl<T> Matrix<T>
+where
+    T: DivAssign + Copy + PartialEq + Default
+{
+    // Matrix /= scalar, the matrix stays untouched on division by zero
+    pub fn try_div_assign(&mut self, scalar: T) -> Result<(), Error> {
+        if scalar == T::default() {
+            return Err(Error::DivisionByZero);
         }
+        for elem in self.data.iter_mut() {
+            *elem /= scalar;
+        }
+        Ok(())
     }
 }
 
@@ -253,18 +355,13 @@ where
     T: DivAssign + Copy + PartialEq + Default,
 {
     fn div_assign(&mut self, scalar: T) {
-        assert!(scalar != T::default(), "cannot divide by zero!");
-        for row in &mut self.data {
-            for elem in row {
-                *elem /= scalar;
-            }
-        }
+        self.try_div_assign(scalar).or_panic()
     }
 }
 
 impl<T: fmt::Display + std::fmt::Debug> fmt::Display for Matrix<T> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        for row in &self.data {
+        for row in self.data.chunks(self.cols) {
             writeln!(f, "{:?}", row)?;
         }
         Ok(())
@@ -279,31 +376,394 @@ where
     where
         F: Fn(T) -> T,
     {
-        for row in self.data.iter_mut() {
-            for elem in row.iter_mut() {
-                *elem = func(*elem);
+        for elem in self.data.iter_mut() {
+            *elem = func(*elem);
+        }
+    }
+}
+
+impl<T> Matrix<T>
+where
+    T: Add<Output=T> + Default + Copy,
+{
+    // adds a 1xn row to every row of an mxn matrix (broadcasting)
+    pub fn add_row(&self, row: &Matrix<T>) -> Matrix<T> {
+        self.try_add_row(row).or_panic()
+    }
+
+    pub fn try_add_row(&self, row: &Matrix<T>) -> Result<Matrix<T>, Error> {
+        let mut result = self.clone();
+        result.try_add_row_assign(row)?;
+        Ok(result)
+    }
+
+    // in-place version of add_row
+    pub fn try_add_row_assign(&mut self, row: &Matrix<T>) -> Result<(), Error> {
+        if row.rows() != 1 || self.cols() != row.cols() {
+            return Err(Error::DimensionMismatch { operation: "broadcast addition", left: self.get_dimensions(), right: row.get_dimensions() });
+        }
+        for result_row in self.data.chunks_mut(self.cols) {
+            for (r, b) in result_row.iter_mut().zip(&row.data) {
+                *r = *r + *b;
+            }
+        }
+        Ok(())
+    }
+}
+
+// constructors
+impl<T> Matrix<T>
+where
+    T: Clone
+{
+    // every element set to value
+    pub fn filled(rows: usize, cols: usize, value: T) -> Self {
+        Self::try_filled(rows, cols, value).or_panic()
+    }
+
+    pub fn try_filled(rows: usize, cols: usize, value: T) -> Result<Self, Error> {
+        if rows == 0 || cols == 0 {
+            return Err(Error::ZeroDimension);
+        }
+        Ok(Self { data: vec![value; rows * cols], rows, cols })
+    }
+
+    // data holds the elements row by row
+    pub fn from_vec(rows: usize, cols: usize, data: Vec<T>) -> Self {
+        Self::try_from_vec(rows, cols, data).or_panic()
+    }
+
+    pub fn try_from_vec(rows: usize, cols: usize, data: Vec<T>) -> Result<Self, Error> {
+        if rows == 0 || cols == 0 {
+            return Err(Error::ZeroDimension);
+        }
+        if data.len() != rows * cols {
+            return Err(Error::ElementCountMismatch { expected: rows * cols, actual: data.len() });
+        }
+        Ok(Self { data, rows, cols })
+    }
+
+    // all rows must have the same length
+    pub fn from_rows<R: AsRef<[T]>>(rows: &[R]) -> Self {
+        Self::try_from_rows(rows).or_panic()
+    }
+
+    pub fn try_from_rows<R: AsRef<[T]>>(rows: &[R]) -> Result<Self, Error> {
+        let cols = rows.first().map_or(0, |row| row.as_ref().len());
+        let mut data = Vec::with_capacity(rows.len() * cols);
+        for row in rows {
+            if row.as_ref().len() != cols {
+                return Err(Error::ElementCountMismatch { expected: cols, actual: row.as_ref().len() });
+            }
+            data.extend_from_slice(row.as_ref());
+        }
+        Self::try_from_vec(rows.len(), cols, data)
+    }
+}
+
+impl<T> Matrix<T>
+where
+    T: num::Zero + num::One + Clone
+{
+    pub fn zeros(rows: usize, cols: usize) -> Self {
+        Self::filled(rows, cols, T::zero())
+    }
+
+    pub fn try_zeros(rows: usize, cols: usize) -> Result<Self, Error> {
+        Self::try_filled(rows, cols, T::zero())
+    }
+
+    pub fn ones(rows: usize, cols: usize) -> Self {
+        Self::filled(rows, cols, T::one())
+    }
+
+    pub fn try_ones(rows: usize, cols: usize) -> Result<Self, Error> {
+        Self::try_filled(rows, cols, T::one())
+    }
+
+    // nxn matrix with ones on the diagonal
+    pub fn identity(n: usize) -> Self {
+        Self::try_identity(n).or_panic()
+    }
+
+    pub fn try_identity(n: usize) -> Result<Self, Error> {
+        let mut matrix = Self::try_zeros(n, n)?;
+        for i in 0..n {
+            matrix.data[i * n + i] = T::one();
+        }
+        Ok(matrix)
+    }
+}
+
+// shape and element-wise operations
+impl<T> Matrix<T>
+where
+    T: Copy
+{
+    // nxm -> mxn
+    pub fn transpose(&self) -> Matrix<T> {
+        let mut data = Vec::with_capacity(self.data.len());
+        for col in 0..self.cols {
+            data.extend((0..self.rows).map(|row| self.data[row * self.cols + col]));
+        }
+        Matrix { data, rows: self.cols, cols: self.rows }
+    }
+
+    // applies the function to every element, the result can have another element type
+    pub fn map<U, F>(&self, func: F) -> Matrix<U>
+    where
+        F: Fn(T) -> U,
+    {
+        Matrix { data: self.data.iter().map(|a| func(*a)).collect(), rows: self.rows, cols: self.cols }
+    }
+
+    // combines the elements at the same position of two matrices of the same size
+    pub fn zip_map<U, V, F>(&self, other: &Matrix<U>, func: F) -> Matrix<V>
+    where
+        U: Copy,
+        F: Fn(T, U) -> V,
+    {
+        self.try_zip_map(other, func).or_panic()
+    }
+
+    pub fn try_zip_map<U, V, F>(&self, other: &Matrix<U>, func: F) -> Result<Matrix<V>, Error>
+    where
+        U: Copy,
+        F: Fn(T, U) -> V,
+    {
+        if self.rows != other.rows() || self.cols != other.cols() {
+            return Err(Error::DimensionMismatch { operation: "element-wise operation", left: self.get_dimensions(), right: other.get_dimensions() });
+        }
+        Ok(Matrix { data: self.data.iter().zip(&other.data).map(|(a, b)| func(*a, *b)).collect(), rows: self.rows, cols: self.cols })
+    }
+
+    // row as a 1xn matrix
+    pub fn row(&self, row: usize) -> Matrix<T> {
+        self.try_row(row).or_panic()
+    }
+
+    pub fn try_row(&self, row: usize) -> Result<Matrix<T>, Error> {
+        self.try_slice_rows(row, row + 1)
+    }
+
+    // column as a mx1 matrix
+    pub fn col(&self, col: usize) -> Matrix<T> {
+        self.try_col(col).or_panic()
+    }
+
+    pub fn try_col(&self, col: usize) -> Result<Matrix<T>, Error> {
+        self.try_slice_cols(col, col + 1)
+    }
+
+    // rows start..end (exclusive) of all columns
+    pub fn slice_rows(&self, start: usize, end: usize) -> Matrix<T> {
+        self.try_slice_rows(start, end).or_panic()
+    }
+
+    pub fn try_slice_rows(&self, start: usize, end: usize) -> Result<Matrix<T>, Error> {
+        if start >= end {
+            return Err(Error::ZeroDimension);
+        }
+        if end > self.rows {
+            return Err(Error::IndexOutOfRange { row: end - 1, col: 0, dimensions: self.get_dimensions() });
+        }
+        Ok(Matrix { data: self.data[start * self.cols..end * self.cols].to_vec(), rows: end - start, cols: self.cols })
+    }
+
+    // columns start..end (exclusive) of all rows
+    pub fn slice_cols(&self, start: usize, end: usize) -> Matrix<T> {
+        self.try_slice_cols(start, end).or_panic()
+    }
+
+    pub fn try_slice_cols(&self, start: usize, end: usize) -> Result<Matrix<T>, Error> {
+        if start >= end {
+            return Err(Error::ZeroDimension);
+        }
+        if end > self.cols {
+            return Err(Error::IndexOutOfRange { row: 0, col: end - 1, dimensions: self.get_dimensions() });
+        }
+        let mut data = Vec::with_capacity(self.rows * (end - start));
+        for row in self.data.chunks(self.cols) {
+            data.extend_from_slice(&row[start..end]);
+        }
+        Ok(Matrix { data, rows: self.rows, cols: end - start })
+    }
+}
+
+impl<T> Matrix<T>
+where
+    T: Mul<Output=T> + Default + Copy
+{
+    // element-wise product of two matrices of the same size
+    pub fn hadamard(&self, other: &Matrix<T>) -> Matrix<T> {
+        self.try_hadamard(other).or_panic()
+    }
+
+    pub fn try_hadamard(&self, other: &Matrix<T>) -> Result<Matrix<T>, Error> {
+        self.check_same_dimensions(other, "element-wise multiplication")?;
+        Ok(self.zip_with(other, |a, b| a * b))
+    }
+}
+
+impl<T> Matrix<T>
+where
+    T: Div<Output=T> + Default + Copy + PartialEq
+{
+    // element-wise quotient, like the scalar division no element of other may be zero
+    pub fn hadamard_div(&self, other: &Matrix<T>) -> Matrix<T> {
+        self.try_hadamard_div(other).or_panic()
+    }
+
+    pub fn try_hadamard_div(&self, other: &Matrix<T>) -> Result<Matrix<T>, Error> {
+        self.check_same_dimensions(other, "element-wise division")?;
+        if other.data.iter().any(|b| *b == T::default()) {
+            return Err(Error::DivisionByZero);
+        }
+        Ok(self.zip_with(other, |a, b| a / b))
+    }
+}
+
+impl<T> Matrix<T>
+where
+    T: Add<Output=T> + Default + Copy,
+{
+    // adds a mx1 column to every column of an mxn matrix (broadcasting)
+    pub fn add_col(&self, col: &Matrix<T>) -> Matrix<T> {
+        self.try_add_col(col).or_panic()
+    }
+
+    pub fn try_add_col(&self, col: &Matrix<T>) -> Result<Matrix<T>, Error> {
+        if col.cols() != 1 || self.rows() != col.rows() {
+            return Err(Error::DimensionMismatch { operation: "broadcast addition", left: self.get_dimensions(), right: col.get_dimensions() });
+        }
+        let mut result = self.clone();
+        for (result_row, b) in result.data.chunks_mut(self.cols).zip(&col.data) {
+            for r in result_row.iter_mut() {
+                *r = *r + *b;
+            }
+        }
+        Ok(result)
+    }
+
+    // sum of all elements
+    pub fn sum(&self) -> T {
+        self.data.iter().fold(T::default(), |acc, a| acc + *a)
+    }
+
+    // mx1 matrix holding the sum of every row
+    pub fn row_sums(&self) -> Matrix<T> {
+        Matrix { data: self.data.chunks(self.cols).map(|row| row.iter().fold(T::default(), |acc, a| acc + *a)).collect(), rows: self.rows, cols: 1 }
+    }
+
+    // 1xn matrix holding the sum of every column
+    pub fn col_sums(&self) -> Matrix<T> {
+        let mut data = vec![T::default(); self.cols];
+        for row in self.data.chunks(self.cols) {
+            for (s, a) in data.iter_mut().zip(row) {
+                *s = *s + *a;
+            }
+        }
+        Matrix { data, rows: 1, cols: self.cols }
+    }
+}
+
+// reductions
+impl<T> Matrix<T>
+where
+    T: PartialOrd + Copy
+{
+    // largest element
+    pub fn max(&self) -> T {
+        let (row, col) = self.argmax();
+        self.data[self.offset(row) + col]
+    }
+
+    // position [row][col] of the first largest element, NaN never compares larger
+    pub fn argmax(&self) -> (usize, usize) {
+        let mut best = 0;
+        for (i, a) in self.data.iter().enumerate().skip(1) {
+            if *a > self.data[best] {
+                best = i;
             }
         }
+        (best / self.cols, best % self.cols)
+    }
+}
+
+impl<T> Matrix<T>
+where
+    T: num::Float
+{
+    // arithmetic mean of all elements
+    pub fn mean(&self) -> T {
+        let sum = self.data.iter().fold(T::zero(), |acc, a| acc + *a);
+        sum / T::from(self.data.len()).unwrap()
+    }
+
+    // sum of the absolute values
+    pub fn l1_norm(&self) -> T {
+        self.data.iter().fold(T::zero(), |acc, a| acc + a.abs())
+    }
+
+    // square root of the sum of the squares (Frobenius norm)
+    pub fn l2_norm(&self) -> T {
+        self.data.iter().fold(T::zero(), |acc, a| acc + *a * *a).sqrt()
+    }
+
+    // largest absolute value
+    pub fn max_norm(&self) -> T {
+        self.data.iter().fold(T::zero(), |acc, a| acc.max(a.abs()))
+    }
+}
+
+// random constructors, the elements are drawn row by row so a seeded generator always gives the same matrix
+impl<T> Matrix<T>
+where
+    T: rand::distr::uniform::SampleUniform + PartialOrd + Copy
+{
+    // uniform elements from [lower, upper)
+    pub fn random(rows: usize, cols: usize, generator: &mut RandomGenerator<T>, lower: T, upper: T) -> Self {
+        Self::try_random(rows, cols, generator, lower, upper).or_panic()
+    }
+
+    pub fn try_random(rows: usize, cols: usize, generator: &mut RandomGenerator<T>, lower: T, upper: T) -> Result<Self, Error> {
+        Self::try_generated(rows, cols, || generator.try_generate(lower, upper))
+    }
+
+    // uniform elements from the range the generator was configured with
+    pub fn from_generator(rows: usize, cols: usize, generator: &mut RandomGenerator<T>) -> Self {
+        Self::try_from_generator(rows, cols, generator).or_panic()
+    }
+
+    pub fn try_from_generator(rows: usize, cols: usize, generator: &mut RandomGenerator<T>) -> Result<Self, Error> {
+        Self::try_generated(rows, cols, || generator.try_sample())
+    }
+
+    fn try_generated<F>(rows: usize, cols: usize, mut draw: F) -> Result<Self, Error>
+    where
+        F: FnMut() -> Result<T, Error>,
+    {
+        if rows == 0 || cols == 0 {
+            return Err(Error::ZeroDimension);
+        }
+        let data = (0..rows * cols).map(|_| draw()).collect::<Result<Vec<T>, Error>>()?;
+        Ok(Self { data, rows, cols })
     }
 }
 
-//impl<T> PartialEq<&Matrix<T>> for &Matrix<T>
-//where
-//    T: PartialEq,
-//    T: Default + Copy
-//{
-//    fn eq(self, other: &Matrix<T>) -> bool {
-//        if self.rows() != other.rows() { return false; }
-//        if self.cols() != other.cols() { return false; }
-//        for i in 0..self.rows() {
-//            for j in 0..other.cols() {
-//                if self.data[i][j] != other.data[i][j] { return false; }
-//            }
-//        }
-//        true
-//    }
-//}
+impl<T> Matrix<T>
+where
+    T: rand::distr::uniform::SampleUniform + num::Float
+{
+    // normally distributed elements
+    pub fn random_normal(rows: usize, cols: usize, generator: &mut RandomGenerator<T>, mean: T, std_dev: T) -> Self {
+        Self::try_random_normal(rows, cols, generator, mean, std_dev).or_panic()
+    }
 
+    pub fn try_random_normal(rows: usize, cols: usize, generator: &mut RandomGenerator<T>, mean: T, std_dev: T) -> Result<Self, Error> {
+        Self::try_generated(rows, cols, || generator.try_generate_normal(mean, std_dev))
+    }
+}
 
 #[cfg(test)]
 mod tests {
@@ -416,6 +876,25 @@ mod tests {
         assert_eq!(result[1][1], 154);
     }
 
+    #[test]
+    fn multiplication_into_buffer() {
+        let mut left = Matrix::<i32>::new(2, 2);
+        left[0][0] = 1;
+        left[1][1] = 2;
+        let mut right = Matrix::<i32>::new(2, 3);
+        right[0][2] = 3;
+        right[1][0] = 4;
+        // stale values are overwritten, wrong shapes are replaced
+        let mut buffer = Matrix::<i32>::new(2, 3);
+        buffer[0][0] = 99;
+        left.try_mul_into(&right, &mut buffer).unwrap();
+        assert_eq!(buffer, left.try_mul(&right).unwrap());
+        let mut small = Matrix::<i32>::new(1, 1);
+        left.try_mul_into(&right, &mut small).unwrap();
+        assert_eq!(small, buffer);
+        assert!(right.try_mul_into(&left, &mut small).is_err());
+    }
+
     #[test]
     fn test_matrix_creation() {
         let m = Matrix::<i32>::new(3, 3);
@@ -524,6 +1003,133 @@ mod tests {
         assert_eq!(m.get(0, 0), 36);
     }
 
+    #[test]
+    fn test_row_major_layout() {
+        let mut m = Matrix::<i32>::new(2, 3);
+        m.set(0, 2, 1);
+        m.set(1, 0, 2);
+        m[1][2] = 3;
+        assert_eq!(m.as_slice(), &[0, 0, 1, 2, 0, 3]);
+        assert_eq!(&m[1], &[2, 0, 3]);
+        m.as_mut_slice()[1] = 4;
+        assert_eq!(m.get(0, 1), 4);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid matrix indices")]
+    fn test_get_out_of_range_column() {
+        // with a contiguous buffer an invalid column must not silently wrap into the next row
+        let m = Matrix::<i32>::new(2, 2);
+        m.get(0, 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid matrix indices")]
+    fn test_index_out_of_range_row() {
+        let m = Matrix::<i32>::new(2, 2);
+        let _ = m[2][0];
+    }
+
+    #[test]
+    fn test_blocked_matrix_multiplication() {
+        // dimensions larger than and not divisible by the block size
+        let (n, m, p) = (70, 131, 67);
+        let mut a = Matrix::<i64>::new(n, m);
+        let mut b = Matrix::<i64>::new(m, p);
+        for i in 0..n {
+            for k in 0..m {
+                a.set(i, k, ((i * 7 + k * 3) % 11) as i64 - 5);
+            }
+        }
+        for k in 0..m {
+            for j in 0..p {
+                b.set(k, j, ((k * 5 + j * 13) % 17) as i64 - 8);
+            }
+        }
+        let result = &a * &b;
+        assert_eq!(result.rows(), n);
+        assert_eq!(result.cols(), p);
+        for i in 0..n {
+            for j in 0..p {
+                let mut sum = 0;
+                for k in 0..m {
+                    sum += a.get(i, k) * b.get(k, j);
+                }
+                assert_eq!(result.get(i, j), sum);
+            }
+        }
+    }
+
+    #[test]
+    fn test_try_new() {
+        assert_eq!(Matrix::<i32>::try_new(0, 3), Err(Error::ZeroDimension));
+        assert_eq!(Matrix::<i32>::try_new(2, 0), Err(Error::ZeroDimension));
+        assert!(Matrix::<i32>::try_new(2, 3).is_ok());
+    }
+
+    #[test]
+    fn test_try_get_set() {
+        let mut m = Matrix::<i32>::new(2, 3);
+        assert_eq!(m.try_set(1, 2, 5), Ok(()));
+        assert_eq!(m.try_get(1, 2), Ok(5));
+        let out_of_range = Err(Error::IndexOutOfRange { row: 2, col: 0, dimensions: Dimensions { rows: 2, cols: 3 } });
+        assert_eq!(m.try_get(2, 0), out_of_range);
+        assert_eq!(m.try_set(2, 0, 1), Err(Error::IndexOutOfRange { row: 2, col: 0, dimensions: Dimensions { rows: 2, cols: 3 } }));
+        assert!(m.try_get(0, 3).is_err());
+    }
+
+    #[test]
+    fn test_try_arithmetic() {
+        let m1 = Matrix::<i32>::new(2, 2);
+        let m2 = Matrix::<i32>::new(3, 3);
+        let mismatch = |operation| Err(Error::DimensionMismatch {
+            operation,
+            left: Dimensions { rows: 2, cols: 2 },
+            right: Dimensions { rows: 3, cols: 3 }
+        });
+        assert_eq!(m1.try_add(&m2), mismatch("addition"));
+        assert_eq!(m1.try_sub(&m2), mismatch("subtraction"));
+        assert_eq!(m1.try_mul(&m2), mismatch("multiplication"));
+        assert_eq!(m1.try_div(0), Err(Error::DivisionByZero));
+        assert!(m1.try_add(&m1).is_ok());
+        assert!(m1.try_sub(&m1).is_ok());
+        assert!(m1.try_mul(&m1).is_ok());
+        assert!(m1.try_div(2).is_ok());
+        let mut m3 = Matrix::<i32>::filled(2, 2, 6);
+        assert_eq!(m3.try_div_assign(0), Err(Error::DivisionByZero));
+        assert_eq!(m3, Matrix::filled(2, 2, 6));
+        assert!(m3.try_div_assign(3).is_ok());
+        assert_eq!(m3, Matrix::filled(2, 2, 2));
+        assert!(m1.try_add_row(&Matrix::new(2, 2)).is_err());
+        assert!(m1.try_add_row(&Matrix::new(1, 2)).is_ok());
+    }
+
+    #[test]
+    fn test_add_row() {
+        let mut m = Matrix::<i32>::new(3, 2);
+        m.set(0, 0, 1);
+        m.set(1, 1, 2);
+        m.set(2, 0, 3);
+        let mut row = Matrix::<i32>::new(1, 2);
+        row.set(0, 0, 10);
+        row.set(0, 1, 20);
+        let result = m.add_row(&row);
+        assert_eq!(result.get(0, 0), 11);
+        assert_eq!(result.get(0, 1), 20);
+        assert_eq!(result.get(1, 0), 10);
+        assert_eq!(result.get(1, 1), 22);
+        assert_eq!(result.get(2, 0), 13);
+        assert_eq!(result.get(2, 1), 20);
+    }
+
+    #[test]
+    #[should_panic(expected = "matrix dimensions must match for broadcast addition")]
+    fn test_add_row_mismatched_size() {
+        let m = Matrix::<i32>::new(3, 2);
+        let row = Matrix::<i32>::new(1, 3);
+        let _ = m.add_row(&row);
+    }
+
     #[test]
     fn test_matrix_equality() {
         let mut m1 = Matrix::<i32>::new(2, 3);
@@ -553,4 +1159,110 @@ mod tests {
         
         assert_ne!(m1, m2);
     }
+
+    #[test]
+    fn constructors() {
+        assert_eq!(Matrix::<i32>::zeros(2, 3), Matrix::new(2, 3));
+        assert_eq!(Matrix::<i32>::ones(2, 2).as_slice(), &[1, 1, 1, 1]);
+        assert_eq!(Matrix::filled(1, 3, 7).as_slice(), &[7, 7, 7]);
+        assert_eq!(Matrix::<i32>::identity(3).as_slice(), &[1, 0, 0, 0, 1, 0, 0, 0, 1]);
+
+        let matrix = Matrix::from_vec(2, 3, vec![1, 2, 3, 4, 5, 6]);
+        assert_eq!(matrix[1][0], 4);
+        assert_eq!(Matrix::from_rows(&[vec![1, 2, 3], vec![4, 5, 6]]), matrix);
+        assert_eq!(Matrix::from_rows(&[[1, 2, 3], [4, 5, 6]]), matrix);
+
+        assert_eq!(Matrix::try_from_vec(2, 2, vec![1, 2, 3]), Err(Error::ElementCountMismatch { expected: 4, actual: 3 }));
+        assert_eq!(Matrix::<i32>::try_from_rows(&[vec![1, 2], vec![3]]), Err(Error::ElementCountMismatch { expected: 2, actual: 1 }));
+        assert_eq!(Matrix::<i32>::try_from_rows::<Vec<i32>>(&[]), Err(Error::ZeroDimension));
+        assert_eq!(Matrix::<i32>::try_identity(0), Err(Error::ZeroDimension));
+    }
+
+    #[test]
+    fn transpose() {
+        let matrix = Matrix::from_rows(&[[1, 2, 3], [4, 5, 6]]);
+        let transposed = matrix.transpose();
+        assert_eq!(transposed, Matrix::from_rows(&[[1, 4], [2, 5], [3, 6]]));
+        assert_eq!(transposed.transpose(), matrix);
+    }
+
+    #[test]
+    fn element_wise_operations() {
+        let matrix_0 = Matrix::from_rows(&[[1.0, 2.0], [3.0, 4.0]]);
+        let matrix_1 = Matrix::from_rows(&[[2.0, 4.0], [6.0, 8.0]]);
+        assert_eq!(matrix_0.hadamard(&matrix_1), Matrix::from_rows(&[[2.0, 8.0], [18.0, 32.0]]));
+        assert_eq!(matrix_1.hadamard_div(&matrix_0), Matrix::filled(2, 2, 2.0));
+        assert_eq!(matrix_0.try_hadamard_div(&Matrix::zeros(2, 2)), Err(Error::DivisionByZero));
+        assert!(matrix_0.try_hadamard(&Matrix::zeros(2, 3)).is_err());
+
+        assert_eq!(matrix_0.map(|a| a > 2.0), Matrix::from_rows(&[[false, false], [true, true]]));
+        let labels = Matrix::from_rows(&[[1, 0], [0, 1]]);
+        assert_eq!(matrix_0.zip_map(&labels, |a, b| a * b as f64), Matrix::from_rows(&[[1.0, 0.0], [0.0, 4.0]]));
+        assert!(matrix_0.try_zip_map(&Matrix::<i32>::new(1, 2), |a, _| a).is_err());
+    }
+
+    #[test]
+    fn broadcast_addition() {
+        let matrix = Matrix::from_rows(&[[1, 2, 3], [4, 5, 6]]);
+        assert_eq!(matrix.add_row(&Matrix::from_rows(&[[10, 20, 30]])), Matrix::from_rows(&[[11, 22, 33], [14, 25, 36]]));
+        assert_eq!(matrix.add_col(&Matrix::from_rows(&[[10], [20]])), Matrix::from_rows(&[[11, 12, 13], [24, 25, 26]]));
+        assert!(matrix.try_add_col(&Matrix::new(3, 1)).is_err());
+        assert!(matrix.try_add_col(&Matrix::new(2, 2)).is_err());
+    }
+
+    #[test]
+    fn reductions() {
+        let matrix = Matrix::from_rows(&[[1, -7, 3], [4, 5, 6]]);
+        assert_eq!(matrix.sum(), 12);
+        assert_eq!(matrix.row_sums(), Matrix::from_rows(&[[-3], [15]]));
+        assert_eq!(matrix.col_sums(), Matrix::from_rows(&[[5, -2, 9]]));
+        assert_eq!(matrix.max(), 6);
+        assert_eq!(matrix.argmax(), (1, 2));
+        // the first of equal maxima wins
+        assert_eq!(Matrix::from_rows(&[[2, 9], [9, 1]]).argmax(), (0, 1));
+
+        let matrix = Matrix::from_rows(&[[3.0, -4.0], [0.0, 1.0]]);
+        assert_eq!(matrix.mean(), 0.0);
+        assert_eq!(matrix.l1_norm(), 8.0);
+        assert!((matrix.l2_norm() - 26.0_f64.sqrt()).abs() < 1e-12);
+        assert_eq!(matrix.max_norm(), 4.0);
+    }
+
+    #[test]
+    fn slicing() {
+        let matrix = Matrix::from_rows(&[[1, 2, 3], [4, 5, 6], [7, 8, 9]]);
+        assert_eq!(matrix.row(1), Matrix::from_rows(&[[4, 5, 6]]));
+        assert_eq!(matrix.col(2), Matrix::from_rows(&[[3], [6], [9]]));
+        assert_eq!(matrix.slice_rows(1, 3), Matrix::from_rows(&[[4, 5, 6], [7, 8, 9]]));
+        assert_eq!(matrix.slice_cols(0, 2), Matrix::from_rows(&[[1, 2], [4, 5], [7, 8]]));
+        assert!(matrix.try_row(3).is_err());
+        assert!(matrix.try_slice_cols(2, 2).is_err());
+        assert!(matrix.try_slice_cols(1, 4).is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid matrix indices [0][3] for a 2x2 matrix")]
+    fn column_out_of_range() {
+        Matrix::<i32>::new(2, 2).col(3);
+    }
+
+    #[test]
+    fn random_constructors() {
+        let mut generator = RandomGenerator::<f64>::with_seed(8);
+        let matrix = Matrix::random(3, 4, &mut generator, -1.0, 1.0);
+        assert_eq!(matrix.get_dimensions(), Dimensions { rows: 3, cols: 4 });
+        assert!(matrix.as_slice().iter().all(|a| (-1.0..1.0).contains(a)));
+        assert_eq!(matrix, Matrix::random(3, 4, &mut RandomGenerator::with_seed(8), -1.0, 1.0));
+        assert!(Matrix::try_random(2, 2, &mut generator, 1.0, 1.0).is_err());
+        assert!(Matrix::try_random(0, 2, &mut generator, 0.0, 1.0).is_err());
+
+        let normal = Matrix::random_normal(50, 50, &mut generator, 1.0, 0.1);
+        assert!((normal.mean() - 1.0).abs() < 0.01);
+        assert!(Matrix::try_random_normal(1, 1, &mut generator, 0.0, -1.0).is_err());
+
+        let config = crate::math::random::RandomGeneratorConfig { seed: 2, lower_limit: 5, upper_limit: 8 };
+        let matrix = Matrix::from_generator(4, 4, &mut RandomGenerator::from_config(config));
+        assert!(matrix.as_slice().iter().all(|a| (5..8).contains(a)));
+        assert!(Matrix::try_from_generator(1, 1, &mut RandomGenerator::<i32>::with_seed(2)).is_err());
+    }
 }
@@ -1,3 +1,4 @@
 pub mod matrix;
 pub mod random;
-pub mod dimensions;
\ No newline at end of file
+pub mod dimensions;
+pub mod activation;
\ No newline at end of file
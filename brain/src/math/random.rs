@@ -1,9 +1,12 @@
 use rand::{Rng, SeedableRng};
 use rand::rngs::StdRng;
 use std::marker::PhantomData;
+use crate::error::{Error, OrPanic};
 
 pub struct RandomGenerator<T> {
     rng: StdRng,
+    // range of sample, set by from_config
+    limits: Option<(T, T)>,
     _marker: PhantomData<T>,
 }
 
@@ -19,19 +22,197 @@ where
     pub fn with_seed(seed: u64) -> Self {
         Self {
             rng: StdRng::seed_from_u64(seed),
+            limits: None,
             _marker: PhantomData,
         }
     }
 
     pub fn generate(&mut self, a: T, b: T) -> T {
+        self.try_generate(a, b).or_panic()
+    }
+
+    // random value from [a, b)
+    pub fn try_generate(&mut self, a: T, b: T) -> Result<T, Error> {
         if a >= b {
-            panic!("Invalid range: a must be less than b");
+            return Err(Error::InvalidRange);
+        }
+        Ok(self.rng.random_range(a..b))
+    }
+
+    // random value from the range of the config
+    pub fn sample(&mut self) -> T {
+        self.try_sample().or_panic()
+    }
+
+    pub fn try_sample(&mut self) -> Result<T, Error> {
+        match self.limits {
+            Some((lower, upper)) => self.try_generate(lower, upper),
+            None => Err(Error::InvalidParameter("the generator has no configured range, create it with from_config"))
+        }
+    }
+
+    pub fn get_limits(&self) -> Option<(T, T)> {
+        self.limits
+    }
+
+    // true with the given probability
+    pub fn generate_bernoulli(&mut self, probability: f64) -> bool {
+        self.try_generate_bernoulli(probability).or_panic()
+    }
+
+    pub fn try_generate_bernoulli(&mut self, probability: f64) -> Result<bool, Error> {
+        if !(0.0..=1.0).contains(&probability) {
+            return Err(Error::InvalidParameter("probability must be in [0, 1]"));
+        }
+        Ok(self.rng.random::<f64>() < probability)
+    }
+
+    // Fisher-Yates
+    pub fn shuffle<E>(&mut self, items: &mut [E]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.rng.random_range(0..=i));
+        }
+    }
+
+    // k distinct elements in random order
+    pub fn choose_k<E: Clone>(&mut self, items: &[E], k: usize) -> Vec<E> {
+        self.try_choose_k(items, k).or_panic()
+    }
+
+    pub fn try_choose_k<E: Clone>(&mut self, items: &[E], k: usize) -> Result<Vec<E>, Error> {
+        if k > items.len() {
+            return Err(Error::InvalidParameter("cannot choose more elements than there are"));
         }
-        self.rng.random_range(a..b)
+        // partial Fisher-Yates over the indices, the first k positions end up chosen
+        let mut indices: Vec<usize> = (0..items.len()).collect();
+        for i in 0..k {
+            indices.swap(i, self.rng.random_range(i..items.len()));
+        }
+        Ok(indices[..k].iter().map(|&i| items[i].clone()).collect())
     }
 }
 
-// Configuration struct
+impl<T> RandomGenerator<T>
+where
+    T: rand::distr::uniform::SampleUniform + num::Float,
+{
+    pub fn generate_normal(&mut self, mean: T, std_dev: T) -> T {
+        self.try_generate_normal(mean, std_dev).or_panic()
+    }
+
+    // normally distributed value, Box-Muller transform on two uniform draws
+    pub fn try_generate_normal(&mut self, mean: T, std_dev: T) -> Result<T, Error> {
+        if std_dev < T::zero() {
+            return Err(Error::InvalidParameter("standard deviation cannot be negative"));
+        }
+        // u1 from (0, 1] so that the logarithm stays finite
+        let u1 = T::one() - self.rng.random_range(T::zero()..T::one());
+        let u2 = self.rng.random_range(T::zero()..T::one());
+        let two = T::one() + T::one();
+        let tau = T::from(std::f64::consts::TAU).unwrap();
+        let z = (-two * u1.ln()).sqrt() * (tau * u2).cos();
+        Ok(mean + std_dev * z)
+    }
+
+    pub fn generate_truncated_normal(&mut self, mean: T, std_dev: T, lower: T, upper: T) -> T {
+        self.try_generate_truncated_normal(mean, std_dev, lower, upper).or_panic()
+    }
+
+    // normally distributed value from [lower, upper], sampled exactly after Robert (1995):
+    // ranges around the mean reject plain normal draws, tail ranges use an exponential or uniform proposal
+    pub fn try_generate_truncated_normal(&mut self, mean: T, std_dev: T, lower: T, upper: T) -> Result<T, Error> {
+        if lower >= upper {
+            return Err(Error::InvalidRange);
+        }
+        if std_dev <= T::zero() {
+            return Err(Error::InvalidParameter("standard deviation of a truncated normal must be positive"));
+        }
+        let a = (lower - mean) / std_dev;
+        let b = (upper - mean) / std_dev;
+        let z = if a >= T::zero() {
+            self.standard_normal_tail(a, b)
+        } else if b <= T::zero() {
+            -self.standard_normal_tail(-b, -a)
+        } else if b - a < T::from(std::f64::consts::TAU).unwrap().sqrt() {
+            // narrow range around zero, uniform proposal accepted with the normal density
+            loop {
+                let z = self.rng.random_range(a..=b);
+                if self.rng.random_range(T::zero()..T::one()) < (-z * z / (T::one() + T::one())).exp() {
+                    break z;
+                }
+            }
+        } else {
+            // wide range around zero, at least half of the normal draws land inside
+            loop {
+                let z = self.try_generate_normal(T::zero(), T::one())?;
+                if z >= a && z <= b {
+                    break z;
+                }
+            }
+        };
+        // rounding in the rescaling must not leave the range
+        Ok((mean + std_dev * z).max(lower).min(upper))
+    }
+
+    // standard normal value from [a, b] with 0 <= a < b
+    fn standard_normal_tail(&mut self, a: T, b: T) -> T {
+        let two = T::one() + T::one();
+        let root = (a * a + two * two).sqrt();
+        // optimal rate of the exponential proposal shifted to a
+        let alpha = (a + root) / two;
+        let uniform_bound = a + two * T::from(std::f64::consts::E.sqrt()).unwrap() / (a + root) * ((a * a - a * root) / (two * two)).exp();
+        if b > uniform_bound {
+            loop {
+                let u = T::one() - self.rng.random_range(T::zero()..T::one());
+                let z = a - u.ln() / alpha;
+                if z <= b && self.rng.random_range(T::zero()..T::one()) < (-(z - alpha) * (z - alpha) / two).exp() {
+                    return z;
+                }
+            }
+        }
+        loop {
+            let z = self.rng.random_range(a..=b);
+            if self.rng.random_range(T::zero()..T::one()) < ((a * a - z * z) / two).exp() {
+                return z;
+            }
+        }
+    }
+
+    // index i with probability weights[i] / sum(weights)
+    pub fn generate_categorical(&mut self, weights: &[T]) -> usize {
+        self.try_generate_categorical(weights).or_panic()
+    }
+
+    pub fn try_generate_categorical(&mut self, weights: &[T]) -> Result<usize, Error> {
+        if weights.iter().any(|w| w.is_nan() || w.is_infinite() || *w < T::zero()) {
+            return Err(Error::InvalidParameter("weights must be finite and non-negative"));
+        }
+        let total = weights.iter().fold(T::zero(), |acc, w| acc + *w);
+        if total <= T::zero() {
+            return Err(Error::InvalidParameter("at least one weight must be positive"));
+        }
+        let mut target = self.rng.random_range(T::zero()..total);
+        for (i, weight) in weights.iter().enumerate() {
+            if target < *weight {
+                return Ok(i);
+            }
+            target = target - *weight;
+        }
+        // rounding can leave the target just above the last weight
+        Ok(weights.iter().rposition(|w| *w > T::zero()).unwrap())
+    }
+}
+
+impl<T> Default for RandomGenerator<T>
+where
+    T: rand::distr::uniform::SampleUniform + PartialOrd + Copy,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// seed and range of the values returned by sample
 pub struct RandomGeneratorConfig<T>
 where
     T: rand::distr::uniform::SampleUniform + PartialOrd + Copy,
@@ -46,10 +227,18 @@ where
     T: rand::distr::uniform::SampleUniform + PartialOrd + Copy,
 {
     pub fn from_config(config: RandomGeneratorConfig<T>) -> Self {
-        Self {
+        Self::try_from_config(config).or_panic()
+    }
+
+    pub fn try_from_config(config: RandomGeneratorConfig<T>) -> Result<Self, Error> {
+        if config.lower_limit >= config.upper_limit {
+            return Err(Error::InvalidRange);
+        }
+        Ok(Self {
             rng: StdRng::seed_from_u64(config.seed),
+            limits: Some((config.lower_limit, config.upper_limit)),
             _marker: PhantomData,
-        }
+        })
     }
 }
 
@@ -61,14 +250,14 @@ mod tests {
     fn test_generate_float() {
         let mut rng = RandomGenerator::<f64>::with_seed(42);
         let value = rng.generate(0.0, 1.0);
-        assert!(value >= 0.0 && value < 1.0);
+        assert!((0.0..1.0).contains(&value));
     }
 
     #[test]
     fn test_generate_int() {
         let mut rng = RandomGenerator::<i32>::with_seed(42);
         let value = rng.generate(10, 20);
-        assert!(value >= 10 && value < 20);
+        assert!((10..20).contains(&value));
     }
 
     #[test]
@@ -78,6 +267,27 @@ mod tests {
         rng.generate(10, 5); // Should panic
     }
 
+    #[test]
+    fn test_try_generate() {
+        let mut rng = RandomGenerator::<i32>::with_seed(42);
+        assert_eq!(rng.try_generate(10, 5), Err(Error::InvalidRange));
+        assert_eq!(rng.try_generate(5, 5), Err(Error::InvalidRange));
+        let value = rng.try_generate(5, 6);
+        assert_eq!(value, Ok(5));
+    }
+
+    #[test]
+    fn test_generate_normal() {
+        let mut rng = RandomGenerator::<f64>::with_seed(7);
+        let samples: Vec<f64> = (0..20000).map(|_| rng.generate_normal(2.0, 0.5)).collect();
+        let mean = samples.iter().sum::<f64>() / samples.len() as f64;
+        let variance = samples.iter().map(|x| (x - mean) * (x - mean)).sum::<f64>() / samples.len() as f64;
+        assert!((mean - 2.0).abs() < 0.02);
+        assert!((variance.sqrt() - 0.5).abs() < 0.02);
+        assert!(samples.iter().all(|x| x.is_finite()));
+        assert!(rng.try_generate_normal(0.0, -1.0).is_err());
+    }
+
     #[test]
     fn test_deterministic_output() {
         let mut rng1 = RandomGenerator::<i32>::with_seed(12345);
@@ -96,6 +306,92 @@ mod tests {
         };
         let mut rng = RandomGenerator::from_config(config);
         let value = rng.generate(5.0, 10.0);
-        assert!(value >= 5.0 && value < 10.0);
+        assert!((5.0..10.0).contains(&value));
+        assert_eq!(rng.get_limits(), Some((5.0, 10.0)));
+        for _ in 0..100 {
+            assert!((5.0..10.0).contains(&rng.sample()));
+        }
+        assert!(RandomGenerator::<f64>::with_seed(1).try_sample().is_err());
+        assert!(RandomGenerator::try_from_config(RandomGeneratorConfig { seed: 1, lower_limit: 3, upper_limit: 3 }).is_err());
+    }
+
+    #[test]
+    fn test_generate_truncated_normal() {
+        let mut rng = RandomGenerator::<f64>::with_seed(3);
+        let samples: Vec<f64> = (0..5000).map(|_| rng.generate_truncated_normal(0.0, 1.0, -0.5, 2.0)).collect();
+        assert!(samples.iter().all(|x| (-0.5..=2.0).contains(x)));
+        assert!(samples.iter().any(|x| *x > 1.0));
+        // far in the tail
+        let value = rng.generate_truncated_normal(0.0, 0.01, 5.0, 6.0);
+        assert!((5.0..=6.0).contains(&value));
+        assert!(rng.try_generate_truncated_normal(0.0, 1.0, 1.0, -1.0).is_err());
+        assert!(rng.try_generate_truncated_normal(0.0, 0.0, -1.0, 1.0).is_err());
+    }
+
+    #[test]
+    fn test_truncated_normal_tail_distribution() {
+        let mut rng = RandomGenerator::<f64>::with_seed(13);
+        let n = 20000.0;
+        // mean of the standard normal truncated to [3, inf) is pdf(3) / (1 - cdf(3)) = 3.2831
+        let mean = (0..20000).map(|_| rng.generate_truncated_normal(0.0, 1.0, 3.0, f64::INFINITY)).sum::<f64>() / n;
+        assert!((mean - 3.2831).abs() < 0.01);
+        // mirrored to the lower tail
+        let mean = (0..20000).map(|_| rng.generate_truncated_normal(0.0, 1.0, f64::NEG_INFINITY, -3.0)).sum::<f64>() / n;
+        assert!((mean + 3.2831).abs() < 0.01);
+        // narrow tail range, the density falls towards the upper end so the mean is below the midpoint 5.05
+        let samples: Vec<f64> = (0..20000).map(|_| rng.generate_truncated_normal(0.0, 1.0, 5.0, 5.1)).collect();
+        assert!(samples.iter().all(|x| (5.0..=5.1).contains(x)));
+        let mean = samples.iter().sum::<f64>() / n;
+        assert!((mean - 5.0458).abs() < 0.002);
+    }
+
+    #[test]
+    fn test_generate_bernoulli() {
+        let mut rng = RandomGenerator::<f64>::with_seed(4);
+        let hits = (0..10000).filter(|_| rng.generate_bernoulli(0.3)).count();
+        assert!((2800..3200).contains(&hits));
+        assert!(!rng.generate_bernoulli(0.0));
+        assert!(rng.generate_bernoulli(1.0));
+        assert!(rng.try_generate_bernoulli(1.5).is_err());
+    }
+
+    #[test]
+    fn test_generate_categorical() {
+        let mut rng = RandomGenerator::<f64>::with_seed(5);
+        let mut counts = [0; 3];
+        for _ in 0..10000 {
+            counts[rng.generate_categorical(&[1.0, 0.0, 3.0])] += 1;
+        }
+        assert_eq!(counts[1], 0);
+        assert!((2300..2700).contains(&counts[0]));
+        assert!(rng.try_generate_categorical(&[0.0, 0.0]).is_err());
+        assert!(rng.try_generate_categorical(&[1.0, -1.0]).is_err());
+        assert!(rng.try_generate_categorical(&[f64::NAN]).is_err());
+    }
+
+    #[test]
+    fn test_shuffle_and_choose() {
+        let mut rng = RandomGenerator::<f64>::with_seed(6);
+        let mut items: Vec<usize> = (0..20).collect();
+        rng.shuffle(&mut items);
+        assert_ne!(items, (0..20).collect::<Vec<usize>>());
+        items.sort_unstable();
+        assert_eq!(items, (0..20).collect::<Vec<usize>>());
+
+        let chosen = rng.choose_k(&items, 5);
+        assert_eq!(chosen.len(), 5);
+        let mut unique = chosen.clone();
+        unique.sort_unstable();
+        unique.dedup();
+        assert_eq!(unique.len(), 5);
+        assert_eq!(rng.choose_k(&items, 20).len(), 20);
+        assert!(rng.try_choose_k(&items, 21).is_err());
+
+        let mut other = RandomGenerator::<f64>::with_seed(6);
+        let mut reshuffled: Vec<usize> = (0..20).collect();
+        other.shuffle(&mut reshuffled);
+        let mut first: Vec<usize> = (0..20).collect();
+        RandomGenerator::<f64>::with_seed(6).shuffle(&mut first);
+        assert_eq!(first, reshuffled);
     }
 }
@@ -0,0 +1,427 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::math::matrix::Matrix;
+use crate::math::activation::Activation;
+use crate::neat::innovation::InnovationTracker;
+use crate::error::{Error, OrPanic};
+
+/*
+    a feed-forward network described by node and connection genes (NEAT)
+    unlike the layered Network, any node can connect to any later node, which allows skip connections
+    and sparse topologies
+
+    every non-input node computes activation(bias + sum(weight * value of the source node))
+    over its enabled incoming connections, the outputs are the output nodes in the order of their ids
+*/
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeKind {
+    Input,
+    Hidden,
+    Output
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NodeGene<T> {
+    pub id: usize,
+    pub kind: NodeKind,
+    pub activation: Activation,
+    pub bias: T
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConnectionGene<T> {
+    pub innovation: usize,
+    pub from: usize,
+    pub to: usize,
+    pub weight: T,
+    pub enabled: bool
+}
+
+// weights of the terms of the compatibility distance
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CompatibilityCoefficients {
+    pub excess: f64,
+    pub disjoint: f64,
+    pub weight: f64
+}
+
+impl Default for CompatibilityCoefficients {
+    // the values of the original NEAT paper
+    fn default() -> Self {
+        Self { excess: 1.0, disjoint: 1.0, weight: 0.4 }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Genome<T> {
+    // sorted by id
+    nodes: Vec<NodeGene<T>>,
+    // sorted by innovation number
+    connections: Vec<ConnectionGene<T>>,
+    input_size: usize,
+    output_size: usize
+}
+
+impl<T> Genome<T>
+where
+    T: num::Float + Default + fmt::Debug
+{
+    pub fn new(input_size: usize, output_size: usize, output_activation: Activation, tracker: &mut InnovationTracker) -> Self {
+        Self::try_new(input_size, output_size, output_activation, tracker).or_panic()
+    }
+
+    // every input connected to every output with zero weights
+    pub fn try_new(input_size: usize, output_size: usize, output_activation: Activation, tracker: &mut InnovationTracker) -> Result<Self, Error> {
+        if input_size == 0 || output_size == 0 {
+            return Err(Error::ZeroDimension);
+        }
+        if output_activation == Activation::Softmax {
+            return Err(Error::InvalidParameter("genome nodes need an element-wise activation"));
+        }
+        let mut nodes = Vec::with_capacity(input_size + output_size);
+        for id in 0..input_size {
+            nodes.push(NodeGene { id, kind: NodeKind::Input, activation: Activation::Identity, bias: T::zero() });
+        }
+        for id in input_size..input_size + output_size {
+            nodes.push(NodeGene { id, kind: NodeKind::Output, activation: output_activation, bias: T::zero() });
+        }
+        let mut connections = Vec::with_capacity(input_size * output_size);
+        for from in 0..input_size {
+            for to in input_size..input_size + output_size {
+                connections.push(ConnectionGene { innovation: tracker.connection(from, to), from, to, weight: T::zero(), enabled: true });
+            }
+        }
+        connections.sort_by_key(|c| c.innovation);
+        Ok(Self { nodes, connections, input_size, output_size })
+    }
+
+    pub fn get_input_size(&self) -> usize {
+        self.input_size
+    }
+
+    pub fn get_output_size(&self) -> usize {
+        self.output_size
+    }
+
+    pub fn nodes(&self) -> &[NodeGene<T>] {
+        &self.nodes
+    }
+
+    pub fn nodes_mut(&mut self) -> &mut [NodeGene<T>] {
+        &mut self.nodes
+    }
+
+    pub fn connections(&self) -> &[ConnectionGene<T>] {
+        &self.connections
+    }
+
+    // enabling a connection or changing its nodes can close a cycle, forward then returns CyclicGenome
+    pub fn connections_mut(&mut self) -> &mut [ConnectionGene<T>] {
+        &mut self.connections
+    }
+
+    pub fn has_node(&self, id: usize) -> bool {
+        self.nodes.binary_search_by_key(&id, |n| n.id).is_ok()
+    }
+
+    pub fn get_node(&self, id: usize) -> Option<&NodeGene<T>> {
+        self.nodes.binary_search_by_key(&id, |n| n.id).ok().map(|index| &self.nodes[index])
+    }
+
+    // connection between the two nodes, enabled or not
+    pub fn has_connection(&self, from: usize, to: usize) -> bool {
+        self.connections.iter().any(|c| c.from == from && c.to == to)
+    }
+
+    pub fn add_node(&mut self, node: NodeGene<T>) {
+        self.try_add_node(node).or_panic()
+    }
+
+    // only hidden nodes can be added, their ids follow the fixed input and output ids,
+    // a node whose id already exists is ignored
+    pub fn try_add_node(&mut self, node: NodeGene<T>) -> Result<(), Error> {
+        if node.kind != NodeKind::Hidden || node.id < self.input_size + self.output_size {
+            return Err(Error::InvalidNode(node.id));
+        }
+        if let Err(index) = self.nodes.binary_search_by_key(&node.id, |n| n.id) {
+            self.nodes.insert(index, node);
+        }
+        Ok(())
+    }
+
+    pub fn add_connection(&mut self, connection: ConnectionGene<T>) {
+        self.try_add_connection(connection).or_panic()
+    }
+
+    // both nodes have to exist and an enabled connection must not close a cycle, the genome has to stay feed-forward
+    pub fn try_add_connection(&mut self, connection: ConnectionGene<T>) -> Result<(), Error> {
+        for id in [connection.from, connection.to] {
+            if !self.has_node(id) {
+                return Err(Error::UnknownNode(id));
+            }
+        }
+        if connection.enabled && self.is_reachable(connection.to, connection.from) {
+            return Err(Error::CyclicGenome);
+        }
+        let index = self.connections.partition_point(|c| c.innovation < connection.innovation);
+        self.connections.insert(index, connection);
+        Ok(())
+    }
+
+    // true if a path of enabled connections leads from one node to the other
+    pub fn is_reachable(&self, from: usize, to: usize) -> bool {
+        let mut stack = vec![from];
+        let mut visited = vec![from];
+        while let Some(node) = stack.pop() {
+            if node == to {
+                return true;
+            }
+            for connection in self.connections.iter().filter(|c| c.enabled && c.from == node) {
+                if !visited.contains(&connection.to) {
+                    visited.push(connection.to);
+                    stack.push(connection.to);
+                }
+            }
+        }
+        false
+    }
+
+    pub fn forward(&self, input: &Matrix<T>) -> Matrix<T> {
+        self.try_forward(input).or_panic()
+    }
+
+    // same contract as Network::forward, a 1xn input gives a 1xo output
+    pub fn try_forward(&self, input: &Matrix<T>) -> Result<Matrix<T>, Error> {
+        if input.cols() != self.input_size {
+            return Err(Error::InputSizeMismatch { expected: self.input_size, actual: input.cols() });
+        }
+        if input.rows() != 1 {
+            return Err(Error::InputNotSingleRow(input.rows()));
+        }
+        // nodes and connections can be edited through nodes_mut and connections_mut, so nothing is taken for granted
+        let positions: HashMap<usize, usize> = self.nodes.iter().enumerate().map(|(i, n)| (n.id, i)).collect();
+        let mut values = vec![T::zero(); self.nodes.len()];
+        for (i, node) in self.nodes.iter().enumerate() {
+            if node.kind == NodeKind::Input {
+                values[i] = *input[0].get(node.id).ok_or(Error::InvalidNode(node.id))?;
+            }
+        }
+        // the pre-activation of every node starts with its bias, the nodes are visited in topological order
+        let mut sums: Vec<T> = self.nodes.iter().map(|n| n.bias).collect();
+        for position in self.topological_order(&positions)? {
+            let node = &self.nodes[position];
+            if node.kind != NodeKind::Input {
+                values[position] = node.activation.try_compute(sums[position])?;
+            }
+            for connection in self.connections.iter().filter(|c| c.enabled && c.from == node.id) {
+                let target = Self::position(&positions, connection.to)?;
+                sums[target] = sums[target] + connection.weight * values[position];
+            }
+        }
+        let mut output = Matrix::new(1, self.output_size);
+        for (i, (position, node)) in self.nodes.iter().enumerate().filter(|(_, n)| n.kind == NodeKind::Output).enumerate() {
+            if i >= self.output_size {
+                return Err(Error::InvalidNode(node.id));
+            }
+            output[0][i] = values[position];
+        }
+        Ok(output)
+    }
+
+    fn position(positions: &HashMap<usize, usize>, id: usize) -> Result<usize, Error> {
+        positions.get(&id).copied().ok_or(Error::UnknownNode(id))
+    }
+
+    // positions of the nodes so that every node comes after all sources of its enabled connections (Kahn)
+    // nodes on a cycle never become ready, so a short order means the genome is not feed-forward
+    fn topological_order(&self, positions: &HashMap<usize, usize>) -> Result<Vec<usize>, Error> {
+        let mut incoming = vec![0; self.nodes.len()];
+        for connection in self.connections.iter().filter(|c| c.enabled) {
+            Self::position(positions, connection.from)?;
+            incoming[Self::position(positions, connection.to)?] += 1;
+        }
+        let mut ready: Vec<usize> = (0..self.nodes.len()).filter(|&i| incoming[i] == 0).rev().collect();
+        let mut order = Vec::with_capacity(self.nodes.len());
+        while let Some(position) = ready.pop() {
+            order.push(position);
+            let id = self.nodes[position].id;
+            for connection in self.connections.iter().filter(|c| c.enabled && c.from == id) {
+                let target = Self::position(positions, connection.to)?;
+                incoming[target] -= 1;
+                if incoming[target] == 0 {
+                    ready.push(target);
+                }
+            }
+        }
+        if order.len() < self.nodes.len() {
+            return Err(Error::CyclicGenome);
+        }
+        Ok(order)
+    }
+
+    // distance used for speciation: c_e * E / N + c_d * D / N + c_w * W
+    // E excess and D disjoint genes, W average weight difference of the matching genes,
+    // N number of genes of the larger genome (1 for small genomes below 20 genes)
+    pub fn compatibility_distance(&self, other: &Genome<T>, coefficients: &CompatibilityCoefficients) -> f64 {
+        let (mut i, mut j) = (0, 0);
+        let (mut matching, mut disjoint, mut weight_difference) = (0, 0, 0.0);
+        while i < self.connections.len() && j < other.connections.len() {
+            let (a, b) = (&self.connections[i], &other.connections[j]);
+            match a.innovation.cmp(&b.innovation) {
+                std::cmp::Ordering::Equal => {
+                    matching += 1;
+                    weight_difference += (a.weight - b.weight).abs().to_f64().unwrap();
+                    i += 1;
+                    j += 1;
+                },
+                std::cmp::Ordering::Less => { disjoint += 1; i += 1; },
+                std::cmp::Ordering::Greater => { disjoint += 1; j += 1; }
+            }
+        }
+        let excess = (self.connections.len() - i) + (other.connections.len() - j);
+        let larger = self.connections.len().max(other.connections.len());
+        let n = if larger < 20 { 1.0 } else { larger as f64 };
+        let average_weight_difference = if matching > 0 { weight_difference / matching as f64 } else { 0.0 };
+        coefficients.excess * excess as f64 / n + coefficients.disjoint * disjoint as f64 / n + coefficients.weight * average_weight_difference
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(values: &[f64]) -> Matrix<f64> {
+        let mut m = Matrix::new(1, values.len());
+        m[0].copy_from_slice(values);
+        m
+    }
+
+    #[test]
+    fn new_test() {
+        let mut tracker = InnovationTracker::new(3, 2);
+        let genome = Genome::<f64>::new(3, 2, Activation::Sigmoid, &mut tracker);
+        assert_eq!(genome.nodes().len(), 5);
+        assert_eq!(genome.connections().len(), 6);
+        assert!(genome.connections().windows(2).all(|w| w[0].innovation < w[1].innovation));
+        // a second genome gets the same innovation numbers
+        let other = Genome::<f64>::new(3, 2, Activation::Sigmoid, &mut tracker);
+        assert_eq!(genome.connections(), other.connections());
+        assert_eq!(Genome::<f64>::try_new(0, 2, Activation::Sigmoid, &mut tracker), Err(Error::ZeroDimension));
+        assert!(Genome::<f64>::try_new(2, 2, Activation::Softmax, &mut tracker).is_err());
+    }
+
+    #[test]
+    fn forward_test() {
+        let mut tracker = InnovationTracker::new(2, 1);
+        let mut genome = Genome::<f64>::new(2, 1, Activation::Identity, &mut tracker);
+        genome.connections_mut()[0].weight = 2.0;
+        genome.connections_mut()[1].weight = -1.0;
+        // hidden node 3 between input 0 and output 2, plus a skip connection from input 1
+        genome.add_node(NodeGene { id: 3, kind: NodeKind::Hidden, activation: Activation::ReLU, bias: 0.5 });
+        genome.add_connection(ConnectionGene { innovation: tracker.connection(0, 3), from: 0, to: 3, weight: 3.0, enabled: true });
+        genome.add_connection(ConnectionGene { innovation: tracker.connection(3, 2), from: 3, to: 2, weight: 0.5, enabled: true });
+        genome.nodes_mut()[2].bias = 0.25;
+        let output = genome.forward(&row(&[1.0, 2.0]));
+        // output = 0.25 + 2 * 1 - 1 * 2 + 0.5 * relu(0.5 + 3 * 1)
+        assert!((output[0][0] - 2.0).abs() < 1e-12);
+        // disabled connections do not contribute
+        genome.connections_mut()[0].enabled = false;
+        let output = genome.forward(&row(&[1.0, 2.0]));
+        assert!((output[0][0] - 0.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn forward_errors_test() {
+        let mut tracker = InnovationTracker::new(2, 1);
+        let genome = Genome::<f64>::new(2, 1, Activation::Sigmoid, &mut tracker);
+        assert_eq!(genome.try_forward(&row(&[1.0])).err(), Some(Error::InputSizeMismatch { expected: 2, actual: 1 }));
+        assert_eq!(genome.try_forward(&Matrix::new(2, 2)).err(), Some(Error::InputNotSingleRow(2)));
+        // softmax set on a node after creation is an error, not a panic
+        let mut softmax = genome.clone();
+        softmax.nodes_mut()[2].activation = Activation::Softmax;
+        assert!(matches!(softmax.try_forward(&row(&[1.0, 1.0])), Err(Error::InvalidParameter(_))));
+        assert_eq!(genome.forward(&row(&[1.0, 1.0]))[0][0], 0.5);
+    }
+
+    #[test]
+    fn reachable_test() {
+        let mut tracker = InnovationTracker::new(2, 1);
+        let mut genome = Genome::<f64>::new(2, 1, Activation::Sigmoid, &mut tracker);
+        assert!(genome.is_reachable(0, 2));
+        assert!(!genome.is_reachable(2, 0));
+        assert!(!genome.is_reachable(0, 1));
+        genome.connections_mut()[0].enabled = false;
+        assert!(!genome.is_reachable(0, 2));
+    }
+
+    #[test]
+    fn cycle_test() {
+        let mut tracker = InnovationTracker::new(2, 1);
+        let mut genome = Genome::<f64>::new(2, 1, Activation::Identity, &mut tracker);
+        genome.add_node(NodeGene { id: 3, kind: NodeKind::Hidden, activation: Activation::Identity, bias: 0.0 });
+        genome.add_connection(ConnectionGene { innovation: tracker.connection(0, 3), from: 0, to: 3, weight: 1.0, enabled: true });
+        genome.add_connection(ConnectionGene { innovation: tracker.connection(3, 2), from: 3, to: 2, weight: 1.0, enabled: true });
+        // 2 -> 3 closes the cycle 3 -> 2 -> 3, a self connection is the shortest cycle
+        let back = ConnectionGene { innovation: tracker.connection(2, 3), from: 2, to: 3, weight: 1.0, enabled: true };
+        assert_eq!(genome.try_add_connection(back), Err(Error::CyclicGenome));
+        let own = ConnectionGene { innovation: tracker.connection(3, 3), from: 3, to: 3, weight: 1.0, enabled: true };
+        assert_eq!(genome.try_add_connection(own), Err(Error::CyclicGenome));
+        assert_eq!(genome.connections().len(), 4);
+        // a disabled connection is fine until it gets enabled
+        assert!(genome.try_add_connection(ConnectionGene { enabled: false, ..back }).is_ok());
+        assert!(genome.try_forward(&row(&[1.0, 1.0])).is_ok());
+        genome.connections_mut().iter_mut().for_each(|c| c.enabled = true);
+        assert_eq!(genome.try_forward(&row(&[1.0, 1.0])).err(), Some(Error::CyclicGenome));
+    }
+
+    #[test]
+    fn invalid_nodes_test() {
+        let mut tracker = InnovationTracker::new(2, 1);
+        let mut genome = Genome::<f64>::new(2, 1, Activation::Identity, &mut tracker);
+        // connections need existing nodes
+        let missing = ConnectionGene { innovation: tracker.connection(0, 42), from: 0, to: 42, weight: 1.0, enabled: true };
+        assert_eq!(genome.try_add_connection(missing), Err(Error::UnknownNode(42)));
+        assert_eq!(genome.try_add_connection(ConnectionGene { from: 42, to: 2, ..missing }), Err(Error::UnknownNode(42)));
+        // inputs and outputs are fixed, hidden ids start after them
+        let hidden = NodeGene { id: 3, kind: NodeKind::Hidden, activation: Activation::Identity, bias: 0.0 };
+        assert_eq!(genome.try_add_node(NodeGene { kind: NodeKind::Input, ..hidden }), Err(Error::InvalidNode(3)));
+        assert_eq!(genome.try_add_node(NodeGene { kind: NodeKind::Output, ..hidden }), Err(Error::InvalidNode(3)));
+        assert_eq!(genome.try_add_node(NodeGene { id: 2, ..hidden }), Err(Error::InvalidNode(2)));
+        assert!(genome.try_add_node(hidden).is_ok());
+        assert_eq!(genome.nodes().len(), 4);
+        // edits through the mutable views are reported by forward instead of panicking
+        let mut edited = genome.clone();
+        edited.connections_mut()[0].to = 42;
+        assert_eq!(edited.try_forward(&row(&[1.0, 1.0])).err(), Some(Error::UnknownNode(42)));
+        let mut edited = genome.clone();
+        edited.nodes_mut()[0].id = 7;
+        assert!(edited.try_forward(&row(&[1.0, 1.0])).is_err());
+        let mut edited = genome.clone();
+        edited.nodes_mut()[3].kind = NodeKind::Output;
+        assert_eq!(edited.try_forward(&row(&[1.0, 1.0])).err(), Some(Error::InvalidNode(3)));
+        let mut edited = genome.clone();
+        edited.nodes_mut()[3].kind = NodeKind::Input;
+        assert_eq!(edited.try_forward(&row(&[1.0, 1.0])).err(), Some(Error::InvalidNode(3)));
+    }
+
+    #[test]
+    fn compatibility_distance_test() {
+        let mut tracker = InnovationTracker::new(2, 1);
+        let mut first = Genome::<f64>::new(2, 1, Activation::Sigmoid, &mut tracker);
+        let mut second = first.clone();
+        let coefficients = CompatibilityCoefficients::default();
+        assert_eq!(first.compatibility_distance(&second, &coefficients), 0.0);
+        second.connections_mut()[0].weight = 1.0;
+        assert!((first.compatibility_distance(&second, &coefficients) - 0.4 * 0.5).abs() < 1e-12);
+        // innovation 2 only in the first (disjoint), innovation 3 only in the second (excess)
+        first.add_node(NodeGene { id: 3, kind: NodeKind::Hidden, activation: Activation::Sigmoid, bias: 0.0 });
+        first.add_connection(ConnectionGene { innovation: tracker.connection(0, 3), from: 0, to: 3, weight: 0.0, enabled: true });
+        second.add_node(NodeGene { id: 3, kind: NodeKind::Hidden, activation: Activation::Sigmoid, bias: 0.0 });
+        second.add_connection(ConnectionGene { innovation: tracker.connection(3, 2), from: 3, to: 2, weight: 0.0, enabled: true });
+        let distance = first.compatibility_distance(&second, &coefficients);
+        assert!((distance - (1.0 + 1.0 + 0.4 * 0.5)).abs() < 1e-12);
+        assert_eq!(distance, second.compatibility_distance(&first, &coefficients));
+    }
+}
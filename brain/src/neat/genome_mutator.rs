@@ -0,0 +1,257 @@
+use std::fmt;
+
+use crate::math::random::RandomGenerator;
+use crate::math::activation::Activation;
+use crate::neat::genome::{Genome, NodeGene, NodeKind, ConnectionGene};
+use crate::neat::innovation::InnovationTracker;
+use crate::error::{Error, OrPanic};
+
+/*
+    NEAT mutations of a genome, each rolled once per call of mutate except the weight perturbation
+
+    add connection      connects two unconnected nodes without creating a cycle
+    add node            splits an enabled connection a -> b into a -> new (weight 1) and new -> b (old weight),
+                        the old connection is disabled
+    weight perturbation every weight and bias moves by a random number from [lower, upper)
+*/
+
+#[derive(Debug, Clone)]
+pub struct GenomeMutatorConfig<T> {
+    pub random_generator_seed: u64,
+    pub add_connection_probability: T,
+    pub add_node_probability: T,
+    pub weight_mutation_probability: T,
+    pub weight_change_lower_limit: T,
+    pub weight_change_upper_limit: T,
+    // range of the weights of new connections
+    pub new_weight_lower_limit: T,
+    pub new_weight_upper_limit: T,
+    pub hidden_activation: Activation
+}
+
+impl<T: num::Float> Default for GenomeMutatorConfig<T> {
+    fn default() -> Self {
+        Self {
+            random_generator_seed: 0,
+            add_connection_probability: T::zero(),
+            add_node_probability: T::zero(),
+            weight_mutation_probability: T::zero(),
+            weight_change_lower_limit: -T::one(),
+            weight_change_upper_limit: T::one(),
+            new_weight_lower_limit: -T::one(),
+            new_weight_upper_limit: T::one(),
+            hidden_activation: Activation::Sigmoid
+        }
+    }
+}
+
+pub struct GenomeMutator<T> {
+    config: GenomeMutatorConfig<T>,
+    value_generator: RandomGenerator<T>,
+    probability_generator: RandomGenerator<T>,
+    index_generator: RandomGenerator<usize>
+}
+
+impl<T> GenomeMutator<T>
+where
+    T: rand::distr::uniform::SampleUniform + num::Float + Default + fmt::Debug
+{
+    pub fn new(config: GenomeMutatorConfig<T>) -> Self {
+        Self::try_new(config).or_panic()
+    }
+
+    pub fn try_new(config: GenomeMutatorConfig<T>) -> Result<Self, Error> {
+        let probabilities = [config.add_connection_probability, config.add_node_probability, config.weight_mutation_probability];
+        if probabilities.iter().any(|p| *p < T::zero() || *p > T::one()) {
+            return Err(Error::InvalidParameter("mutation probabilities must be in [0, 1]"));
+        }
+        if config.weight_change_lower_limit >= config.weight_change_upper_limit || config.new_weight_lower_limit >= config.new_weight_upper_limit {
+            return Err(Error::InvalidRange);
+        }
+        if config.hidden_activation == Activation::Softmax {
+            return Err(Error::InvalidParameter("genome nodes need an element-wise activation"));
+        }
+        Ok(Self {
+            value_generator: RandomGenerator::with_seed(config.random_generator_seed),
+            probability_generator: RandomGenerator::with_seed(config.random_generator_seed),
+            index_generator: RandomGenerator::with_seed(config.random_generator_seed),
+            config
+        })
+    }
+
+    // draws every weight and bias (except the ones of the inputs) from the new weight range
+    pub fn randomize(&mut self, genome: &mut Genome<T>) {
+        for connection in genome.connections_mut() {
+            connection.weight = self.value_generator.generate(self.config.new_weight_lower_limit, self.config.new_weight_upper_limit);
+        }
+        for node in genome.nodes_mut().iter_mut().filter(|n| n.kind != NodeKind::Input) {
+            node.bias = self.value_generator.generate(self.config.new_weight_lower_limit, self.config.new_weight_upper_limit);
+        }
+    }
+
+    pub fn mutate(&mut self, genome: &mut Genome<T>, tracker: &mut InnovationTracker) {
+        if self.roll(self.config.add_connection_probability) {
+            self.add_connection(genome, tracker);
+        }
+        if self.roll(self.config.add_node_probability) {
+            self.add_node(genome, tracker);
+        }
+        if self.config.weight_mutation_probability > T::zero() {
+            let (lower, upper) = (self.config.weight_change_lower_limit, self.config.weight_change_upper_limit);
+            for i in 0..genome.connections().len() {
+                if self.roll(self.config.weight_mutation_probability) {
+                    let change = self.value_generator.generate(lower, upper);
+                    let connection = &mut genome.connections_mut()[i];
+                    connection.weight = connection.weight + change;
+                }
+            }
+            for i in 0..genome.nodes().len() {
+                if genome.nodes()[i].kind != NodeKind::Input && self.roll(self.config.weight_mutation_probability) {
+                    let change = self.value_generator.generate(lower, upper);
+                    let node = &mut genome.nodes_mut()[i];
+                    node.bias = node.bias + change;
+                }
+            }
+        }
+    }
+
+    // returns false if every possible connection already exists
+    pub fn add_connection(&mut self, genome: &mut Genome<T>, tracker: &mut InnovationTracker) -> bool {
+        let mut candidates = Vec::new();
+        for from in genome.nodes().iter().filter(|n| n.kind != NodeKind::Output) {
+            for to in genome.nodes().iter().filter(|n| n.kind != NodeKind::Input && n.id != from.id) {
+                // a path back from the target would close a cycle
+                if !genome.has_connection(from.id, to.id) && !genome.is_reachable(to.id, from.id) {
+                    candidates.push((from.id, to.id));
+                }
+            }
+        }
+        if candidates.is_empty() {
+            return false;
+        }
+        let (from, to) = candidates[self.index_generator.generate(0, candidates.len())];
+        let weight = self.value_generator.generate(self.config.new_weight_lower_limit, self.config.new_weight_upper_limit);
+        genome.add_connection(ConnectionGene { innovation: tracker.connection(from, to), from, to, weight, enabled: true });
+        true
+    }
+
+    // returns false if there is no enabled connection to split
+    pub fn add_node(&mut self, genome: &mut Genome<T>, tracker: &mut InnovationTracker) -> bool {
+        let enabled: Vec<usize> = (0..genome.connections().len()).filter(|&i| genome.connections()[i].enabled).collect();
+        if enabled.is_empty() {
+            return false;
+        }
+        let index = enabled[self.index_generator.generate(0, enabled.len())];
+        let split = genome.connections()[index];
+        genome.connections_mut()[index].enabled = false;
+        let mut id = tracker.split(split.innovation);
+        if genome.has_node(id) {
+            // this genome split the same connection before
+            id = tracker.new_node();
+        }
+        genome.add_node(NodeGene { id, kind: NodeKind::Hidden, activation: self.config.hidden_activation, bias: T::zero() });
+        genome.add_connection(ConnectionGene { innovation: tracker.connection(split.from, id), from: split.from, to: id, weight: T::one(), enabled: true });
+        genome.add_connection(ConnectionGene { innovation: tracker.connection(id, split.to), from: id, to: split.to, weight: split.weight, enabled: true });
+        true
+    }
+
+    fn roll(&mut self, probability: T) -> bool {
+        probability > T::zero() && self.probability_generator.generate(T::zero(), T::one()) < probability
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::matrix::Matrix;
+
+    fn setup() -> (Genome<f64>, InnovationTracker) {
+        let mut tracker = InnovationTracker::new(3, 2);
+        let genome = Genome::new(3, 2, Activation::Identity, &mut tracker);
+        (genome, tracker)
+    }
+
+    fn input() -> Matrix<f64> {
+        let mut m = Matrix::new(1, 3);
+        m[0][0] = 0.5;
+        m[0][1] = -1.0;
+        m[0][2] = 2.0;
+        m
+    }
+
+    #[test]
+    fn try_new_test() {
+        assert!(GenomeMutator::<f64>::try_new(GenomeMutatorConfig { add_node_probability: 2.0, ..Default::default() }).is_err());
+        assert!(GenomeMutator::<f64>::try_new(GenomeMutatorConfig { new_weight_lower_limit: 1.0, new_weight_upper_limit: 1.0, ..Default::default() }).is_err());
+        assert!(GenomeMutator::<f64>::try_new(GenomeMutatorConfig { hidden_activation: Activation::Softmax, ..Default::default() }).is_err());
+        assert!(GenomeMutator::<f64>::try_new(GenomeMutatorConfig::default()).is_ok());
+    }
+
+    #[test]
+    fn add_node_test() {
+        let (mut genome, mut tracker) = setup();
+        let mut mutator = GenomeMutator::new(GenomeMutatorConfig { random_generator_seed: 1, hidden_activation: Activation::Identity, ..Default::default() });
+        mutator.randomize(&mut genome);
+        let expected = genome.forward(&input());
+        assert!(mutator.add_node(&mut genome, &mut tracker));
+        assert_eq!(genome.nodes().len(), 6);
+        assert_eq!(genome.connections().len(), 8);
+        assert_eq!(genome.connections().iter().filter(|c| !c.enabled).count(), 1);
+        // with an identity hidden node the split changes nothing
+        let output = genome.forward(&input());
+        for col in 0..2 {
+            assert!((output[0][col] - expected[0][col]).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn same_split_same_innovation_test() {
+        let (first, mut tracker) = setup();
+        let mut second = first.clone();
+        let mut first = first;
+        GenomeMutator::new(GenomeMutatorConfig { random_generator_seed: 2, ..Default::default() }).add_node(&mut first, &mut tracker);
+        GenomeMutator::new(GenomeMutatorConfig { random_generator_seed: 2, ..Default::default() }).add_node(&mut second, &mut tracker);
+        assert_eq!(first.connections(), second.connections());
+        assert_eq!(first.nodes(), second.nodes());
+    }
+
+    #[test]
+    fn add_connection_test() {
+        let (mut genome, mut tracker) = setup();
+        let mut mutator = GenomeMutator::new(GenomeMutatorConfig { random_generator_seed: 3, ..Default::default() });
+        // the initial genome is fully connected
+        assert!(!mutator.add_connection(&mut genome, &mut tracker));
+        for _ in 0..5 {
+            mutator.add_node(&mut genome, &mut tracker);
+        }
+        let mut added = 0;
+        while mutator.add_connection(&mut genome, &mut tracker) {
+            added += 1;
+        }
+        assert!(added > 0);
+        // still acyclic: every node is evaluated
+        for connection in genome.connections().iter().filter(|c| c.enabled) {
+            assert!(!genome.is_reachable(connection.to, connection.from));
+        }
+        assert_eq!(genome.forward(&input()).cols(), 2);
+    }
+
+    #[test]
+    fn mutate_test() {
+        let (mut genome, mut tracker) = setup();
+        let mut mutator = GenomeMutator::new(GenomeMutatorConfig {
+            random_generator_seed: 4,
+            add_connection_probability: 1.0,
+            add_node_probability: 1.0,
+            weight_mutation_probability: 0.5,
+            ..Default::default()
+        });
+        for _ in 0..20 {
+            mutator.mutate(&mut genome, &mut tracker);
+        }
+        assert!(genome.nodes().len() > 5);
+        assert!(genome.connections().iter().any(|c| c.weight != 0.0 && c.weight != 1.0));
+        let output = genome.forward(&input());
+        assert!(output[0][0].is_finite() && output[0][1].is_finite());
+    }
+}
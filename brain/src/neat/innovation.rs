@@ -0,0 +1,82 @@
+use std::collections::HashMap;
+
+/*
+    hands out node ids and innovation numbers for the whole population,
+    the same structural change in two genomes gets the same numbers, which lets crossover
+    and speciation line up the genes of different genomes
+
+    node ids 0..inputs are the inputs, inputs..inputs + outputs the outputs, hidden nodes follow
+*/
+
+#[derive(Debug, Clone)]
+pub struct InnovationTracker {
+    next_node_id: usize,
+    next_innovation: usize,
+    // (from, to) -> innovation number of the connection
+    connections: HashMap<(usize, usize), usize>,
+    // innovation number of a split connection -> id of the node created by the split
+    splits: HashMap<usize, usize>
+}
+
+impl InnovationTracker {
+    pub fn new(input_size: usize, output_size: usize) -> Self {
+        Self {
+            next_node_id: input_size + output_size,
+            next_innovation: 0,
+            connections: HashMap::new(),
+            splits: HashMap::new()
+        }
+    }
+
+    // innovation number of the connection, a new one if the connection was never seen before
+    pub fn connection(&mut self, from: usize, to: usize) -> usize {
+        let next_innovation = &mut self.next_innovation;
+        *self.connections.entry((from, to)).or_insert_with(|| {
+            *next_innovation += 1;
+            *next_innovation - 1
+        })
+    }
+
+    // id of the node splitting the connection, the same for every genome splitting it
+    pub fn split(&mut self, innovation: usize) -> usize {
+        let next_node_id = &mut self.next_node_id;
+        *self.splits.entry(innovation).or_insert_with(|| {
+            *next_node_id += 1;
+            *next_node_id - 1
+        })
+    }
+
+    // a node id nobody used before, for genomes that split the same connection twice
+    pub fn new_node(&mut self) -> usize {
+        self.next_node_id += 1;
+        self.next_node_id - 1
+    }
+
+    pub fn get_num_innovations(&self) -> usize {
+        self.next_innovation
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn connection_test() {
+        let mut tracker = InnovationTracker::new(2, 1);
+        assert_eq!(tracker.connection(0, 2), 0);
+        assert_eq!(tracker.connection(1, 2), 1);
+        assert_eq!(tracker.connection(0, 2), 0);
+        assert_eq!(tracker.connection(2, 0), 2);
+        assert_eq!(tracker.get_num_innovations(), 3);
+    }
+
+    #[test]
+    fn split_test() {
+        let mut tracker = InnovationTracker::new(2, 1);
+        assert_eq!(tracker.split(0), 3);
+        assert_eq!(tracker.split(1), 4);
+        assert_eq!(tracker.split(0), 3);
+        assert_eq!(tracker.new_node(), 5);
+    }
+}
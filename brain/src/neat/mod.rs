@@ -0,0 +1,4 @@
+pub mod innovation;
+pub mod genome;
+pub mod genome_mutator;
+pub mod speciation;
@@ -0,0 +1,111 @@
+use std::fmt;
+
+use crate::neat::genome::{Genome, CompatibilityCoefficients};
+
+/*
+    groups genomes into species by compatibility distance, so new structures can be protected
+    from competing with the optimized ones right away
+
+    every genome joins the first species whose representative is closer than the threshold,
+    otherwise it founds a new species, the first member of each species becomes the next representative
+*/
+
+#[derive(Debug, Clone)]
+pub struct Species<T> {
+    pub id: usize,
+    pub representative: Genome<T>,
+    // indices of the genomes of the last speciate call
+    pub members: Vec<usize>
+}
+
+pub struct Speciation<T> {
+    threshold: f64,
+    coefficients: CompatibilityCoefficients,
+    species: Vec<Species<T>>,
+    next_species_id: usize
+}
+
+impl<T> Speciation<T>
+where
+    T: num::Float + Default + fmt::Debug
+{
+    pub fn new(threshold: f64, coefficients: CompatibilityCoefficients) -> Self {
+        Self {
+            threshold,
+            coefficients,
+            species: Vec::new(),
+            next_species_id: 0
+        }
+    }
+
+    pub fn species(&self) -> &[Species<T>] {
+        &self.species
+    }
+
+    pub fn speciate(&mut self, genomes: &[Genome<T>]) -> &[Species<T>] {
+        for species in self.species.iter_mut() {
+            species.members.clear();
+        }
+        for (index, genome) in genomes.iter().enumerate() {
+            let found = self.species.iter_mut()
+                .find(|species| species.representative.compatibility_distance(genome, &self.coefficients) < self.threshold);
+            match found {
+                Some(species) => species.members.push(index),
+                None => {
+                    self.species.push(Species { id: self.next_species_id, representative: genome.clone(), members: vec![index] });
+                    self.next_species_id += 1;
+                }
+            }
+        }
+        // species without members went extinct
+        self.species.retain(|species| !species.members.is_empty());
+        for species in self.species.iter_mut() {
+            species.representative = genomes[species.members[0]].clone();
+        }
+        &self.species
+    }
+
+    // explicit fitness sharing: the fitness of every genome divided by the size of its species
+    pub fn shared_fitness(&self, fitness: &[f64]) -> Vec<f64> {
+        let mut shared = fitness.to_vec();
+        for species in self.species.iter() {
+            for &member in species.members.iter() {
+                shared[member] = fitness[member] / species.members.len() as f64;
+            }
+        }
+        shared
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::activation::Activation;
+    use crate::neat::innovation::InnovationTracker;
+
+    #[test]
+    fn speciate_test() {
+        let mut tracker = InnovationTracker::new(2, 1);
+        let base = Genome::<f64>::new(2, 1, Activation::Sigmoid, &mut tracker);
+        let mut close = base.clone();
+        close.connections_mut()[0].weight = 0.5;
+        let mut far = base.clone();
+        for connection in far.connections_mut() {
+            connection.weight = 10.0;
+        }
+        let genomes = vec![base.clone(), far.clone(), close, far];
+        let mut speciation = Speciation::new(1.0, CompatibilityCoefficients::default());
+        let species = speciation.speciate(&genomes);
+        assert_eq!(species.len(), 2);
+        assert_eq!(species[0].members, vec![0, 2]);
+        assert_eq!(species[1].members, vec![1, 3]);
+
+        let shared = speciation.shared_fitness(&[4.0, 6.0, 2.0, 8.0]);
+        assert_eq!(shared, vec![2.0, 3.0, 1.0, 4.0]);
+
+        // the far species goes extinct, the ids stay stable
+        let species = speciation.speciate(&[base]);
+        assert_eq!(species.len(), 1);
+        assert_eq!(species[0].id, 0);
+    }
+}
@@ -1,4 +1,8 @@
+#[allow(clippy::module_inception)]
 pub mod network;
 pub mod network_initializer;
 pub mod network_mutator;
-pub mod network_creator;
\ No newline at end of file
+pub mod network_creator;
+pub mod network_builder;
+pub mod network_crossover;
+pub mod network_serializer;
\ No newline at end of file
@@ -1,5 +1,9 @@
 use crate::math::matrix::Matrix;
 use crate::math::dimensions::Dimensions;
+use crate::math::activation::Activation;
+use crate::training::loss::Loss;
+use crate::training::gradients::Gradients;
+use crate::error::{Error, OrPanic};
 use std::marker::PhantomData;
 use std::fmt;
 
@@ -8,6 +12,7 @@ use std::fmt;
     [1xn]       [1xm]       [1xo]       layers
                 [1xm]       [1xo]       biases
           [nxm]       [mxo]             weights
+                 f_0         f_1        activations
 
 */
 
@@ -16,10 +21,57 @@ pub struct Network<T> {
     layers: Vec<Matrix<T>>,
     weights: Vec<Matrix<T>>,
     biases: Vec<Matrix<T>>,
+    activations: Vec<Activation>,
     valid_network: bool,
+    // whether layers holds the values of a forward_cached or forward_batch pass with the current
+    // parameters and activations, which is what backward differentiates
+    cached: bool,
     _marker: PhantomData<T>,
 }
 
+/*
+    the parameters of a network as one flat vector, layer by layer the weights row by row followed by the biases
+    [w_0 (row-major) | b_0 | w_1 (row-major) | b_1 | ...]
+*/
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParameterKind {
+    Weight,
+    Bias
+}
+
+// position of a parameter, row is always 0 for biases
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParameterIndex {
+    pub kind: ParameterKind,
+    pub layer: usize,
+    pub row: usize,
+    pub col: usize
+}
+
+#[derive(Debug)]
+pub struct ParameterMut<'a, T> {
+    pub index: ParameterIndex,
+    pub value: &'a mut T
+}
+
+// values of the layers after the input for forward_with, reusing one buffer avoids reallocating them on every call
+#[derive(Debug, Clone, Default)]
+pub struct ForwardScratch<T> {
+    layers: Vec<Matrix<T>>
+}
+
+impl<T> ForwardScratch<T> {
+    pub fn new() -> Self {
+        Self { layers: Vec::new() }
+    }
+
+    // the activated values of layer index (1 is the first hidden layer) of the last forward_with call
+    pub fn get_layer(&self, index: usize) -> Option<&Matrix<T>> {
+        index.checked_sub(1).and_then(|i| self.layers.get(i))
+    }
+}
+
 impl<T> Network<T>
 where
     T: Default + Clone + Copy + PartialOrd + num::Zero + num::Float + num::Signed,
@@ -27,69 +79,356 @@ where
     T: fmt::Debug
 {
     pub fn new(num_layers: usize) -> Self {
+        Self::try_new(num_layers).or_panic()
+    }
+
+    pub fn try_new(num_layers: usize) -> Result<Self, Error> {
         if num_layers < 3 {
-            panic!("Less than 3 layers makes no sense");
+            return Err(Error::TooFewLayers(num_layers));
         }
         
         let layers = vec![Matrix::new(1, 1); num_layers];
         let weights = vec![Matrix::new(1, 1); num_layers - 1];
         let biases = vec![Matrix::new(1, 1); num_layers - 1];
+        let activations = vec![Activation::default(); num_layers - 1];
         
-        Self {
+        Ok(Self {
             layers,
             weights,
             biases,
+            activations,
             valid_network: true,
+            cached: false,
             _marker: PhantomData,
-        }
+        })
     }
 
     pub fn reshape(&mut self, num_layers: usize) {
+        self.try_reshape(num_layers).or_panic()
+    }
+
+    pub fn try_reshape(&mut self, num_layers: usize) -> Result<(), Error> {
         if num_layers < 3 {
-            panic!("Less than 3 layers makes no sense");
+            return Err(Error::TooFewLayers(num_layers));
         }
         self.layers = vec![Matrix::new(1, 1); num_layers];
         self.weights = vec![Matrix::new(1, 1); num_layers - 1];
         self.biases = vec![Matrix::new(1, 1); num_layers - 1];
+        self.activations = vec![Activation::default(); num_layers - 1];
         self.valid_network = true;
+        self.cached = false;
+        Ok(())
     }
 
     pub fn resize(&mut self, layer_index: usize, size: usize) {
-        self.assert_valid_layer(layer_index);
-        self.layers[layer_index] = Matrix::new(1, size);
+        self.try_resize(layer_index, size).or_panic()
+    }
+
+    pub fn try_resize(&mut self, layer_index: usize, size: usize) -> Result<(), Error> {
+        self.check_valid_layer(layer_index)?;
+        // a zero size is rejected here with ZeroDimension, before any matrix of the network is replaced
+        let layer = Matrix::try_new(1, size)?;
         
         if self.is_input_layer(layer_index) {
             let next_layer_size = self.layers[layer_index + 1].cols();
-            self.weights[layer_index] = Matrix::new(size, next_layer_size);
+            self.weights[layer_index] = Matrix::try_new(size, next_layer_size)?;
         } else if self.is_output_layer(layer_index) {
             let prev_layer_size = self.layers[layer_index - 1].cols();
-            self.weights[layer_index - 1] = Matrix::new(prev_layer_size, size);
-            self.biases[layer_index - 1] = Matrix::new(1, size);
+            self.weights[layer_index - 1] = Matrix::try_new(prev_layer_size, size)?;
+            self.biases[layer_index - 1] = Matrix::try_new(1, size)?;
         } else {
             let prev_layer_size = self.layers[layer_index - 1].cols();
             let next_layer_size = self.layers[layer_index + 1].cols();
-            self.weights[layer_index - 1] = Matrix::new(prev_layer_size, size);
-            self.weights[layer_index] = Matrix::new(size, next_layer_size);
-            self.biases[layer_index - 1] = Matrix::new(1, size);
+            self.weights[layer_index - 1] = Matrix::try_new(prev_layer_size, size)?;
+            self.weights[layer_index] = Matrix::try_new(size, next_layer_size)?;
+            self.biases[layer_index - 1] = Matrix::try_new(1, size)?;
+        }
+        self.layers[layer_index] = layer;
+        self.cached = false;
+        Ok(())
+    }
+
+    // inference, the network stays untouched so it can be shared between threads
+    // the layer values of a previous forward_cached stay as they are, backward still uses those
+    pub fn forward(&self, input: &Matrix<T>) -> Matrix<T> {
+        self.try_forward(input).or_panic()
+    }
+
+    pub fn try_forward(&self, input: &Matrix<T>) -> Result<Matrix<T>, Error> {
+        self.check_single_row(input)?;
+        let mut scratch = ForwardScratch::new();
+        self.try_forward_with(input, &mut scratch).cloned()
+    }
+
+    // inference keeping the layer values in the scratch buffer, works for batches (one sample per row) as well
+    pub fn forward_with<'a>(&self, input: &Matrix<T>, scratch: &'a mut ForwardScratch<T>) -> &'a Matrix<T> {
+        self.try_forward_with(input, scratch).or_panic()
+    }
+
+    pub fn try_forward_with<'a>(&self, input: &Matrix<T>, scratch: &'a mut ForwardScratch<T>) -> Result<&'a Matrix<T>, Error> {
+        if !self.valid_network {
+            return Err(Error::InvalidNetwork);
+        }
+        if input.cols() != self.layers[0].cols() {
+            return Err(Error::InputSizeMismatch { expected: self.layers[0].cols(), actual: input.cols() });
         }
+        scratch.layers.resize_with(self.weights.len(), || Matrix::new(1, 1));
+        for i in 0..self.weights.len() {
+            let (done, rest) = scratch.layers.split_at_mut(i);
+            let previous = if i == 0 { input } else { &done[i - 1] };
+            previous.try_mul_into(&self.weights[i], &mut rest[0])?;
+            rest[0].try_add_row_assign(&self.biases[i])?;
+            self.activations[i].apply(&mut rest[0]);
+        }
+        Ok(scratch.layers.last().unwrap())
     }
 
-    pub fn forward(&mut self, input: &Matrix<T>) -> Matrix<T> {
+    // forward pass that keeps the values of every layer in the network, needed by backward
+    pub fn forward_cached(&mut self, input: &Matrix<T>) -> Matrix<T> {
+        self.try_forward_cached(input).or_panic()
+    }
+
+    pub fn try_forward_cached(&mut self, input: &Matrix<T>) -> Result<Matrix<T>, Error> {
+        self.check_single_row(input)?;
+        self.try_forward_batch(input)
+    }
+
+    fn check_single_row(&self, input: &Matrix<T>) -> Result<(), Error> {
+        if input.cols() != self.layers[0].cols() {
+            return Err(Error::InputSizeMismatch { expected: self.layers[0].cols(), actual: input.cols() });
+        }
+        if input.rows() != 1 {
+            return Err(Error::InputNotSingleRow(input.rows()));
+        }
+        Ok(())
+    }
+
+    // evaluates every row of the mxn input at once and returns an mxo output
+    // the cached layers become mx? matrices, so backward works on the whole batch (forward_with does not cache)
+    pub fn forward_batch(&mut self, input: &Matrix<T>) -> Matrix<T> {
+        self.try_forward_batch(input).or_panic()
+    }
+
+    pub fn try_forward_batch(&mut self, input: &Matrix<T>) -> Result<Matrix<T>, Error> {
         if !self.valid_network {
-            panic!("Network is invalid");
+            return Err(Error::InvalidNetwork);
         }
         if input.cols() != self.layers[0].cols() {
-            panic!("Input size mismatch with input layer");
+            return Err(Error::InputSizeMismatch { expected: self.layers[0].cols(), actual: input.cols() });
         }
 
+        // a failing pass leaves the layers half updated
+        self.cached = false;
         self.layers[0] = input.clone(); // Assign input to the first layer
 
         for i in 1..self.layers.len() {
-            self.layers[i] = &(&self.layers[i - 1] * &self.weights[i - 1]) + &self.biases[i - 1];
-            self.layers[i].apply(|x| x.max(T::zero()));
+            // the shapes of stale weights (see resize) are only discovered here
+            self.layers[i] = self.layers[i - 1].try_mul(&self.weights[i - 1])?.try_add_row(&self.biases[i - 1])?;
+            self.activations[i - 1].apply(&mut self.layers[i]);
+        }
+
+        self.cached = true;
+        Ok(self.layers.last().unwrap().clone()) // Return output layer
+    }
+
+    // computes the gradients of the loss for the activations cached by the last forward_cached or forward_batch,
+    // an error if there was none or the parameters, activations or topology changed since then
+    // plain forward and forward_with do not cache, a backward after them refers to the last cached pass
+    pub fn backward(&self, target: &Matrix<T>, loss: Loss) -> Gradients<T> {
+        self.try_backward(target, loss).or_panic()
+    }
+
+    pub fn try_backward(&self, target: &Matrix<T>, loss: Loss) -> Result<Gradients<T>, Error> {
+        if !self.valid_network {
+            return Err(Error::InvalidNetwork);
+        }
+        if !self.cached {
+            return Err(Error::NoCachedForwardPass);
         }
+        let output = self.layers.last().unwrap();
+        if target.get_dimensions() != output.get_dimensions() {
+            return Err(Error::TargetSizeMismatch { expected: output.get_dimensions(), actual: target.get_dimensions() });
+        }
+
+        let mut gradients = Gradients::zeros(self);
+        // gradient of the loss with respect to the activated values of the current layer
+        let mut delta = loss.try_gradient(output, target)?;
 
-        self.layers.last().unwrap().clone() // Return output layer
+        for i in (0..self.weights.len()).rev() {
+            // recompute the pre-activation values of layer i + 1 instead of caching them
+            let pre_activation = self.layers[i].try_mul(&self.weights[i])?.try_add_row(&self.biases[i])?;
+            let delta_z = self.activations[i].backpropagate(&pre_activation, &self.layers[i + 1], &delta);
+            let previous = &self.layers[i];
+            for row in 0..self.weights[i].rows() {
+                for col in 0..self.weights[i].cols() {
+                    let mut sum = T::zero();
+                    for sample in 0..delta_z.rows() {
+                        sum = sum + previous[sample][row] * delta_z[sample][col];
+                    }
+                    gradients.weights[i][row][col] = sum;
+                }
+            }
+            for col in 0..self.biases[i].cols() {
+                let mut sum = T::zero();
+                for sample in 0..delta_z.rows() {
+                    sum = sum + delta_z[sample][col];
+                }
+                gradients.biases[i][0][col] = sum;
+            }
+            if i > 0 {
+                // propagate to the previous layer: delta_z * W^T
+                let mut next_delta = Matrix::new(delta_z.rows(), self.weights[i].rows());
+                for sample in 0..delta_z.rows() {
+                    for row in 0..self.weights[i].rows() {
+                        let mut sum = T::zero();
+                        for col in 0..self.weights[i].cols() {
+                            sum = sum + delta_z[sample][col] * self.weights[i][row][col];
+                        }
+                        next_delta[sample][row] = sum;
+                    }
+                }
+                delta = next_delta;
+            }
+        }
+        Ok(gradients)
+    }
+
+    // inserts a neuron at the given position of a hidden layer, all of its weights and its bias are zero
+    // so the output of the network does not change
+    pub fn insert_neuron(&mut self, layer_index: usize, neuron: usize) {
+        self.try_insert_neuron(layer_index, neuron).or_panic()
+    }
+
+    pub fn try_insert_neuron(&mut self, layer_index: usize, neuron: usize) -> Result<(), Error> {
+        self.check_hidden_layer(layer_index)?;
+        let size = self.layers[layer_index].cols();
+        if neuron > size {
+            return Err(Error::NeuronOutOfRange { layer: layer_index, neuron });
+        }
+        // incoming weights are a column of the previous weight matrix, outgoing ones a row of the next
+        self.weights[layer_index - 1] = Self::with_column(&self.weights[layer_index - 1], neuron, true);
+        self.biases[layer_index - 1] = Self::with_column(&self.biases[layer_index - 1], neuron, true);
+        self.weights[layer_index] = Self::with_row(&self.weights[layer_index], neuron, true);
+        self.layers[layer_index] = Matrix::new(1, size + 1);
+        self.cached = false;
+        Ok(())
+    }
+
+    // removes a neuron of a hidden layer together with its weights and bias
+    pub fn remove_neuron(&mut self, layer_index: usize, neuron: usize) {
+        self.try_remove_neuron(layer_index, neuron).or_panic()
+    }
+
+    pub fn try_remove_neuron(&mut self, layer_index: usize, neuron: usize) -> Result<(), Error> {
+        self.check_hidden_layer(layer_index)?;
+        let size = self.layers[layer_index].cols();
+        if neuron >= size {
+            return Err(Error::NeuronOutOfRange { layer: layer_index, neuron });
+        }
+        if size == 1 {
+            return Err(Error::EmptyLayer(layer_index));
+        }
+        self.weights[layer_index - 1] = Self::with_column(&self.weights[layer_index - 1], neuron, false);
+        self.biases[layer_index - 1] = Self::with_column(&self.biases[layer_index - 1], neuron, false);
+        self.weights[layer_index] = Self::with_row(&self.weights[layer_index], neuron, false);
+        self.layers[layer_index] = Matrix::new(1, size - 1);
+        self.cached = false;
+        Ok(())
+    }
+
+    // inserts a hidden layer in front of layer_index with the size of the layer before it, identity weights,
+    // zero biases and the activation of the hidden layer before it (or after it, right behind the input)
+    // the output does not change if that activation is idempotent on its own outputs, e.g. behind a ReLU or
+    // identity layer, and mostly stays close otherwise
+    pub fn insert_layer(&mut self, layer_index: usize) {
+        self.try_insert_layer(layer_index).or_panic()
+    }
+
+    pub fn try_insert_layer(&mut self, layer_index: usize) -> Result<(), Error> {
+        if layer_index == 0 || layer_index >= self.layers.len() {
+            return Err(Error::NotHiddenLayer(layer_index));
+        }
+        let size = self.layers[layer_index - 1].cols();
+        let mut identity = Matrix::new(size, size);
+        for i in 0..size {
+            identity[i][i] = T::one();
+        }
+        let activation = if layer_index >= 2 {
+            self.activations[layer_index - 2]
+        } else if layer_index < self.layers.len() - 1 {
+            self.activations[layer_index - 1]
+        } else {
+            Activation::Identity
+        };
+        self.layers.insert(layer_index, Matrix::new(1, size));
+        self.weights.insert(layer_index - 1, identity);
+        self.biases.insert(layer_index - 1, Matrix::new(1, size));
+        self.activations.insert(layer_index - 1, activation);
+        self.cached = false;
+        Ok(())
+    }
+
+    // removes a hidden layer, the two weight layers around it are merged into their product
+    // (x * W1 + b1) * W2 + b2 = x * (W1 * W2) + (b1 * W2 + b2)
+    // which drops the activation of the removed layer: exact for an identity layer, an approximation otherwise
+    pub fn remove_layer(&mut self, layer_index: usize) {
+        self.try_remove_layer(layer_index).or_panic()
+    }
+
+    pub fn try_remove_layer(&mut self, layer_index: usize) -> Result<(), Error> {
+        self.check_hidden_layer(layer_index)?;
+        if self.layers.len() == 3 {
+            return Err(Error::TooFewLayers(2));
+        }
+        let weights = self.weights[layer_index - 1].try_mul(&self.weights[layer_index])?;
+        let biases = self.biases[layer_index - 1].try_mul(&self.weights[layer_index])?.try_add(&self.biases[layer_index])?;
+        self.layers.remove(layer_index);
+        self.weights.remove(layer_index);
+        self.biases.remove(layer_index);
+        self.activations.remove(layer_index - 1);
+        self.weights[layer_index - 1] = weights;
+        self.biases[layer_index - 1] = biases;
+        self.cached = false;
+        Ok(())
+    }
+
+    fn check_hidden_layer(&self, layer_index: usize) -> Result<(), Error> {
+        if layer_index == 0 || layer_index >= self.layers.len() - 1 {
+            return Err(Error::NotHiddenLayer(layer_index));
+        }
+        Ok(())
+    }
+
+    // copy of the matrix with a zero column inserted at (or the column removed from) the given index
+    fn with_column(matrix: &Matrix<T>, index: usize, insert: bool) -> Matrix<T> {
+        let cols = if insert { matrix.cols() + 1 } else { matrix.cols() - 1 };
+        let mut result = Matrix::new(matrix.rows(), cols);
+        for row in 0..matrix.rows() {
+            for col in 0..matrix.cols() {
+                match (col.cmp(&index), insert) {
+                    (std::cmp::Ordering::Less, _) => result[row][col] = matrix[row][col],
+                    (_, true) => result[row][col + 1] = matrix[row][col],
+                    (std::cmp::Ordering::Greater, false) => result[row][col - 1] = matrix[row][col],
+                    (std::cmp::Ordering::Equal, false) => {}
+                }
+            }
+        }
+        result
+    }
+
+    // copy of the matrix with a zero row inserted at (or the row removed from) the given index
+    fn with_row(matrix: &Matrix<T>, index: usize, insert: bool) -> Matrix<T> {
+        let rows = if insert { matrix.rows() + 1 } else { matrix.rows() - 1 };
+        let mut result = Matrix::new(rows, matrix.cols());
+        for row in 0..matrix.rows() {
+            match (row.cmp(&index), insert) {
+                (std::cmp::Ordering::Less, _) => result[row].copy_from_slice(&matrix[row]),
+                (_, true) => result[row + 1].copy_from_slice(&matrix[row]),
+                (std::cmp::Ordering::Greater, false) => result[row - 1].copy_from_slice(&matrix[row]),
+                (std::cmp::Ordering::Equal, false) => {}
+            }
+        }
+        result
     }
 }
 
@@ -102,10 +441,11 @@ impl<T> Network<T> {
         index == self.layers.len() - 1
     }
     
-    fn assert_valid_layer(&self, index: usize) {
+    fn check_valid_layer(&self, index: usize) -> Result<(), Error> {
         if index >= self.layers.len() {
-            panic!("Layer does not exist");
+            return Err(Error::LayerOutOfRange(index));
         }
+        Ok(())
     }
     
     pub fn get_num_layers(&self) -> usize {
@@ -119,53 +459,214 @@ impl<T> Network<T> {
     pub fn get_num_bias_layers(&self) -> usize {
         self.biases.len()
     }
+
+    pub fn get_num_activation_layers(&self) -> usize {
+        self.activations.len()
+    }
+
+    fn check_valid_activation_layer(&self, layer_index: usize) -> Result<(), Error> {
+        if layer_index >= self.activations.len() {
+            return Err(Error::ActivationLayerOutOfRange(layer_index));
+        }
+        Ok(())
+    }
+
+    // activation layer i is applied to the output of weight layer i, i.e. it produces layer i + 1
+    pub fn get_activation(&self, activation_layer_index: usize) -> Activation {
+        self.try_get_activation(activation_layer_index).or_panic()
+    }
+
+    pub fn try_get_activation(&self, activation_layer_index: usize) -> Result<Activation, Error> {
+        self.check_valid_activation_layer(activation_layer_index)?;
+        Ok(self.activations[activation_layer_index])
+    }
+
+    pub fn set_activation(&mut self, activation_layer_index: usize, activation: Activation) {
+        self.try_set_activation(activation_layer_index, activation).or_panic()
+    }
+
+    pub fn try_set_activation(&mut self, activation_layer_index: usize, activation: Activation) -> Result<(), Error> {
+        self.check_valid_activation_layer(activation_layer_index)?;
+        self.activations[activation_layer_index] = activation;
+        self.cached = false;
+        Ok(())
+    }
+
+    pub fn set_activations(&mut self, activation: Activation) {
+        for a in self.activations.iter_mut() {
+            *a = activation;
+        }
+        self.cached = false;
+    }
 }
 
 impl<T: Default + Copy> Network<T> {
-    fn assert_valid_weight_layer(&self, layer_index: usize) {
-        assert!(layer_index < self.weights.len(), "Invalid weight layer index");
+    fn check_valid_weight_layer(&self, layer_index: usize) -> Result<(), Error> {
+        if layer_index >= self.weights.len() {
+            return Err(Error::WeightLayerOutOfRange(layer_index));
+        }
+        Ok(())
     }
 
-    fn assert_valid_bias_layer(&self, layer_index: usize) {
-        assert!(layer_index < self.biases.len(), "Invalid bias layer index");
+    fn check_valid_bias_layer(&self, layer_index: usize) -> Result<(), Error> {
+        if layer_index >= self.biases.len() {
+            return Err(Error::BiasLayerOutOfRange(layer_index));
+        }
+        Ok(())
     }
 
     pub fn get_weight(&self, weight_layer_index: usize, row: usize, col: usize) -> T {
-        self.assert_valid_weight_layer(weight_layer_index);
-        let mat = &self.weights[weight_layer_index];
-        mat.assert_valid_element(row, col);
-        mat[row][col]
+        self.try_get_weight(weight_layer_index, row, col).or_panic()
+    }
+
+    pub fn try_get_weight(&self, weight_layer_index: usize, row: usize, col: usize) -> Result<T, Error> {
+        self.check_valid_weight_layer(weight_layer_index)?;
+        self.weights[weight_layer_index].try_get(row, col)
     }
 
     pub fn set_weight(&mut self, weight_layer_index: usize, row: usize, col: usize, value: T) {
-        self.assert_valid_weight_layer(weight_layer_index);
-        let mat = &mut self.weights[weight_layer_index];
-        mat.assert_valid_element(row, col);
-        mat[row][col] = value;
+        self.try_set_weight(weight_layer_index, row, col, value).or_panic()
+    }
+
+    pub fn try_set_weight(&mut self, weight_layer_index: usize, row: usize, col: usize, value: T) -> Result<(), Error> {
+        self.check_valid_weight_layer(weight_layer_index)?;
+        self.weights[weight_layer_index].try_set(row, col, value)?;
+        self.cached = false;
+        Ok(())
     }
 
     pub fn get_bias(&self, bias_layer_index: usize, col: usize) -> T {
-        self.assert_valid_bias_layer(bias_layer_index);
-        let mat = &self.biases[bias_layer_index];
-        mat.assert_valid_element(0, col);
-        mat[0][col]
+        self.try_get_bias(bias_layer_index, col).or_panic()
+    }
+
+    pub fn try_get_bias(&self, bias_layer_index: usize, col: usize) -> Result<T, Error> {
+        self.check_valid_bias_layer(bias_layer_index)?;
+        self.biases[bias_layer_index].try_get(0, col)
     }
 
     pub fn set_bias(&mut self, bias_layer_index: usize, col: usize, value: T) {
-        self.assert_valid_bias_layer(bias_layer_index);
-        let mat = &mut self.biases[bias_layer_index];
-        mat.assert_valid_element(0, col);
-        mat[0][col] = value;
+        self.try_set_bias(bias_layer_index, col, value).or_panic()
+    }
+
+    pub fn try_set_bias(&mut self, bias_layer_index: usize, col: usize, value: T) -> Result<(), Error> {
+        self.check_valid_bias_layer(bias_layer_index)?;
+        self.biases[bias_layer_index].try_set(0, col, value)?;
+        self.cached = false;
+        Ok(())
+    }
+
+    // used by the optimizers, handing out the parameters counts as changing them
+    pub(crate) fn get_weights_mut(&mut self, weight_layer_index: usize) -> &mut Matrix<T> {
+        self.check_valid_weight_layer(weight_layer_index).or_panic();
+        self.cached = false;
+        &mut self.weights[weight_layer_index]
+    }
+
+    pub(crate) fn get_biases_mut(&mut self, bias_layer_index: usize) -> &mut Matrix<T> {
+        self.check_valid_bias_layer(bias_layer_index).or_panic();
+        self.cached = false;
+        &mut self.biases[bias_layer_index]
     }
 
     pub fn get_weights_dimension(&self, weight_layer_index: usize) -> Dimensions {
-        self.assert_valid_weight_layer(weight_layer_index);
-        self.weights[weight_layer_index].get_dimensions()
+        self.try_get_weights_dimension(weight_layer_index).or_panic()
+    }
+
+    pub fn try_get_weights_dimension(&self, weight_layer_index: usize) -> Result<Dimensions, Error> {
+        self.check_valid_weight_layer(weight_layer_index)?;
+        Ok(self.weights[weight_layer_index].get_dimensions())
     }
 
     pub fn get_biases_dimension(&self, bias_layer_index: usize) -> Dimensions {
-        self.assert_valid_bias_layer(bias_layer_index);
-        self.biases[bias_layer_index].get_dimensions()
+        self.try_get_biases_dimension(bias_layer_index).or_panic()
+    }
+
+    pub fn try_get_biases_dimension(&self, bias_layer_index: usize) -> Result<Dimensions, Error> {
+        self.check_valid_bias_layer(bias_layer_index)?;
+        Ok(self.biases[bias_layer_index].get_dimensions())
+    }
+
+    // number of weights and biases
+    pub fn get_num_parameters(&self) -> usize {
+        self.weights.iter().zip(self.biases.iter()).map(|(w, b)| w.as_slice().len() + b.as_slice().len()).sum()
+    }
+
+    pub fn get_parameters(&self) -> Vec<T> {
+        let mut parameters = Vec::with_capacity(self.get_num_parameters());
+        for (weights, biases) in self.weights.iter().zip(self.biases.iter()) {
+            parameters.extend_from_slice(weights.as_slice());
+            parameters.extend_from_slice(biases.as_slice());
+        }
+        parameters
+    }
+
+    pub fn set_parameters(&mut self, parameters: &[T]) {
+        self.try_set_parameters(parameters).or_panic()
+    }
+
+    pub fn try_set_parameters(&mut self, parameters: &[T]) -> Result<(), Error> {
+        let expected = self.get_num_parameters();
+        if parameters.len() != expected {
+            return Err(Error::ParameterCountMismatch { expected, actual: parameters.len() });
+        }
+        let mut rest = parameters;
+        for (weights, biases) in self.weights.iter_mut().zip(self.biases.iter_mut()) {
+            for matrix in [weights, biases] {
+                let (head, tail) = rest.split_at(matrix.as_slice().len());
+                matrix.as_mut_slice().copy_from_slice(head);
+                rest = tail;
+            }
+        }
+        self.cached = false;
+        Ok(())
+    }
+
+    // position of every parameter in the order of the flat vector
+    pub fn parameter_indices(&self) -> Vec<ParameterIndex> {
+        let mut indices = Vec::with_capacity(self.get_num_parameters());
+        for (layer, (weights, biases)) in self.weights.iter().zip(self.biases.iter()).enumerate() {
+            for row in 0..weights.rows() {
+                for col in 0..weights.cols() {
+                    indices.push(ParameterIndex { kind: ParameterKind::Weight, layer, row, col });
+                }
+            }
+            for col in 0..biases.cols() {
+                indices.push(ParameterIndex { kind: ParameterKind::Bias, layer, row: 0, col });
+            }
+        }
+        indices
+    }
+
+    // every parameter in the order of the flat vector together with its position
+    pub fn parameters_mut(&mut self) -> impl Iterator<Item = ParameterMut<'_, T>> {
+        self.cached = false;
+        self.weights.iter_mut().zip(self.biases.iter_mut()).enumerate().flat_map(|(layer, (weights, biases))| {
+            let cols = weights.cols();
+            let weights = weights.as_mut_slice().iter_mut().enumerate().map(move |(i, value)| ParameterMut {
+                index: ParameterIndex { kind: ParameterKind::Weight, layer, row: i / cols, col: i % cols },
+                value
+            });
+            let biases = biases.as_mut_slice().iter_mut().enumerate().map(move |(col, value)| ParameterMut {
+                index: ParameterIndex { kind: ParameterKind::Bias, layer, row: 0, col },
+                value
+            });
+            weights.chain(biases)
+        })
+    }
+
+    // None if the network has no parameter at that position
+    pub fn get_parameter(&self, index: ParameterIndex) -> Option<T> {
+        match index.kind {
+            ParameterKind::Weight => self.try_get_weight(index.layer, index.row, index.col).ok(),
+            ParameterKind::Bias => self.try_get_bias(index.layer, index.col).ok()
+        }
+    }
+
+    pub fn set_parameter(&mut self, index: ParameterIndex, value: T) {
+        match index.kind {
+            ParameterKind::Weight => self.set_weight(index.layer, index.row, index.col, value),
+            ParameterKind::Bias => self.set_bias(index.layer, index.col, value)
+        }
     }
 }
 
@@ -175,6 +676,7 @@ impl<T: fmt::Debug> fmt::Display for Network<T> {
             writeln!(f, "Layer {}: {:?}", i, layer)?;
             if i > 0 {
                 writeln!(f, "Biases: {:?}", self.biases[i - 1])?;
+                writeln!(f, "Activation: {:?}", self.activations[i - 1])?;
             }
             if i < self.weights.len() {
                 writeln!(f, "Weights: {:?}", self.weights[i])?;
@@ -192,7 +694,8 @@ impl<T: PartialEq> PartialEq for Network<T> {
         if self.weights.len() != other.weights.len() || self.biases.len() != other.biases.len() {
             return false;
         }
-        self.weights.iter().zip(&other.weights).all(|(w1, w2)| w1 == w2)
+        self.activations == other.activations
+            && self.weights.iter().zip(&other.weights).all(|(w1, w2)| w1 == w2)
             && self.biases.iter().zip(&other.biases).all(|(b1, b2)| b1 == b2)
     }
 }
@@ -283,7 +786,7 @@ mod tests {
 
     #[test]
     fn test_forward() {
-        let mut network = create_test_network();
+        let network = create_test_network();
         let input = Matrix::new(1, 3); // Input size must match input layer
 
         // Forward pass should work without panicking
@@ -296,13 +799,228 @@ mod tests {
     #[test]
     #[should_panic(expected = "Input size mismatch with input layer")]
     fn test_forward_invalid_input() {
-        let mut network = create_test_network();
+        let network = create_test_network();
         let invalid_input = Matrix::new(1, 5); // Incorrect input size
         network.forward(&invalid_input);
     }
 
 
 
+    #[test]
+    #[should_panic(expected = "Input must be a single row")]
+    fn test_forward_multiple_rows() {
+        let network = create_test_network();
+        network.forward(&Matrix::new(2, 3));
+    }
+
+    #[test]
+    fn test_forward_without_caching() {
+        let mut network = create_test_network();
+        crate::network::network_initializer::NetworkInitializer::new(4, -1.0, 1.0).initialize(&mut network);
+        network.set_activation(1, Activation::Softmax);
+        let mut input = Matrix::new(3, 3);
+        for row in 0..3 {
+            for col in 0..3 {
+                input[row][col] = (row as f64) * 0.5 - (col as f64) * 0.3;
+            }
+        }
+        let cached_layers = network.layers.clone();
+        let mut scratch = ForwardScratch::new();
+        // the same buffer serves single rows and batches
+        for rows in [1, 3, 1] {
+            let mut batch = Matrix::new(rows, 3);
+            for row in 0..rows {
+                batch[row].copy_from_slice(&input[row]);
+            }
+            let output = network.forward_with(&batch, &mut scratch).clone();
+            assert_eq!(scratch.get_layer(2), Some(&output));
+            assert_eq!(scratch.get_layer(1).unwrap().get_dimensions().rows, rows);
+            assert!(scratch.get_layer(0).is_none());
+            assert_eq!(network.layers, cached_layers);
+            assert_eq!(output, network.clone().forward_batch(&batch));
+        }
+        let single = Matrix::new(1, 3);
+        assert_eq!(network.forward(&single), network.forward_cached(&single));
+        assert_eq!(network.layers.last().unwrap(), &network.forward(&single));
+    }
+
+    #[test]
+    fn test_forward_batch() {
+        let mut network = create_test_network();
+        let mut initializer = crate::network::network_initializer::NetworkInitializer::new(7, -1.0, 1.0);
+        initializer.initialize(&mut network);
+        let mut input = Matrix::new(5, 3);
+        for row in 0..5 {
+            for col in 0..3 {
+                input[row][col] = (row as f64) * 0.37 - (col as f64) * 0.81 + 0.1;
+            }
+        }
+        for activation in [Activation::ReLU, Activation::Tanh, Activation::Softmax].iter() {
+            network.set_activations(*activation);
+            let output = network.forward_batch(&input);
+            assert_eq!(output.rows(), 5);
+            assert_eq!(output.cols(), 2);
+            for row in 0..5 {
+                let mut single = Matrix::new(1, 3);
+                for col in 0..3 {
+                    single[0][col] = input[row][col];
+                }
+                let expected = network.forward(&single);
+                // bit-identical to the row by row evaluation
+                assert_eq!(output[row], expected[0]);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Input size mismatch with input layer")]
+    fn test_forward_batch_invalid_input() {
+        let mut network = create_test_network();
+        network.forward_batch(&Matrix::new(4, 2));
+    }
+
+    #[test]
+    fn test_backward_needs_cached_pass() {
+        let mut network = create_test_network();
+        crate::network::network_initializer::NetworkInitializer::new(5, -1.0, 1.0).initialize(&mut network);
+        let input = Matrix::new(1, 3);
+        let target = Matrix::new(1, 2);
+        assert_eq!(network.try_backward(&target, Loss::MeanSquaredError).err(), Some(Error::NoCachedForwardPass));
+        network.forward_cached(&input);
+        assert!(network.try_backward(&target, Loss::MeanSquaredError).is_ok());
+        // forward only reads the network, the values of the cached pass still fit the parameters
+        network.forward(&input);
+        assert!(network.try_backward(&target, Loss::MeanSquaredError).is_ok());
+        // every change of the parameters, activations or shape throws the cached values away
+        let changes: [fn(&mut Network<f64>); 7] = [
+            |n| n.set_weight(0, 0, 0, 0.5),
+            |n| n.set_bias(1, 0, 0.5),
+            |n| n.set_parameters(&vec![0.5; n.get_num_parameters()]),
+            |n| { n.parameters_mut().next(); },
+            |n| n.set_activation(0, Activation::Sigmoid),
+            |n| {
+                let gradients = n.backward(&Matrix::new(1, 2), Loss::MeanSquaredError);
+                crate::training::optimizer::Optimizer::step(&mut crate::training::optimizer::Sgd::new(0.1, 0.0), n, &gradients);
+            },
+            |n| n.insert_neuron(1, 0)
+        ];
+        for change in changes {
+            network.forward_batch(&input);
+            assert!(network.try_backward(&target, Loss::MeanSquaredError).is_ok());
+            change(&mut network);
+            assert_eq!(network.try_backward(&target, Loss::MeanSquaredError).err(), Some(Error::NoCachedForwardPass));
+        }
+    }
+
+    #[test]
+    fn test_backward_batch() {
+        // the gradient of a batch is the average of the gradients of its samples
+        let mut network = create_test_network();
+        let mut initializer = crate::network::network_initializer::NetworkInitializer::new(3, -1.0, 1.0);
+        initializer.initialize(&mut network);
+        network.set_activations(Activation::Tanh);
+        let mut input = Matrix::new(2, 3);
+        let mut target = Matrix::new(2, 2);
+        input[0][0] = 0.5;
+        input[1][2] = -0.5;
+        target[0][1] = 1.0;
+        target[1][0] = 1.0;
+        network.forward_batch(&input);
+        let batch = network.backward(&target, Loss::MeanSquaredError);
+        let mut expected = Gradients::zeros(&network);
+        for row in 0..2 {
+            let mut single_input = Matrix::new(1, 3);
+            let mut single_target = Matrix::new(1, 2);
+            for col in 0..3 { single_input[0][col] = input[row][col]; }
+            for col in 0..2 { single_target[0][col] = target[row][col]; }
+            network.forward_cached(&single_input);
+            expected.accumulate(&network.backward(&single_target, Loss::MeanSquaredError));
+        }
+        expected.scale(0.5);
+        for (a, b) in batch.weights.iter().zip(&expected.weights) {
+            for row in 0..a.rows() {
+                for col in 0..a.cols() {
+                    assert!((a[row][col] - b[row][col]).abs() < 1e-12);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_default_activations() {
+        let network = create_test_network();
+        assert_eq!(network.get_num_activation_layers(), 2);
+        assert_eq!(network.get_activation(0), Activation::ReLU);
+        assert_eq!(network.get_activation(1), Activation::ReLU);
+    }
+
+    #[test]
+    fn test_set_activation() {
+        let mut network = create_test_network();
+        network.set_activation(1, Activation::Sigmoid);
+        assert_eq!(network.get_activation(0), Activation::ReLU);
+        assert_eq!(network.get_activation(1), Activation::Sigmoid);
+        network.set_activations(Activation::Tanh);
+        assert_eq!(network.get_activation(0), Activation::Tanh);
+        assert_eq!(network.get_activation(1), Activation::Tanh);
+        // reshaping resets the activations
+        network.reshape(4);
+        for i in 0..network.get_num_activation_layers() {
+            assert_eq!(network.get_activation(i), Activation::ReLU);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid activation layer index")]
+    fn test_set_activation_invalid_layer() {
+        let mut network = create_test_network();
+        network.set_activation(2, Activation::Sigmoid);
+    }
+
+    #[test]
+    fn test_activation_equality() {
+        let network_0 = create_test_network();
+        let mut network_1 = create_test_network();
+        assert!(network_0 == network_1);
+        network_1.set_activation(1, Activation::Identity);
+        assert!(network_0 != network_1);
+    }
+
+    #[test]
+    fn test_forward_output_activation() {
+        let mut network = create_test_network();
+        network.set_bias(1, 0, -1.0);
+        network.set_bias(1, 1, 2.0);
+        let input = Matrix::new(1, 3);
+        // relu clips the negative output
+        let output = network.forward(&input);
+        assert_eq!(output[0][0], 0.0);
+        assert_eq!(output[0][1], 2.0);
+        // identity lets it through
+        network.set_activation(1, Activation::Identity);
+        let output = network.forward(&input);
+        assert_eq!(output[0][0], -1.0);
+        assert_eq!(output[0][1], 2.0);
+        // sigmoid squashes it
+        network.set_activation(1, Activation::Sigmoid);
+        let output = network.forward(&input);
+        assert!((output[0][0] - 0.2689414213699951).abs() < 1e-12);
+        assert!((output[0][1] - 0.8807970779778823).abs() < 1e-12);
+        // softmax normalizes it
+        network.set_activation(1, Activation::Softmax);
+        let output = network.forward(&input);
+        assert!((output[0][0] + output[0][1] - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_display_activation() {
+        let mut network = create_test_network();
+        network.set_activation(1, Activation::Softmax);
+        let text = format!("{}", network);
+        assert!(text.contains("Activation: ReLU"));
+        assert!(text.contains("Activation: Softmax"));
+    }
+
     #[test]
     fn test_forward_propagation() {
         let mut network = Network::new(3); // panic if could not create network
@@ -4467,4 +5185,129 @@ mod tests {
         assert!((output[0][8] - 2.906571691180753).abs() < 1e-9);
         assert!((output[0][9] - 7.079195908228607).abs() < 1e-9);
     }
-}
\ No newline at end of file
+
+    fn random_network(sizes: &[usize], seed: u64) -> Network<f64> {
+        let mut network = Network::<f64>::new(sizes.len());
+        for (i, size) in sizes.iter().enumerate() {
+            network.resize(i, *size);
+        }
+        network.set_activations(Activation::Tanh);
+        crate::network::network_initializer::NetworkInitializer::new(seed, -1.0, 1.0).initialize(&mut network);
+        network
+    }
+
+    fn sample_input() -> Matrix<f64> {
+        let mut input = Matrix::new(1, 3);
+        input[0][0] = 0.3;
+        input[0][1] = -0.8;
+        input[0][2] = 0.5;
+        input
+    }
+
+    #[test]
+    fn test_insert_remove_neuron() {
+        let mut network = random_network(&[3, 4, 2], 1);
+        let original = network.clone();
+        let expected = network.forward(&sample_input());
+        network.insert_neuron(1, 2);
+        assert_eq!(network.get_weights_dimension(0).cols, 5);
+        assert_eq!(network.get_biases_dimension(0).cols, 5);
+        assert_eq!(network.get_weights_dimension(1).rows, 5);
+        // the existing weights moved around the new neuron
+        assert_eq!(network.get_weight(0, 1, 3), original.get_weight(0, 1, 2));
+        assert_eq!(network.get_weight(1, 4, 1), original.get_weight(1, 3, 1));
+        assert_eq!(network.get_weight(0, 1, 2), 0.0);
+        assert_eq!(network.forward(&sample_input()), expected);
+        network.remove_neuron(1, 2);
+        assert!(network == original);
+        network.remove_neuron(1, 0);
+        assert_eq!(network.get_weight(0, 2, 0), original.get_weight(0, 2, 1));
+        assert_eq!(network.get_bias(0, 2), original.get_bias(0, 3));
+    }
+
+    #[test]
+    fn test_structural_errors() {
+        let mut network = random_network(&[3, 1, 2], 1);
+        assert_eq!(network.try_insert_neuron(0, 0), Err(Error::NotHiddenLayer(0)));
+        assert_eq!(network.try_insert_neuron(2, 0), Err(Error::NotHiddenLayer(2)));
+        assert_eq!(network.try_insert_neuron(1, 2), Err(Error::NeuronOutOfRange { layer: 1, neuron: 2 }));
+        assert_eq!(network.try_remove_neuron(1, 0), Err(Error::EmptyLayer(1)));
+        assert_eq!(network.try_remove_layer(1), Err(Error::TooFewLayers(2)));
+        assert_eq!(network.try_insert_layer(0), Err(Error::NotHiddenLayer(0)));
+        assert_eq!(network.try_insert_layer(3), Err(Error::NotHiddenLayer(3)));
+        assert!(network == random_network(&[3, 1, 2], 1));
+    }
+
+    #[test]
+    fn test_insert_remove_layer() {
+        let close = |a: &Matrix<f64>, b: &Matrix<f64>| (0..2).all(|col| (a[0][col] - b[0][col]).abs() < 1e-12);
+        let mut network = random_network(&[3, 4, 2], 2);
+        network.set_activations(Activation::ReLU);
+        let expected = network.forward(&sample_input());
+        // behind a ReLU layer the copied ReLU sees non-negative values only, the output does not change
+        network.insert_layer(2);
+        assert_eq!(network.get_num_layers(), 4);
+        assert_eq!(network.get_activation(1), Activation::ReLU);
+        assert!(close(&network.forward(&sample_input()), &expected));
+        // merging it again drops a ReLU on non-negative values, which is exact as well
+        network.remove_layer(2);
+        assert_eq!(network.get_num_layers(), 3);
+        assert!(close(&network.forward(&sample_input()), &expected));
+        // right behind the input the layer takes the activation of the hidden layer after it,
+        // the negative input is cut off, so the output only stays similar
+        network.insert_layer(1);
+        assert_eq!(network.get_activation(0), Activation::ReLU);
+        assert_eq!(network.get_weights_dimension(0).cols, 3);
+        assert_eq!(network.forward(&sample_input()).cols(), 2);
+        // any hidden layer can be merged, dropping its activation keeps the shapes consistent
+        let mut network = random_network(&[3, 4, 5, 2], 3);
+        assert_eq!(network.get_activation(0), Activation::Tanh);
+        network.remove_layer(1);
+        assert_eq!(network.get_num_layers(), 3);
+        assert_eq!(network.get_weights_dimension(0).rows, 3);
+        assert_eq!(network.get_weights_dimension(0).cols, 5);
+        assert_eq!(network.get_biases_dimension(0).cols, 5);
+        assert_eq!(network.get_activation(0), Activation::Tanh);
+        assert_eq!(network.forward(&sample_input()).cols(), 2);
+        // an identity layer merges exactly
+        let mut network = random_network(&[3, 4, 5, 2], 4);
+        network.set_activation(0, Activation::Identity);
+        let expected = network.forward(&sample_input());
+        network.remove_layer(1);
+        assert!(close(&network.forward(&sample_input()), &expected));
+    }
+
+    #[test]
+    fn test_parameters() {
+        let mut network = create_test_network();
+        assert_eq!(network.get_num_parameters(), 3 * 4 + 4 + 4 * 2 + 2);
+        let values: Vec<f64> = (0..26).map(|i| i as f64).collect();
+        network.set_parameters(&values);
+        assert_eq!(network.get_parameters(), values);
+        assert_eq!(network.get_weight(0, 1, 2), 6.0);
+        assert_eq!(network.get_bias(0, 3), 15.0);
+        assert_eq!(network.get_weight(1, 3, 1), 23.0);
+        assert_eq!(network.get_bias(1, 0), 24.0);
+        assert_eq!(network.try_set_parameters(&values[1..]), Err(Error::ParameterCountMismatch { expected: 26, actual: 25 }));
+    }
+
+    #[test]
+    fn test_parameters_mut() {
+        let mut network = create_test_network();
+        let indices = network.parameter_indices();
+        assert_eq!(indices.len(), network.get_num_parameters());
+        for (i, parameter) in network.parameters_mut().enumerate() {
+            assert_eq!(parameter.index, indices[i]);
+            *parameter.value = i as f64;
+        }
+        assert_eq!(indices[6], ParameterIndex { kind: ParameterKind::Weight, layer: 0, row: 1, col: 2 });
+        assert_eq!(indices[15], ParameterIndex { kind: ParameterKind::Bias, layer: 0, row: 0, col: 3 });
+        for (i, index) in indices.iter().enumerate() {
+            assert_eq!(network.get_parameter(*index), Some(i as f64));
+        }
+        let missing = ParameterIndex { kind: ParameterKind::Weight, layer: 2, row: 0, col: 0 };
+        assert_eq!(network.get_parameter(missing), None);
+        network.set_parameter(indices[24], -1.0);
+        assert_eq!(network.get_bias(1, 0), -1.0);
+    }
+}
@@ -0,0 +1,208 @@
+use std::marker::PhantomData;
+use std::fmt;
+
+use crate::math::activation::Activation;
+use crate::network::network::Network;
+use crate::error::{Error, OrPanic};
+
+/*
+    describes the whole topology up front and creates a network with consistent shapes
+
+    NetworkBuilder::<f32>::new()
+        .input(4)
+        .hidden(8, Activation::ReLU)
+        .hidden(8, Activation::ReLU)
+        .output(2, Activation::Softmax)
+        .build()
+
+    the activation of a layer is the one applied to the values flowing into it,
+    the input layer has none
+*/
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LayerSpec {
+    pub size: usize,
+    pub activation: Activation
+}
+
+impl LayerSpec {
+    pub fn new(size: usize, activation: Activation) -> Self {
+        Self { size, activation }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct NetworkBuilder<T> {
+    input_size: Option<usize>,
+    hidden_layers: Vec<LayerSpec>,
+    output_layer: Option<LayerSpec>,
+    _marker: PhantomData<T>
+}
+
+impl<T> Default for NetworkBuilder<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> NetworkBuilder<T> {
+    pub fn new() -> Self {
+        Self {
+            input_size: None,
+            hidden_layers: Vec::new(),
+            output_layer: None,
+            _marker: PhantomData
+        }
+    }
+
+    pub fn input(mut self, size: usize) -> Self {
+        self.input_size = Some(size);
+        self
+    }
+
+    // hidden layers are stacked in the order they are added
+    pub fn hidden(mut self, size: usize, activation: Activation) -> Self {
+        self.hidden_layers.push(LayerSpec::new(size, activation));
+        self
+    }
+
+    pub fn hidden_layers(mut self, specs: &[LayerSpec]) -> Self {
+        self.hidden_layers.extend_from_slice(specs);
+        self
+    }
+
+    pub fn output(mut self, size: usize, activation: Activation) -> Self {
+        self.output_layer = Some(LayerSpec::new(size, activation));
+        self
+    }
+
+    // checks the topology without creating anything
+    pub fn validate(&self) -> Result<(), Error> {
+        let input_size = self.input_size.ok_or(Error::InvalidParameter("the input layer size is missing"))?;
+        let output_layer = self.output_layer.ok_or(Error::InvalidParameter("the output layer is missing"))?;
+        if self.hidden_layers.is_empty() {
+            return Err(Error::TooFewLayers(2));
+        }
+        if input_size == 0 {
+            return Err(Error::EmptyLayer(0));
+        }
+        if let Some(index) = self.hidden_layers.iter().position(|spec| spec.size == 0) {
+            return Err(Error::EmptyLayer(index + 1));
+        }
+        if output_layer.size == 0 {
+            return Err(Error::EmptyLayer(self.hidden_layers.len() + 1));
+        }
+        Ok(())
+    }
+
+    // sizes of every layer, input first
+    fn layer_sizes(&self) -> Vec<usize> {
+        let mut sizes = Vec::with_capacity(self.hidden_layers.len() + 2);
+        sizes.extend(self.input_size);
+        sizes.extend(self.hidden_layers.iter().map(|spec| spec.size));
+        sizes.extend(self.output_layer.map(|spec| spec.size));
+        sizes
+    }
+}
+
+impl<T> NetworkBuilder<T>
+where
+    T: num::Float + num::Signed + Default + fmt::Debug
+{
+    pub fn build(&self) -> Network<T> {
+        self.try_build().or_panic()
+    }
+
+    // the weights and biases are zero, run a NetworkInitializer over the result
+    pub fn try_build(&self) -> Result<Network<T>, Error> {
+        self.validate()?;
+        let sizes = self.layer_sizes();
+        let mut network = Network::try_new(sizes.len())?;
+        // resizing from the input towards the output, so every layer sees the final size of its predecessor
+        for (layer, size) in sizes.iter().enumerate() {
+            network.try_resize(layer, *size)?;
+        }
+        let activations = self.hidden_layers.iter().chain(self.output_layer.iter()).map(|spec| spec.activation);
+        for (layer, activation) in activations.enumerate() {
+            network.try_set_activation(layer, activation)?;
+        }
+        Ok(network)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::matrix::Matrix;
+    use crate::network::network_initializer::NetworkInitializer;
+
+    #[test]
+    fn build_test() {
+        let network = NetworkBuilder::<f32>::new()
+            .input(3)
+            .hidden(5, Activation::Tanh)
+            .hidden(4, Activation::LeakyReLU(0.01))
+            .output(2, Activation::Sigmoid)
+            .build();
+
+        assert_eq!(network.get_num_layers(), 4);
+        let expected = [(3, 5), (5, 4), (4, 2)];
+        for (layer, (rows, cols)) in expected.iter().enumerate() {
+            let dims = network.get_weights_dimension(layer);
+            assert_eq!((dims.rows, dims.cols), (*rows, *cols));
+            let dims = network.get_biases_dimension(layer);
+            assert_eq!((dims.rows, dims.cols), (1, *cols));
+        }
+        assert_eq!(network.get_activation(0), Activation::Tanh);
+        assert_eq!(network.get_activation(1), Activation::LeakyReLU(0.01));
+        assert_eq!(network.get_activation(2), Activation::Sigmoid);
+    }
+
+    #[test]
+    fn hidden_layers_test() {
+        let specs = [LayerSpec::new(6, Activation::ReLU), LayerSpec::new(7, Activation::GELU)];
+        let network = NetworkBuilder::<f64>::new()
+            .input(2)
+            .hidden_layers(&specs)
+            .output(1, Activation::Identity)
+            .build();
+        assert_eq!(network.get_weights_dimension(1).rows, 6);
+        assert_eq!(network.get_weights_dimension(1).cols, 7);
+        assert_eq!(network.get_activation(1), Activation::GELU);
+    }
+
+    #[test]
+    fn build_and_initialize_test() {
+        let mut network = NetworkBuilder::<f32>::new()
+            .input(2)
+            .hidden(3, Activation::ReLU)
+            .output(1, Activation::Sigmoid)
+            .build();
+        NetworkInitializer::new(7, -1.0, 1.0).initialize(&mut network);
+        let output = network.forward(&Matrix::new(1, 2));
+        assert_eq!(output.cols(), 1);
+        assert!(output[0][0] > 0.0 && output[0][0] < 1.0);
+    }
+
+    #[test]
+    fn validate_test() {
+        let builder = NetworkBuilder::<f32>::new();
+        assert!(matches!(builder.clone().output(1, Activation::ReLU).hidden(2, Activation::ReLU).validate(), Err(Error::InvalidParameter(_))));
+        assert!(matches!(builder.clone().input(1).hidden(2, Activation::ReLU).validate(), Err(Error::InvalidParameter(_))));
+        assert_eq!(builder.clone().input(1).output(1, Activation::ReLU).validate(), Err(Error::TooFewLayers(2)));
+        assert_eq!(builder.clone().input(0).hidden(2, Activation::ReLU).output(1, Activation::ReLU).validate(), Err(Error::EmptyLayer(0)));
+        assert_eq!(builder.clone().input(1).hidden(2, Activation::ReLU).hidden(0, Activation::ReLU).output(1, Activation::ReLU).validate(), Err(Error::EmptyLayer(2)));
+        assert_eq!(builder.clone().input(1).hidden(2, Activation::ReLU).output(0, Activation::ReLU).validate(), Err(Error::EmptyLayer(2)));
+        assert!(builder.input(1).hidden(2, Activation::ReLU).output(1, Activation::ReLU).validate().is_ok());
+    }
+
+    #[test]
+    #[should_panic(expected = "Layer 1 must have at least one neuron")]
+    fn build_invalid_test() {
+        NetworkBuilder::<f32>::new()
+            .input(2)
+            .hidden(0, Activation::ReLU)
+            .output(1, Activation::ReLU)
+            .build();
+    }
+}
@@ -3,7 +3,31 @@ use std::fmt;
 
 use crate::network::network_initializer::NetworkInitializer;
 use crate::math::random::RandomGenerator;
+use crate::math::activation::Activation;
 use crate::network::network::Network;
+use crate::network::network_builder::NetworkBuilder;
+use crate::error::{Error, OrPanic};
+
+// limits of the topologies, the maxima are exclusive like the ranges of NetworkCreator
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TopologyLimits {
+    pub min_hidden_layers : usize,
+    pub max_hidden_layers : usize,
+    pub min_neurons : usize,
+    pub max_neurons : usize
+}
+
+impl Default for TopologyLimits {
+    // anything with at least one hidden layer and one neuron per layer
+    fn default() -> Self {
+        Self {
+            min_hidden_layers: 1,
+            max_hidden_layers: usize::MAX,
+            min_neurons: 1,
+            max_neurons: usize::MAX
+        }
+    }
+}
 
 pub struct NetworkCreator<T> {
     initializer : NetworkInitializer<T>,
@@ -20,37 +44,114 @@ where
     T: rand::distr::uniform::SampleUniform + std::cmp::PartialOrd + Copy + Default,
     T: Add + fmt::Debug + Div + num::Float + Mul + num::Signed + Sub + num::Zero
 {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(v_seed : u64, lower_limit: T, upper_limit: T,
                l_seed : u64, min_hidden_layers : usize, max_hidden_layers : usize,
                n_seed : u64, min_neurons : usize, max_neurons : usize) -> Self
     {
-        if lower_limit >= upper_limit {
-            panic!("Invalid range: lower limit must be less than upper limit");
+        Self::try_new(v_seed, lower_limit, upper_limit, l_seed, min_hidden_layers, max_hidden_layers, n_seed, min_neurons, max_neurons).or_panic()
+    }
+
+    // the number of hidden layers is drawn from [min_hidden_layers, max_hidden_layers)
+    // and the number of neurons of every hidden layer from [min_neurons, max_neurons)
+    #[allow(clippy::too_many_arguments)]
+    pub fn try_new(v_seed : u64, lower_limit: T, upper_limit: T,
+                   l_seed : u64, min_hidden_layers : usize, max_hidden_layers : usize,
+                   n_seed : u64, min_neurons : usize, max_neurons : usize) -> Result<Self, Error>
+    {
+        if min_hidden_layers == 0 {
+            return Err(Error::InvalidParameter("a network needs at least one hidden layer"));
         }
-        Self {
-            initializer: NetworkInitializer::<T>::new(v_seed, lower_limit, upper_limit),
+        if min_hidden_layers >= max_hidden_layers {
+            return Err(Error::InvalidParameter("minimum number of hidden layers must be less than the maximum"));
+        }
+        if min_neurons == 0 {
+            return Err(Error::InvalidParameter("a hidden layer needs at least one neuron"));
+        }
+        if min_neurons >= max_neurons {
+            return Err(Error::InvalidParameter("minimum number of neurons must be less than the maximum"));
+        }
+        Ok(Self {
+            initializer: NetworkInitializer::<T>::try_new(v_seed, lower_limit, upper_limit)?,
             layer_number_generator: RandomGenerator::<usize>::with_seed(l_seed),
             min_hidden_layers, max_hidden_layers,
             neuron_number_generator: RandomGenerator::<usize>::with_seed(n_seed),
             min_neurons, max_neurons
+        })
+    }
+
+    pub fn get_topology_limits (&self) -> TopologyLimits {
+        TopologyLimits {
+            min_hidden_layers: self.min_hidden_layers,
+            max_hidden_layers: self.max_hidden_layers,
+            min_neurons: self.min_neurons,
+            max_neurons: self.max_neurons
         }
     }
 
     pub fn create (&mut self, input_size : usize, output_size : usize) -> Network<T>
     {
-        let num_hidden_layers = self.layer_number_generator.generate(self.min_hidden_layers, self.max_hidden_layers);
-        let mut network = Network::<T>::new(num_hidden_layers + 2); // +2 for input and output
-        network.resize(0, input_size); // input layer
-        // hidden layers
-        for layer in 1..num_hidden_layers {
-            network.resize(layer, self.neuron_number_generator.generate(self.min_neurons, self.max_neurons));
-        }
-        network.resize(num_hidden_layers + 1, output_size); // output layer
+        self.try_create(input_size, output_size).or_panic()
+    }
+
+    pub fn try_create (&mut self, input_size : usize, output_size : usize) -> Result<Network<T>, Error>
+    {
+        let num_hidden_layers = self.layer_number_generator.try_generate(self.min_hidden_layers, self.max_hidden_layers)?;
+        let mut builder = NetworkBuilder::<T>::new().input(input_size);
+        for _ in 0..num_hidden_layers {
+            let size = self.neuron_number_generator.try_generate(self.min_neurons, self.max_neurons)?;
+            builder = builder.hidden(size, Activation::default());
+        }
+        let mut network = builder.output(output_size, Activation::default()).try_build()?;
         self.initializer.initialize(&mut network);
-        network
+        Ok(network)
     }
 
     pub fn initialize (&mut self, network : &mut Network<T>) {
         self.initializer.initialize(network);
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn try_new_test() {
+        let create = |lower, upper, min_layers, max_layers, min_neurons, max_neurons| {
+            NetworkCreator::<f32>::try_new(1, lower, upper, 2, min_layers, max_layers, 3, min_neurons, max_neurons)
+        };
+        assert_eq!(create(1.0, -1.0, 1, 4, 1, 8).err(), Some(Error::InvalidRange));
+        assert!(matches!(create(-1.0, 1.0, 0, 4, 1, 8), Err(Error::InvalidParameter(_))));
+        assert!(matches!(create(-1.0, 1.0, 4, 4, 1, 8), Err(Error::InvalidParameter(_))));
+        assert!(matches!(create(-1.0, 1.0, 1, 4, 0, 8), Err(Error::InvalidParameter(_))));
+        assert!(matches!(create(-1.0, 1.0, 1, 4, 8, 8), Err(Error::InvalidParameter(_))));
+        assert!(create(-1.0, 1.0, 1, 4, 1, 8).is_ok());
+    }
+
+    #[test]
+    fn try_create_test() {
+        let mut creator = NetworkCreator::<f32>::try_new(1, -1.0, 1.0, 2, 1, 4, 3, 1, 8).unwrap();
+        let network = creator.try_create(4, 1).unwrap();
+        assert_eq!(network.get_weights_dimension(0).rows, 4);
+        assert_eq!(network.get_biases_dimension(network.get_num_bias_layers() - 1).cols, 1);
+        assert_eq!(creator.try_create(0, 1).err(), Some(Error::EmptyLayer(0)));
+    }
+
+    #[test]
+    fn create_consistent_shapes_test() {
+        let mut creator = NetworkCreator::<f32>::new(1, -1.0, 1.0, 2, 1, 6, 3, 2, 9);
+        for _ in 0..20 {
+            let network = creator.create(5, 3);
+            for layer in 1..network.get_num_weight_layers() {
+                assert_eq!(network.get_weights_dimension(layer - 1).cols, network.get_weights_dimension(layer).rows);
+                assert_eq!(network.get_biases_dimension(layer - 1).cols, network.get_weights_dimension(layer).rows);
+            }
+            // every hidden layer gets a size from the configured range
+            for layer in 1..network.get_num_weight_layers() {
+                let size = network.get_weights_dimension(layer).rows;
+                assert!((2..9).contains(&size));
+            }
+        }
+    }
+}
@@ -0,0 +1,262 @@
+use std::fmt;
+
+use crate::math::random::RandomGenerator;
+use crate::network::network::{Network, ParameterIndex, ParameterKind};
+use crate::error::{Error, OrPanic};
+
+/*
+    combines two parent networks into a child
+
+    the parameters are flattened layer by layer, the weights of a layer row by row followed by its biases
+    [w_0 (row-major) | b_0 | w_1 (row-major) | b_1 | ...]
+
+    Uniform             every parameter comes from either parent with equal probability
+    SinglePoint         parameters after a random cut come from the second parent
+    MultiPoint(n)       n random cuts, the parents alternate between the cuts
+    PerNeuron           every neuron (a column of a weight matrix plus its bias) comes from either parent
+    Arithmetic(alpha)   alpha * first + (1 - alpha) * second for every parameter
+
+    parents with different topologies: the child always has the topology and activations of the first parent,
+    parameters that do not exist in the second parent (same layer, row and column) are taken from the first
+*/
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CrossoverKind {
+    Uniform,
+    SinglePoint,
+    MultiPoint(usize),
+    PerNeuron,
+    Arithmetic(f64)
+}
+
+pub struct NetworkCrossover<T> {
+    kind: CrossoverKind,
+    generator: RandomGenerator<f64>,
+    _marker: std::marker::PhantomData<T>
+}
+
+impl<T> NetworkCrossover<T>
+where
+    T: num::Float + num::Signed + Default + fmt::Debug
+{
+    pub fn new(seed: u64, kind: CrossoverKind) -> Self {
+        Self::try_new(seed, kind).or_panic()
+    }
+
+    pub fn try_new(seed: u64, kind: CrossoverKind) -> Result<Self, Error> {
+        match kind {
+            CrossoverKind::MultiPoint(0) => return Err(Error::InvalidParameter("multi-point crossover needs at least one point")),
+            CrossoverKind::Arithmetic(alpha) if !(0.0..=1.0).contains(&alpha) => {
+                return Err(Error::InvalidParameter("blend factor must be in [0, 1]"));
+            },
+            _ => {}
+        }
+        Ok(Self {
+            kind,
+            generator: RandomGenerator::with_seed(seed),
+            _marker: std::marker::PhantomData
+        })
+    }
+
+    pub fn get_kind(&self) -> CrossoverKind {
+        self.kind
+    }
+
+    pub fn crossover(&mut self, first: &Network<T>, second: &Network<T>) -> Network<T> {
+        let mut child = first.clone();
+        match self.kind {
+            CrossoverKind::Uniform => {
+                for parameter in first.parameter_indices() {
+                    if self.coin_flip() {
+                        Self::inherit(&mut child, second, parameter);
+                    }
+                }
+            },
+            CrossoverKind::SinglePoint => self.multi_point(&mut child, second, 1),
+            CrossoverKind::MultiPoint(points) => self.multi_point(&mut child, second, points),
+            CrossoverKind::PerNeuron => {
+                for layer in 0..first.get_num_weight_layers() {
+                    let dimensions = first.get_weights_dimension(layer);
+                    for col in 0..dimensions.cols {
+                        if !self.coin_flip() {
+                            continue;
+                        }
+                        for row in 0..dimensions.rows {
+                            Self::inherit(&mut child, second, ParameterIndex { kind: ParameterKind::Weight, layer, row, col });
+                        }
+                        Self::inherit(&mut child, second, ParameterIndex { kind: ParameterKind::Bias, layer, row: 0, col });
+                    }
+                }
+            },
+            CrossoverKind::Arithmetic(alpha) => {
+                let alpha = T::from(alpha).unwrap();
+                for parameter in first.parameter_indices() {
+                    if let Some(other) = second.get_parameter(parameter) {
+                        let own = first.get_parameter(parameter).unwrap();
+                        child.set_parameter(parameter, alpha * own + (T::one() - alpha) * other);
+                    }
+                }
+            }
+        }
+        child
+    }
+
+    // the cuts split the flattened parameters into segments, every other segment comes from the second parent
+    fn multi_point(&mut self, child: &mut Network<T>, second: &Network<T>, points: usize) {
+        let parameters = child.parameter_indices();
+        let mut cuts: Vec<usize> = (0..points)
+            .map(|_| ((self.generator.generate(0.0, 1.0) * parameters.len() as f64) as usize).min(parameters.len() - 1))
+            .collect();
+        cuts.sort_unstable();
+        let mut from_second = false;
+        let mut next_cut = 0;
+        for (index, parameter) in parameters.into_iter().enumerate() {
+            while next_cut < cuts.len() && cuts[next_cut] == index {
+                from_second = !from_second;
+                next_cut += 1;
+            }
+            if from_second {
+                Self::inherit(child, second, parameter);
+            }
+        }
+    }
+
+    fn coin_flip(&mut self) -> bool {
+        self.generator.generate(0.0, 1.0) < 0.5
+    }
+
+    // copies the parameter from the other parent if it has one
+    fn inherit(child: &mut Network<T>, other: &Network<T>, parameter: ParameterIndex) {
+        if let Some(value) = other.get_parameter(parameter) {
+            child.set_parameter(parameter, value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::activation::Activation;
+
+    fn filled_network(sizes: &[usize], value: f64) -> Network<f64> {
+        let mut network = Network::<f64>::new(sizes.len());
+        for (i, size) in sizes.iter().enumerate() {
+            network.resize(i, *size);
+        }
+        for layer in 0..network.get_num_weight_layers() {
+            let dimensions = network.get_weights_dimension(layer);
+            for row in 0..dimensions.rows {
+                for col in 0..dimensions.cols {
+                    network.set_weight(layer, row, col, value);
+                }
+            }
+            for col in 0..dimensions.cols {
+                network.set_bias(layer, col, value);
+            }
+        }
+        network
+    }
+
+    #[test]
+    fn try_new_test() {
+        assert!(NetworkCrossover::<f64>::try_new(1, CrossoverKind::MultiPoint(0)).is_err());
+        assert!(NetworkCrossover::<f64>::try_new(1, CrossoverKind::Arithmetic(1.5)).is_err());
+        assert!(NetworkCrossover::<f64>::try_new(1, CrossoverKind::Arithmetic(0.3)).is_ok());
+    }
+
+    #[test]
+    fn uniform_test() {
+        let first = filled_network(&[4, 5, 3], 1.0);
+        let second = filled_network(&[4, 5, 3], 2.0);
+        let child = NetworkCrossover::new(3, CrossoverKind::Uniform).crossover(&first, &second);
+        let values = child.get_parameters();
+        let from_second = values.iter().filter(|v| **v == 2.0).count();
+        assert!(values.iter().all(|v| *v == 1.0 || *v == 2.0));
+        assert!(from_second > 10 && from_second < values.len() - 10);
+    }
+
+    #[test]
+    fn single_point_test() {
+        let first = filled_network(&[4, 5, 3], 1.0);
+        let second = filled_network(&[4, 5, 3], 2.0);
+        let mut crossover = NetworkCrossover::new(8, CrossoverKind::SinglePoint);
+        for _ in 0..10 {
+            let values = crossover.crossover(&first, &second).get_parameters();
+            // a prefix from the first parent followed by a suffix from the second
+            let cut = values.iter().position(|v| *v == 2.0).unwrap();
+            assert!(values[..cut].iter().all(|v| *v == 1.0));
+            assert!(values[cut..].iter().all(|v| *v == 2.0));
+        }
+    }
+
+    #[test]
+    fn multi_point_test() {
+        let first = filled_network(&[6, 8, 4], 1.0);
+        let second = filled_network(&[6, 8, 4], 2.0);
+        let values = NetworkCrossover::new(5, CrossoverKind::MultiPoint(4)).crossover(&first, &second).get_parameters();
+        let switches = values.windows(2).filter(|w| w[0] != w[1]).count();
+        // cuts can coincide, which cancels them out
+        assert!(switches > 0 && switches <= 4);
+        assert!(values.iter().all(|v| *v == 1.0 || *v == 2.0));
+    }
+
+    #[test]
+    fn per_neuron_test() {
+        let first = filled_network(&[3, 6, 2], 1.0);
+        let second = filled_network(&[3, 6, 2], 2.0);
+        let child = NetworkCrossover::new(2, CrossoverKind::PerNeuron).crossover(&first, &second);
+        let mut mixed = false;
+        for layer in 0..child.get_num_weight_layers() {
+            let dimensions = child.get_weights_dimension(layer);
+            for col in 0..dimensions.cols {
+                // the whole column and the bias come from the same parent
+                let bias = child.get_bias(layer, col);
+                for row in 0..dimensions.rows {
+                    assert_eq!(child.get_weight(layer, row, col), bias);
+                }
+                mixed |= bias == 2.0;
+            }
+        }
+        assert!(mixed);
+    }
+
+    #[test]
+    fn arithmetic_test() {
+        let first = filled_network(&[3, 4, 2], 1.0);
+        let second = filled_network(&[3, 4, 2], 3.0);
+        let child = NetworkCrossover::new(2, CrossoverKind::Arithmetic(0.25)).crossover(&first, &second);
+        assert!(child.get_parameters().iter().all(|v| (*v - 2.5).abs() < 1e-12));
+    }
+
+    #[test]
+    fn different_topologies_test() {
+        let mut first = filled_network(&[3, 4, 2], 1.0);
+        first.set_activation(1, Activation::Sigmoid);
+        let second = filled_network(&[3, 2, 5, 2], 3.0);
+        for kind in [CrossoverKind::Uniform, CrossoverKind::SinglePoint, CrossoverKind::MultiPoint(3), CrossoverKind::PerNeuron, CrossoverKind::Arithmetic(0.5)] {
+            let child = NetworkCrossover::new(4, kind).crossover(&first, &second);
+            assert_eq!(child.get_num_layers(), 3);
+            assert_eq!(child.get_weights_dimension(0).cols, 4);
+            assert_eq!(child.get_activation(1), Activation::Sigmoid);
+            // neurons 2 and 3 of the hidden layer do not exist in the second parent
+            for row in 0..3 {
+                assert_eq!(child.get_weight(0, row, 2), 1.0);
+                assert_eq!(child.get_weight(0, row, 3), 1.0);
+            }
+            assert_eq!(child.get_bias(0, 3), 1.0);
+            // the second weight layers have different shapes (4x2 and 2x5), only the overlap can be inherited
+            assert_eq!(child.get_weight(1, 3, 1), 1.0);
+        }
+    }
+
+    #[test]
+    fn reproducible_test() {
+        let first = filled_network(&[4, 5, 3], 1.0);
+        let second = filled_network(&[4, 5, 3], 2.0);
+        for kind in [CrossoverKind::Uniform, CrossoverKind::MultiPoint(3), CrossoverKind::PerNeuron] {
+            let a = NetworkCrossover::new(11, kind).crossover(&first, &second);
+            let b = NetworkCrossover::new(11, kind).crossover(&first, &second);
+            assert!(a == b);
+        }
+    }
+}
@@ -1,45 +1,152 @@
 use crate::math::random::RandomGenerator;
-use crate::network::network::Network;
+use crate::math::matrix::Matrix;
+use crate::network::network::{Network, ParameterKind};
+use crate::error::{Error, OrPanic};
+
+/*
+    weight initialization schemes, fan_in and fan_out are the rows and columns of each weight matrix
+
+    Uniform(lower, upper)   U(lower, upper), biases from the same range
+    XavierUniform           U(-sqrt(6 / (fan_in + fan_out)), +sqrt(6 / (fan_in + fan_out)))
+    XavierNormal            N(0, 2 / (fan_in + fan_out))
+    HeUniform               U(-sqrt(6 / fan_in), +sqrt(6 / fan_in))
+    HeNormal                N(0, 2 / fan_in)
+    LeCunUniform            U(-sqrt(3 / fan_in), +sqrt(3 / fan_in))
+    LeCunNormal             N(0, 1 / fan_in)
+    Orthogonal              orthonormal rows or columns (whichever are fewer) of a random normal matrix
+
+    all schemes except Uniform start with zero biases
+*/
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InitScheme<T> {
+    Uniform(T, T),
+    XavierUniform,
+    XavierNormal,
+    HeUniform,
+    HeNormal,
+    LeCunUniform,
+    LeCunNormal,
+    Orthogonal
+}
 
 pub struct NetworkInitializer<T> {
     value_generator: RandomGenerator<T>,
-    lower_limit: T,
-    upper_limit: T
+    scheme: InitScheme<T>,
+    zero_bias: bool
 }
 
 impl<T> NetworkInitializer<T>
 where
-    T: rand::distr::uniform::SampleUniform + std::cmp::PartialOrd + Copy + Default
+    T: rand::distr::uniform::SampleUniform + num::Float + Default
 {
     pub fn new(seed: u64, lower_limit: T, upper_limit: T) -> Self {
-        if lower_limit >= upper_limit {
-            panic!("Invalid range: lower limit must be less than upper limit");
+        Self::try_new(seed, lower_limit, upper_limit).or_panic()
+    }
+
+    pub fn try_new(seed: u64, lower_limit: T, upper_limit: T) -> Result<Self, Error> {
+        Self::try_with_scheme(seed, InitScheme::Uniform(lower_limit, upper_limit))
+    }
+
+    pub fn with_scheme(seed: u64, scheme: InitScheme<T>) -> Self {
+        Self::try_with_scheme(seed, scheme).or_panic()
+    }
+
+    pub fn try_with_scheme(seed: u64, scheme: InitScheme<T>) -> Result<Self, Error> {
+        if let InitScheme::Uniform(lower_limit, upper_limit) = scheme && lower_limit >= upper_limit {
+            return Err(Error::InvalidRange);
         }
-        Self {
+        Ok(Self {
             value_generator: RandomGenerator::with_seed(seed),
-            lower_limit,
-            upper_limit
-        }
+            scheme,
+            zero_bias: !matches!(scheme, InitScheme::Uniform(..))
+        })
+    }
+
+    // sets every bias to zero regardless of the scheme
+    pub fn with_zero_bias(mut self) -> Self {
+        self.zero_bias = true;
+        self
+    }
+
+    pub fn get_scheme(&self) -> InitScheme<T> {
+        self.scheme
     }
 
     pub fn initialize(&mut self, network: &mut Network<T>) {
-        for layer in 0..network.get_num_weight_layers() {
-            let dimensions = network.get_weights_dimension(layer);
-            for row in 0..dimensions.rows {
-                for col in 0..dimensions.cols {
-                    let weight = self.value_generator.generate(self.lower_limit, self.upper_limit);
-                    network.set_weight(layer, row, col, weight);
+        let dimensions: Vec<_> = (0..network.get_num_weight_layers()).map(|layer| network.get_weights_dimension(layer)).collect();
+        // orthogonal weights are generated per matrix up front
+        let orthogonal: Vec<Matrix<T>> = match self.scheme {
+            InitScheme::Orthogonal => dimensions.iter().map(|d| self.orthogonal(d.rows, d.cols)).collect(),
+            _ => Vec::new()
+        };
+        // all weights before all biases, this is the order in which seeded networks always drew their values
+        let (weights, biases): (Vec<_>, Vec<_>) = network.parameters_mut().partition(|p| p.index.kind == ParameterKind::Weight);
+        for parameter in weights.into_iter().chain(biases) {
+            let index = parameter.index;
+            *parameter.value = match (index.kind, self.scheme) {
+                (ParameterKind::Weight, InitScheme::Orthogonal) => orthogonal[index.layer][index.row][index.col],
+                (ParameterKind::Weight, _) => self.draw_weight(dimensions[index.layer].rows, dimensions[index.layer].cols),
+                (ParameterKind::Bias, InitScheme::Uniform(lower_limit, upper_limit)) if !self.zero_bias => {
+                    self.value_generator.generate(lower_limit, upper_limit)
+                },
+                (ParameterKind::Bias, _) => T::zero()
+            };
+        }
+    }
+
+    fn draw_weight(&mut self, fan_in: usize, fan_out: usize) -> T {
+        let fan_in = T::from(fan_in).unwrap();
+        let fan_out = T::from(fan_out).unwrap();
+        let constant = |value: f64| T::from(value).unwrap();
+        match self.scheme {
+            InitScheme::Uniform(lower_limit, upper_limit) => self.value_generator.generate(lower_limit, upper_limit),
+            InitScheme::XavierUniform => self.symmetric_uniform((constant(6.0) / (fan_in + fan_out)).sqrt()),
+            InitScheme::XavierNormal => self.value_generator.generate_normal(T::zero(), (constant(2.0) / (fan_in + fan_out)).sqrt()),
+            InitScheme::HeUniform => self.symmetric_uniform((constant(6.0) / fan_in).sqrt()),
+            InitScheme::HeNormal => self.value_generator.generate_normal(T::zero(), (constant(2.0) / fan_in).sqrt()),
+            InitScheme::LeCunUniform => self.symmetric_uniform((constant(3.0) / fan_in).sqrt()),
+            InitScheme::LeCunNormal => self.value_generator.generate_normal(T::zero(), (T::one() / fan_in).sqrt()),
+            InitScheme::Orthogonal => unreachable!("orthogonal weights are generated per matrix")
+        }
+    }
+
+    fn symmetric_uniform(&mut self, limit: T) -> T {
+        self.value_generator.generate(-limit, limit)
+    }
+
+    // random rows x cols matrix with orthonormal columns (rows >= cols) or orthonormal rows (rows < cols)
+    fn orthogonal(&mut self, rows: usize, cols: usize) -> Matrix<T> {
+        let (long, short) = if rows >= cols { (rows, cols) } else { (cols, rows) };
+        // vectors[i] is the i-th column of a long x short matrix
+        let mut vectors: Vec<Vec<T>> = Vec::with_capacity(short);
+        while vectors.len() < short {
+            let mut vector: Vec<T> = (0..long).map(|_| self.value_generator.generate_normal(T::zero(), T::one())).collect();
+            // modified Gram-Schmidt against the vectors accepted so far
+            for other in vectors.iter() {
+                let dot = vector.iter().zip(other.iter()).fold(T::zero(), |acc, (&a, &b)| acc + a * b);
+                for (v, &o) in vector.iter_mut().zip(other.iter()) {
+                    *v = *v - dot * o;
                 }
             }
+            let norm = vector.iter().fold(T::zero(), |acc, &v| acc + v * v).sqrt();
+            // a (numerically) dependent draw is thrown away and drawn again
+            if norm <= T::epsilon() {
+                continue;
+            }
+            vectors.push(vector.into_iter().map(|v| v / norm).collect());
         }
-        for layer in 0..network.get_num_bias_layers() {
-            let dimensions = network.get_biases_dimension(layer);
-            assert_eq!(dimensions.rows, 1, "something is off, biases layer should have dimensions 1xN");
-            for col in 0..dimensions.cols {
-                let bias = self.value_generator.generate(self.lower_limit, self.upper_limit);
-                network.set_bias(layer, col, bias);
+        let mut result = Matrix::new(rows, cols);
+        for (i, vector) in vectors.iter().enumerate() {
+            for (j, &value) in vector.iter().enumerate() {
+                if rows >= cols {
+                    result[j][i] = value;
+                } else {
+                    result[i][j] = value;
+                }
             }
         }
+        result
     }
 }
 
@@ -48,6 +155,37 @@ where
 mod tests {
     use super::*;
 
+    #[test]
+    fn try_new_test() {
+        assert!(NetworkInitializer::<f32>::try_new(50, 1.0, 1.0).is_err());
+        assert!(NetworkInitializer::<f32>::try_new(50, 2.0, 1.0).is_err());
+        assert!(NetworkInitializer::<f32>::try_new(50, -1.0, 1.0).is_ok());
+    }
+
+    #[test]
+    fn draw_order_test() {
+        // seeded networks draw all weights first and then all biases
+        let mut network = Network::<f32>::new(3);
+        network.resize(0, 3);
+        network.resize(1, 4);
+        network.resize(2, 2);
+        NetworkInitializer::<f32>::new(50, -1.0, 1.0).initialize(&mut network);
+        let mut generator = RandomGenerator::<f32>::with_seed(50);
+        for layer in 0..network.get_num_weight_layers() {
+            let dimensions = network.get_weights_dimension(layer);
+            for row in 0..dimensions.rows {
+                for col in 0..dimensions.cols {
+                    assert_eq!(network.get_weight(layer, row, col), generator.generate(-1.0, 1.0));
+                }
+            }
+        }
+        for layer in 0..network.get_num_bias_layers() {
+            for col in 0..network.get_biases_dimension(layer).cols {
+                assert_eq!(network.get_bias(layer, col), generator.generate(-1.0, 1.0));
+            }
+        }
+    }
+
     #[test]
     fn initialize_test() {
         let mut network = Network::<f32>::new(3);
@@ -78,7 +216,7 @@ mod tests {
             for row in 0..dimensions.rows {
                 for col in 0..dimensions.cols {
                     let w = network.get_weight(layer, row, col);
-                    assert!(w >= 3.0 && w < 4.0);
+                    assert!((3.0..4.0).contains(&w));
                 }
             }
         }
@@ -87,8 +225,117 @@ mod tests {
             assert_eq!(dimensions.rows, 1, "something is off, biases layer should have dimensions 1xN");
             for col in 0..dimensions.cols {
                 let b = network.get_bias(layer, col);
-                assert!(b >= 3.0 && b < 4.0);
+                assert!((3.0..4.0).contains(&b));
+            }
+        }
+    }
+
+    fn create_network(sizes: &[usize]) -> Network<f64> {
+        let mut network = Network::<f64>::new(sizes.len());
+        for (i, size) in sizes.iter().enumerate() {
+            network.resize(i, *size);
+        }
+        network
+    }
+
+    fn weights(network: &Network<f64>, layer: usize) -> Vec<f64> {
+        let dimensions = network.get_weights_dimension(layer);
+        let mut values = Vec::new();
+        for row in 0..dimensions.rows {
+            for col in 0..dimensions.cols {
+                values.push(network.get_weight(layer, row, col));
+            }
+        }
+        values
+    }
+
+    fn assert_zero_biases(network: &Network<f64>) {
+        for layer in 0..network.get_num_bias_layers() {
+            for col in 0..network.get_biases_dimension(layer).cols {
+                assert_eq!(network.get_bias(layer, col), 0.0);
             }
         }
     }
+
+    #[test]
+    fn uniform_schemes_test() {
+        // (scheme, limit for the 40x60 weight matrix)
+        let schemes = [
+            (InitScheme::XavierUniform, (6.0_f64 / 100.0).sqrt()),
+            (InitScheme::HeUniform, (6.0_f64 / 40.0).sqrt()),
+            (InitScheme::LeCunUniform, (3.0_f64 / 40.0).sqrt())
+        ];
+        for (scheme, limit) in schemes.iter() {
+            let mut network = create_network(&[40, 60, 1]);
+            NetworkInitializer::with_scheme(3, *scheme).initialize(&mut network);
+            let values = weights(&network, 0);
+            assert!(values.iter().all(|w| w.abs() <= *limit), "{:?}", scheme);
+            // the values should use most of the range
+            assert!(values.iter().any(|w| w.abs() > 0.9 * limit), "{:?}", scheme);
+            assert_zero_biases(&network);
+        }
+    }
+
+    #[test]
+    fn normal_schemes_test() {
+        let schemes = [
+            (InitScheme::XavierNormal, (2.0_f64 / 300.0).sqrt()),
+            (InitScheme::HeNormal, (2.0_f64 / 100.0).sqrt()),
+            (InitScheme::LeCunNormal, (1.0_f64 / 100.0).sqrt())
+        ];
+        for (scheme, std_dev) in schemes.iter() {
+            let mut network = create_network(&[100, 200, 1]);
+            NetworkInitializer::with_scheme(5, *scheme).initialize(&mut network);
+            let values = weights(&network, 0);
+            let mean = values.iter().sum::<f64>() / values.len() as f64;
+            let variance = values.iter().map(|w| (w - mean) * (w - mean)).sum::<f64>() / values.len() as f64;
+            assert!(mean.abs() < 0.05 * std_dev, "{:?}", scheme);
+            assert!((variance.sqrt() / std_dev - 1.0).abs() < 0.05, "{:?}", scheme);
+            assert_zero_biases(&network);
+        }
+    }
+
+    #[test]
+    fn orthogonal_test() {
+        let mut network = create_network(&[6, 4, 9]);
+        NetworkInitializer::with_scheme(11, InitScheme::Orthogonal).initialize(&mut network);
+        // 6x4: orthonormal columns
+        let w = weights(&network, 0);
+        for i in 0..4 {
+            for j in 0..4 {
+                let dot: f64 = (0..6).map(|k| w[k * 4 + i] * w[k * 4 + j]).sum();
+                let expected = if i == j { 1.0 } else { 0.0 };
+                assert!((dot - expected).abs() < 1e-10);
+            }
+        }
+        // 4x9: orthonormal rows
+        let w = weights(&network, 1);
+        for i in 0..4 {
+            for j in 0..4 {
+                let dot: f64 = (0..9).map(|k| w[i * 9 + k] * w[j * 9 + k]).sum();
+                let expected = if i == j { 1.0 } else { 0.0 };
+                assert!((dot - expected).abs() < 1e-10);
+            }
+        }
+        assert_zero_biases(&network);
+    }
+
+    #[test]
+    fn zero_bias_test() {
+        let mut network = create_network(&[3, 4, 2]);
+        NetworkInitializer::new(1, 3.0, 4.0).with_zero_bias().initialize(&mut network);
+        assert!(weights(&network, 0).iter().all(|w| (3.0..4.0).contains(w)));
+        assert_zero_biases(&network);
+    }
+
+    #[test]
+    fn reproducible_test() {
+        for scheme in [InitScheme::XavierNormal, InitScheme::HeUniform, InitScheme::Orthogonal] {
+            let mut first = create_network(&[5, 7, 3]);
+            let mut second = create_network(&[5, 7, 3]);
+            NetworkInitializer::with_scheme(42, scheme).initialize(&mut first);
+            NetworkInitializer::with_scheme(42, scheme).initialize(&mut second);
+            assert!(first == second, "{:?}", scheme);
+        }
+    }
 }
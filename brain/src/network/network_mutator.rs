@@ -1,73 +1,585 @@
+use std::fmt;
+
 use crate::math::random::RandomGenerator;
-use crate::network::network::Network;
+use crate::network::network::{Network, ParameterKind};
+use crate::network::network_creator::TopologyLimits;
+use crate::error::{Error, OrPanic};
+
+/*
+    every weight and bias goes through the mutation kinds in this order, each one rolls its own probability:
+
+    uniform perturbation    adds a random number from [lower, upper) times the factor
+    gaussian perturbation   adds a random number from N(0, std_dev^2) times the factor
+    replacement             replaces the value with a random number from [lower, upper)
+    sign flip               negates the value
+    clamping                keeps the value in [lower, upper], whether another kind changed it or not
+
+    a kind with zero probability is skipped without drawing random numbers,
+    the probabilities of weight layer i (and its biases) are multiplied by layer_rates[i] (1 if missing)
+
+    before that the topology can change, each structural mutation is rolled once per network:
+
+    add neuron      a new neuron in a random hidden layer, random incoming weights and bias from the
+                    uniform perturbation range, zero outgoing weights (the output does not change)
+    remove neuron   a random neuron of a random hidden layer disappears with its weights
+    add layer       a layer with identity weights and the activation of a neighbouring hidden layer
+                    at a random position (see Network::insert_layer), the output mostly stays the same
+    remove layer    a random hidden layer is merged into its neighbours (see Network::remove_layer),
+                    which only approximates the output unless the layer was linear
 
-pub struct NetworkMutatorConfig {
+    structural mutations never leave the topology limits, e.g. the ones of the NetworkCreator
+*/
+
+#[derive(Debug, Clone)]
+pub struct NetworkMutatorConfig<T> {
     // seed for the random number generator for reproducibility
     pub random_generator_seed: u64,
     // weight mutation parameters
     // if a randomly generated number between 0.0 and 1.0 is smaller than the mutation probability,
     // the weight mutates adding a random number to it from the interval [lower, upper]
-    pub weight_mutation_probability: f32,
-    pub weight_change_upper_limit: f32,
-    pub weight_change_lower_limit: f32
+    pub weight_mutation_probability: T,
+    pub weight_change_upper_limit: T,
+    pub weight_change_lower_limit: T,
+    // gaussian perturbation
+    pub gaussian_mutation_probability: T,
+    pub gaussian_std_dev: T,
+    // replacement with a new random value from [lower, upper)
+    pub replacement_probability: T,
+    pub replacement_lower_limit: T,
+    pub replacement_upper_limit: T,
+    // sign flip
+    pub sign_flip_probability: T,
+    // clamping into [lower, upper]
+    pub clamp_probability: T,
+    pub clamp_lower_limit: T,
+    pub clamp_upper_limit: T,
+    // multipliers of the probabilities per weight layer
+    pub layer_rates: Vec<T>,
+    // structural mutations
+    pub add_neuron_probability: T,
+    pub remove_neuron_probability: T,
+    pub add_layer_probability: T,
+    pub remove_layer_probability: T,
+    pub topology_limits: TopologyLimits
 }
 
-pub struct NetworkMutator {
-    value_change_generator: RandomGenerator<f32>,
-    value_change_lower_limit: f32,
-    value_change_upper_limit: f32,
-    value_change_probability: f32,
-    probability_generator: RandomGenerator<f32>,
-    factor: f32,
+impl<T: num::Float> Default for NetworkMutatorConfig<T> {
+    // nothing mutates until a probability is set
+    fn default() -> Self {
+        Self {
+            random_generator_seed: 0,
+            weight_mutation_probability: T::zero(),
+            weight_change_upper_limit: T::one(),
+            weight_change_lower_limit: -T::one(),
+            gaussian_mutation_probability: T::zero(),
+            gaussian_std_dev: T::one(),
+            replacement_probability: T::zero(),
+            replacement_lower_limit: -T::one(),
+            replacement_upper_limit: T::one(),
+            sign_flip_probability: T::zero(),
+            clamp_probability: T::zero(),
+            clamp_lower_limit: -T::one(),
+            clamp_upper_limit: T::one(),
+            layer_rates: Vec::new(),
+            add_neuron_probability: T::zero(),
+            remove_neuron_probability: T::zero(),
+            add_layer_probability: T::zero(),
+            remove_layer_probability: T::zero(),
+            topology_limits: TopologyLimits::default()
+        }
+    }
 }
 
-impl NetworkMutator {
-    pub fn new(config: NetworkMutatorConfig) -> Self {
-        Self {
+pub struct NetworkMutator<T> {
+    value_change_generator: RandomGenerator<T>,
+    value_change_lower_limit: T,
+    value_change_upper_limit: T,
+    value_change_probability: T,
+    gaussian_probability: T,
+    gaussian_std_dev: T,
+    replacement_probability: T,
+    replacement_lower_limit: T,
+    replacement_upper_limit: T,
+    sign_flip_probability: T,
+    clamp_probability: T,
+    clamp_lower_limit: T,
+    clamp_upper_limit: T,
+    layer_rates: Vec<T>,
+    add_neuron_probability: T,
+    remove_neuron_probability: T,
+    add_layer_probability: T,
+    remove_layer_probability: T,
+    topology_limits: TopologyLimits,
+    structure_generator: RandomGenerator<usize>,
+    probability_generator: RandomGenerator<T>,
+    factor: T,
+}
+
+impl<T> NetworkMutator<T>
+where
+    T: rand::distr::uniform::SampleUniform + num::Float + num::Signed + Default + fmt::Debug
+{
+    pub fn new(config: NetworkMutatorConfig<T>) -> Self {
+        Self::try_new(config).or_panic()
+    }
+
+    pub fn try_new(config: NetworkMutatorConfig<T>) -> Result<Self, Error> {
+        let probabilities = [
+            config.weight_mutation_probability,
+            config.gaussian_mutation_probability,
+            config.replacement_probability,
+            config.sign_flip_probability,
+            config.clamp_probability,
+            config.add_neuron_probability,
+            config.remove_neuron_probability,
+            config.add_layer_probability,
+            config.remove_layer_probability
+        ];
+        if probabilities.iter().any(|p| *p < T::zero() || *p > T::one()) {
+            return Err(Error::InvalidParameter("mutation probabilities must be in [0, 1]"));
+        }
+        if config.layer_rates.iter().any(|rate| *rate < T::zero()) {
+            return Err(Error::InvalidParameter("layer mutation rates cannot be negative"));
+        }
+        if config.gaussian_std_dev < T::zero() {
+            return Err(Error::InvalidParameter("standard deviation cannot be negative"));
+        }
+        let ranges = [
+            (config.weight_mutation_probability, config.weight_change_lower_limit, config.weight_change_upper_limit),
+            (config.replacement_probability, config.replacement_lower_limit, config.replacement_upper_limit)
+        ];
+        if ranges.iter().any(|(p, lower, upper)| *p > T::zero() && lower >= upper) {
+            return Err(Error::InvalidRange);
+        }
+        if config.clamp_probability > T::zero() && config.clamp_lower_limit > config.clamp_upper_limit {
+            return Err(Error::InvalidRange);
+        }
+        let limits = config.topology_limits;
+        if limits.min_hidden_layers == 0 || limits.min_neurons == 0 {
+            return Err(Error::InvalidParameter("a network needs at least one hidden layer with one neuron"));
+        }
+        if config.add_neuron_probability > T::zero() && config.weight_change_lower_limit >= config.weight_change_upper_limit {
+            return Err(Error::InvalidRange);
+        }
+        Ok(Self {
             value_change_generator: RandomGenerator::with_seed(config.random_generator_seed),
             value_change_lower_limit: config.weight_change_lower_limit,
             value_change_upper_limit: config.weight_change_upper_limit,
             value_change_probability: config.weight_mutation_probability,
-            probability_generator: RandomGenerator::<f32>::with_seed(config.random_generator_seed),
-            factor: 1.0
-        }
+            gaussian_probability: config.gaussian_mutation_probability,
+            gaussian_std_dev: config.gaussian_std_dev,
+            replacement_probability: config.replacement_probability,
+            replacement_lower_limit: config.replacement_lower_limit,
+            replacement_upper_limit: config.replacement_upper_limit,
+            sign_flip_probability: config.sign_flip_probability,
+            clamp_probability: config.clamp_probability,
+            clamp_lower_limit: config.clamp_lower_limit,
+            clamp_upper_limit: config.clamp_upper_limit,
+            layer_rates: config.layer_rates,
+            add_neuron_probability: config.add_neuron_probability,
+            remove_neuron_probability: config.remove_neuron_probability,
+            add_layer_probability: config.add_layer_probability,
+            remove_layer_probability: config.remove_layer_probability,
+            topology_limits: config.topology_limits,
+            structure_generator: RandomGenerator::with_seed(config.random_generator_seed),
+            probability_generator: RandomGenerator::<T>::with_seed(config.random_generator_seed),
+            factor: T::one()
+        })
     }
 
-    pub fn set_factor (&mut self, factor: f32) {
+    pub fn set_factor (&mut self, factor: T) {
         self.factor = factor;
     }
 
-    pub fn get_factor (&self) -> f32 {
+    pub fn get_factor (&self) -> T {
         self.factor
     }
 
-    pub fn mutate(&mut self, network: &mut Network<f32>) {
+    pub fn mutate(&mut self, network: &mut Network<T>) {
+        self.mutate_structure(network);
+        // all weights before all biases, this is the order in which seeded mutators always drew their values
+        let (weights, biases): (Vec<_>, Vec<_>) = network.parameters_mut().partition(|p| p.index.kind == ParameterKind::Weight);
+        for parameter in weights.into_iter().chain(biases) {
+            let rate = self.layer_rates.get(parameter.index.layer).copied().unwrap_or(T::one());
+            *parameter.value = self.mutate_value(*parameter.value, rate);
+        }
+    }
+
+    fn mutate_structure(&mut self, network: &mut Network<T>) {
+        let limits = self.topology_limits;
+        if self.roll(self.add_layer_probability) {
+            // the new layer gets the size of the layer in front of it, which has to be a valid hidden layer size
+            let num_hidden_layers = network.get_num_layers() - 2;
+            let positions: Vec<usize> = (1..network.get_num_layers())
+                .filter(|&layer| (limits.min_neurons..limits.max_neurons).contains(&network.get_weights_dimension(layer - 1).rows))
+                .collect();
+            if num_hidden_layers + 1 < limits.max_hidden_layers && !positions.is_empty() {
+                let position = positions[self.structure_generator.generate(0, positions.len())];
+                network.insert_layer(position);
+            }
+        }
+        if self.roll(self.remove_layer_probability) {
+            let num_hidden_layers = network.get_num_layers() - 2;
+            if num_hidden_layers > limits.min_hidden_layers.max(1) {
+                let layer = self.structure_generator.generate(1, num_hidden_layers + 1);
+                network.remove_layer(layer);
+            }
+        }
+        if self.roll(self.add_neuron_probability) {
+            let layers = Self::hidden_layers_where(network, |size| size + 1 < limits.max_neurons);
+            if !layers.is_empty() {
+                let layer = layers[self.structure_generator.generate(0, layers.len())];
+                let neuron = self.structure_generator.generate(0, network.get_weights_dimension(layer).rows + 1);
+                network.insert_neuron(layer, neuron);
+                for row in 0..network.get_weights_dimension(layer - 1).rows {
+                    let weight = self.value_change_generator.generate(self.value_change_lower_limit, self.value_change_upper_limit);
+                    network.set_weight(layer - 1, row, neuron, weight);
+                }
+                let bias = self.value_change_generator.generate(self.value_change_lower_limit, self.value_change_upper_limit);
+                network.set_bias(layer - 1, neuron, bias);
+            }
+        }
+        if self.roll(self.remove_neuron_probability) {
+            let layers = Self::hidden_layers_where(network, |size| size > limits.min_neurons.max(1));
+            if !layers.is_empty() {
+                let layer = layers[self.structure_generator.generate(0, layers.len())];
+                let neuron = self.structure_generator.generate(0, network.get_weights_dimension(layer).rows);
+                network.remove_neuron(layer, neuron);
+            }
+        }
+    }
+
+    // hidden layers whose number of neurons satisfies the condition
+    fn hidden_layers_where(network: &Network<T>, condition: impl Fn(usize) -> bool) -> Vec<usize> {
+        (1..network.get_num_layers() - 1)
+            .filter(|&layer| condition(network.get_weights_dimension(layer).rows))
+            .collect()
+    }
+
+    fn mutate_value(&mut self, mut value: T, rate: T) -> T {
+        if self.roll(self.value_change_probability * rate) {
+            let value_change = self.value_change_generator.generate(self.value_change_lower_limit, self.value_change_upper_limit);
+            value = value + value_change * self.factor;
+        }
+        if self.roll(self.gaussian_probability * rate) {
+            value = value + self.value_change_generator.generate_normal(T::zero(), self.gaussian_std_dev) * self.factor;
+        }
+        if self.roll(self.replacement_probability * rate) {
+            value = self.value_change_generator.generate(self.replacement_lower_limit, self.replacement_upper_limit);
+        }
+        if self.roll(self.sign_flip_probability * rate) {
+            value = -value;
+        }
+        if self.roll(self.clamp_probability * rate) {
+            value = value.max(self.clamp_lower_limit).min(self.clamp_upper_limit);
+        }
+        value
+    }
+
+    // zero probability does not consume a random number, so unused kinds keep the sequence of the others
+    fn roll(&mut self, probability: T) -> bool {
+        if probability <= T::zero() {
+            return false;
+        }
+        let mutation_roll = self.probability_generator.generate(T::zero(), T::one());
+        mutation_roll < probability
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::activation::Activation;
+
+    fn create_network(value: f64) -> Network<f64> {
+        let mut network = Network::<f64>::new(3);
+        network.resize(0, 4);
+        network.resize(1, 6);
+        network.resize(2, 3);
         for layer in 0..network.get_num_weight_layers() {
             let dimensions = network.get_weights_dimension(layer);
             for row in 0..dimensions.rows {
                 for col in 0..dimensions.cols {
-                    let mutation_roll = self.probability_generator.generate(0.0, 1.0);
-                    if mutation_roll < self.value_change_probability {
-                        let value_change = self.value_change_generator.generate(self.value_change_lower_limit, self.value_change_upper_limit);
-                        let mut weight = network.get_weight(layer, row, col);
-                        weight += value_change * self.factor;
-                        network.set_weight(layer, row, col, weight);
-                    }
+                    network.set_weight(layer, row, col, value);
                 }
             }
+            for col in 0..dimensions.cols {
+                network.set_bias(layer, col, value);
+            }
         }
-        for layer in 0..network.get_num_bias_layers() {
-            let dimensions = network.get_biases_dimension(layer);
-            assert_eq!(dimensions.rows, 1, "something is off, biases layer should have dimensions 1xN");
+        network
+    }
+
+    fn values(network: &Network<f64>, layer: usize) -> Vec<f64> {
+        let dimensions = network.get_weights_dimension(layer);
+        let mut values = Vec::new();
+        for row in 0..dimensions.rows {
             for col in 0..dimensions.cols {
-                let mutation_roll = self.probability_generator.generate(0.0, 1.0);
-                if mutation_roll < self.value_change_probability {
-                    let value_change = self.value_change_generator.generate(self.value_change_lower_limit, self.value_change_upper_limit);
-                    let mut bias = network.get_bias(layer, col);
-                    bias += value_change * self.factor;
-                    network.set_bias(layer, col, bias);
-                }
+                values.push(network.get_weight(layer, row, col));
             }
         }
+        for col in 0..dimensions.cols {
+            values.push(network.get_bias(layer, col));
+        }
+        values
+    }
+
+    #[test]
+    fn try_new_test() {
+        let config = |config: NetworkMutatorConfig<f64>| NetworkMutator::try_new(config);
+        assert!(config(NetworkMutatorConfig { weight_mutation_probability: 1.5, ..Default::default() }).is_err());
+        assert!(config(NetworkMutatorConfig { sign_flip_probability: -0.1, ..Default::default() }).is_err());
+        assert!(config(NetworkMutatorConfig { layer_rates: vec![1.0, -1.0], ..Default::default() }).is_err());
+        assert!(config(NetworkMutatorConfig { gaussian_std_dev: -1.0, ..Default::default() }).is_err());
+        assert_eq!(config(NetworkMutatorConfig { replacement_probability: 0.5, replacement_lower_limit: 1.0, replacement_upper_limit: 1.0, ..Default::default() }).err(), Some(Error::InvalidRange));
+        assert_eq!(config(NetworkMutatorConfig { clamp_probability: 1.0, clamp_lower_limit: 1.0, clamp_upper_limit: -1.0, ..Default::default() }).err(), Some(Error::InvalidRange));
+        assert_eq!(config(NetworkMutatorConfig { clamp_probability: 1.5, ..Default::default() }).err(),
+                   Some(Error::InvalidParameter("mutation probabilities must be in [0, 1]")));
+        assert!(config(NetworkMutatorConfig::default()).is_ok());
+    }
+
+    #[test]
+    fn default_does_nothing_test() {
+        let mut network = create_network(0.5);
+        NetworkMutator::new(NetworkMutatorConfig::default()).mutate(&mut network);
+        assert!(network == create_network(0.5));
+    }
+
+    #[test]
+    fn uniform_test() {
+        let mut network = create_network(0.0);
+        let mut mutator = NetworkMutator::new(NetworkMutatorConfig {
+            random_generator_seed: 3,
+            weight_mutation_probability: 1.0,
+            weight_change_lower_limit: -0.5,
+            weight_change_upper_limit: 0.5,
+            ..Default::default()
+        });
+        mutator.set_factor(0.5);
+        mutator.mutate(&mut network);
+        let values = values(&network, 0);
+        assert!(values.iter().all(|v| *v != 0.0 && v.abs() <= 0.25));
+    }
+
+    #[test]
+    fn gaussian_test() {
+        let mut network = create_network(1.0);
+        NetworkMutator::new(NetworkMutatorConfig {
+            random_generator_seed: 4,
+            gaussian_mutation_probability: 1.0,
+            gaussian_std_dev: 0.01,
+            ..Default::default()
+        }).mutate(&mut network);
+        let values = values(&network, 0);
+        assert!(values.iter().all(|v| *v != 1.0 && (v - 1.0).abs() < 0.1));
+    }
+
+    #[test]
+    fn replacement_test() {
+        let mut network = create_network(10.0);
+        NetworkMutator::new(NetworkMutatorConfig {
+            random_generator_seed: 5,
+            replacement_probability: 1.0,
+            replacement_lower_limit: 2.0,
+            replacement_upper_limit: 3.0,
+            ..Default::default()
+        }).mutate(&mut network);
+        assert!(values(&network, 1).iter().all(|v| (2.0..3.0).contains(v)));
+    }
+
+    #[test]
+    fn sign_flip_test() {
+        let mut network = create_network(0.7);
+        NetworkMutator::new(NetworkMutatorConfig {
+            random_generator_seed: 6,
+            sign_flip_probability: 0.5,
+            ..Default::default()
+        }).mutate(&mut network);
+        let values = values(&network, 0);
+        let flipped = values.iter().filter(|v| **v == -0.7).count();
+        assert!(values.iter().all(|v| v.abs() == 0.7));
+        assert!(flipped > 5 && flipped < values.len() - 5);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn clamp_test() {
+        let mut network = create_network(0.9);
+        NetworkMutator::new(NetworkMutatorConfig {
+            random_generator_seed: 7,
+            weight_mutation_probability: 1.0,
+            weight_change_lower_limit: 0.5,
+            weight_change_upper_limit: 1.0,
+            clamp_probability: 1.0,
+            ..Default::default()
+        }).mutate(&mut network);
+        assert!(values(&network, 0).iter().all(|v| *v == 1.0));
+        // clamping is a kind of its own, it also applies to values no other kind changed
+        let mut network = create_network(0.9);
+        NetworkMutator::new(NetworkMutatorConfig {
+            random_generator_seed: 7,
+            clamp_probability: 1.0,
+            clamp_lower_limit: -0.5,
+            clamp_upper_limit: 0.5,
+            ..Default::default()
+        }).mutate(&mut network);
+        assert!(values(&network, 0).iter().all(|v| *v == 0.5));
+        let mut network = create_network(0.9);
+        NetworkMutator::new(NetworkMutatorConfig {
+            random_generator_seed: 7,
+            clamp_probability: 0.5,
+            clamp_lower_limit: -0.5,
+            clamp_upper_limit: 0.5,
+            ..Default::default()
+        }).mutate(&mut network);
+        let values = values(&network, 0);
+        assert!(values.iter().all(|v| *v == 0.5 || *v == 0.9));
+        assert!(values.contains(&0.5) && values.contains(&0.9));
+    }
+
+    #[test]
+    fn layer_rates_test() {
+        let mut network = create_network(0.0);
+        NetworkMutator::new(NetworkMutatorConfig {
+            random_generator_seed: 8,
+            weight_mutation_probability: 1.0,
+            layer_rates: vec![0.0],
+            ..Default::default()
+        }).mutate(&mut network);
+        // the first layer is frozen, the second one (no rate given) mutates
+        assert!(values(&network, 0).iter().all(|v| *v == 0.0));
+        assert!(values(&network, 1).iter().all(|v| *v != 0.0));
+    }
+
+    #[test]
+    fn reproducible_test() {
+        let config = NetworkMutatorConfig {
+            random_generator_seed: 9,
+            weight_mutation_probability: 0.5,
+            gaussian_mutation_probability: 0.3,
+            replacement_probability: 0.1,
+            sign_flip_probability: 0.1,
+            ..Default::default()
+        };
+        let mut first = create_network(0.3);
+        let mut second = create_network(0.3);
+        NetworkMutator::new(config.clone()).mutate(&mut first);
+        NetworkMutator::new(config).mutate(&mut second);
+        assert!(first == second);
+        assert!(first != create_network(0.3));
+    }
+
+    #[test]
+    fn generic_test() {
+        let mut network = Network::<f32>::new(3);
+        NetworkMutator::new(NetworkMutatorConfig {
+            random_generator_seed: 1,
+            weight_mutation_probability: 1.0_f32,
+            ..Default::default()
+        }).mutate(&mut network);
+        assert!(network.get_weight(0, 0, 0) != 0.0);
+    }
+
+    fn hidden_sizes(network: &Network<f64>) -> Vec<usize> {
+        (1..network.get_num_layers() - 1).map(|layer| network.get_weights_dimension(layer).rows).collect()
+    }
+
+    fn sample_output(network: &mut Network<f64>) -> f64 {
+        let mut input = crate::math::matrix::Matrix::new(1, 4);
+        input[0][0] = 0.5;
+        input[0][1] = -0.25;
+        input[0][3] = 0.75;
+        network.forward(&input)[0][0]
+    }
+
+    #[test]
+    fn add_neuron_test() {
+        let mut network = create_network(0.2);
+        let expected = sample_output(&mut network);
+        let mut mutator = NetworkMutator::new(NetworkMutatorConfig {
+            random_generator_seed: 10,
+            add_neuron_probability: 1.0,
+            topology_limits: TopologyLimits { min_hidden_layers: 1, max_hidden_layers: 3, min_neurons: 1, max_neurons: 8 },
+            ..Default::default()
+        });
+        mutator.mutate(&mut network);
+        assert_eq!(hidden_sizes(&network), vec![7]);
+        // zero outgoing weights, the output stays the same
+        assert_eq!(sample_output(&mut network), expected);
+        // 7 + 1 would reach the exclusive maximum
+        mutator.mutate(&mut network);
+        assert_eq!(hidden_sizes(&network), vec![7]);
+    }
+
+    #[test]
+    fn remove_neuron_test() {
+        let mut network = create_network(0.2);
+        let mut mutator = NetworkMutator::new(NetworkMutatorConfig {
+            random_generator_seed: 11,
+            remove_neuron_probability: 1.0,
+            topology_limits: TopologyLimits { min_hidden_layers: 1, max_hidden_layers: 3, min_neurons: 4, max_neurons: 8 },
+            ..Default::default()
+        });
+        for _ in 0..5 {
+            mutator.mutate(&mut network);
+        }
+        assert_eq!(hidden_sizes(&network), vec![4]);
+        assert_eq!(network.get_weights_dimension(0).cols, 4);
+    }
+
+    #[test]
+    fn add_remove_layer_test() {
+        let mut network = create_network(0.2);
+        let limits = TopologyLimits { min_hidden_layers: 1, max_hidden_layers: 4, min_neurons: 1, max_neurons: 8 };
+        let mut mutator = NetworkMutator::new(NetworkMutatorConfig {
+            random_generator_seed: 12,
+            add_layer_probability: 1.0,
+            topology_limits: limits,
+            ..Default::default()
+        });
+        for _ in 0..5 {
+            mutator.mutate(&mut network);
+        }
+        // at most 3 hidden layers, the input layer (4) and the hidden layer (6) are valid sizes to copy
+        assert_eq!(network.get_num_layers(), 5);
+        assert!(hidden_sizes(&network).iter().all(|size| *size == 4 || *size == 6));
+        // the new layers copy the ReLU of their neighbours, only one in front of the first hidden layer
+        // cuts off the negative input and changes the output
+        assert!((0..3).all(|layer| network.get_activation(layer) == Activation::ReLU));
+        assert!(sample_output(&mut network).is_finite());
+
+        let mut mutator = NetworkMutator::new(NetworkMutatorConfig {
+            random_generator_seed: 13,
+            remove_layer_probability: 1.0,
+            topology_limits: limits,
+            ..Default::default()
+        });
+        for _ in 0..5 {
+            mutator.mutate(&mut network);
+        }
+        // any hidden layer can be merged until the lower limit is reached
+        assert_eq!(network.get_num_layers(), 3);
+        assert!(hidden_sizes(&network).iter().all(|size| *size == 4 || *size == 6));
+        assert!(sample_output(&mut network).is_finite());
+    }
+
+    #[test]
+    fn creator_limits_test() {
+        let mut creator = crate::network::network_creator::NetworkCreator::<f64>::new(1, -1.0, 1.0, 2, 1, 3, 3, 2, 6);
+        let mut mutator = NetworkMutator::new(NetworkMutatorConfig {
+            random_generator_seed: 14,
+            add_neuron_probability: 0.5,
+            remove_neuron_probability: 0.5,
+            add_layer_probability: 0.5,
+            remove_layer_probability: 0.5,
+            topology_limits: creator.get_topology_limits(),
+            ..Default::default()
+        });
+        let mut network = creator.create(4, 2);
+        for _ in 0..200 {
+            mutator.mutate(&mut network);
+            let sizes = hidden_sizes(&network);
+            assert!((1..3).contains(&sizes.len()));
+            assert!(sizes.iter().all(|size| (2..6).contains(size)));
+        }
+    }
+}
@@ -0,0 +1,580 @@
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use crate::math::activation::Activation;
+use crate::network::network::Network;
+
+/*
+    text format (one record per line):
+
+        brain-network
+        version 1
+        layers 3 4 2
+        activation 0 ReLU
+        activation 1 LeakyReLU 0.01
+        weights 0
+        <one line per row, values separated by spaces>
+        biases 0
+        <one line>
+        ...
+
+    binary format (little endian):
+
+        magic "BRNN" | u32 version | u8 scalar width (4 or 8) | u32 number of layers | u32 layer sizes ...
+        per activation layer: u8 activation tag | f64 parameter
+        weights of every weight layer, row by row
+        biases of every bias layer
+*/
+
+const TEXT_HEADER: &str = "brain-network";
+const BINARY_MAGIC: &[u8; 4] = b"BRNN";
+pub const FORMAT_VERSION: u32 = 1;
+
+#[derive(Debug)]
+pub enum SerializationError {
+    Io(io::Error),
+    InvalidFormat(String),
+    UnsupportedVersion(u32)
+}
+
+impl fmt::Display for SerializationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SerializationError::Io(e) => write!(f, "i/o error: {}", e),
+            SerializationError::InvalidFormat(message) => write!(f, "invalid format: {}", message),
+            SerializationError::UnsupportedVersion(version) => write!(f, "unsupported format version: {}", version)
+        }
+    }
+}
+
+impl std::error::Error for SerializationError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SerializationError::Io(e) => Some(e),
+            _ => None
+        }
+    }
+}
+
+impl From<io::Error> for SerializationError {
+    fn from(e: io::Error) -> Self {
+        SerializationError::Io(e)
+    }
+}
+
+fn invalid<V>(message: impl Into<String>) -> Result<V, SerializationError> {
+    Err(SerializationError::InvalidFormat(message.into()))
+}
+
+fn activation_to_tag(activation: Activation) -> (u8, f64) {
+    match activation {
+        Activation::ReLU => (0, 0.0),
+        Activation::LeakyReLU(alpha) => (1, alpha),
+        Activation::Sigmoid => (2, 0.0),
+        Activation::Tanh => (3, 0.0),
+        Activation::Identity => (4, 0.0),
+        Activation::Softmax => (5, 0.0),
+        Activation::GELU => (6, 0.0)
+    }
+}
+
+fn activation_from_tag(tag: u8, parameter: f64) -> Result<Activation, SerializationError> {
+    match tag {
+        0 => Ok(Activation::ReLU),
+        1 => Ok(Activation::LeakyReLU(parameter)),
+        2 => Ok(Activation::Sigmoid),
+        3 => Ok(Activation::Tanh),
+        4 => Ok(Activation::Identity),
+        5 => Ok(Activation::Softmax),
+        6 => Ok(Activation::GELU),
+        _ => invalid(format!("unknown activation tag {}", tag))
+    }
+}
+
+fn activation_to_text(activation: Activation) -> String {
+    match activation {
+        Activation::LeakyReLU(alpha) => format!("LeakyReLU {:?}", alpha),
+        _ => format!("{:?}", activation)
+    }
+}
+
+fn activation_from_text(tokens: &[&str]) -> Result<Activation, SerializationError> {
+    match tokens {
+        ["ReLU"] => Ok(Activation::ReLU),
+        ["LeakyReLU", alpha] => Ok(Activation::LeakyReLU(parse_value(alpha)?)),
+        ["Sigmoid"] => Ok(Activation::Sigmoid),
+        ["Tanh"] => Ok(Activation::Tanh),
+        ["Identity"] => Ok(Activation::Identity),
+        ["Softmax"] => Ok(Activation::Softmax),
+        ["GELU"] => Ok(Activation::GELU),
+        _ => invalid(format!("unknown activation '{}'", tokens.join(" ")))
+    }
+}
+
+fn parse_value<V: std::str::FromStr>(token: &str) -> Result<V, SerializationError> {
+    match token.parse::<V>() {
+        Ok(value) => Ok(value),
+        Err(_) => invalid(format!("cannot parse '{}'", token))
+    }
+}
+
+// checks the layer sizes before they are handed to the (panicking) network constructor,
+// the input has to hold exactly as many values as the layers need, so hostile sizes cannot trigger huge allocations
+fn validate_layer_sizes(sizes: &[usize], available_values: usize) -> Result<(), SerializationError> {
+    validate_num_layers(sizes.len())?;
+    if sizes.contains(&0) {
+        return invalid("layer sizes cannot be zero");
+    }
+    match count_parameters(sizes) {
+        Some(expected) if expected == available_values => Ok(()),
+        Some(expected) => invalid(format!("the layer sizes need {} values, found {}", expected, available_values)),
+        None => invalid("the layer sizes are too large")
+    }
+}
+
+fn validate_num_layers(num_layers: usize) -> Result<(), SerializationError> {
+    if num_layers < 3 {
+        return invalid(format!("a network needs at least 3 layers, found {}", num_layers));
+    }
+    Ok(())
+}
+
+// number of weights and biases of a network with these layer sizes, None on overflow
+fn count_parameters(sizes: &[usize]) -> Option<usize> {
+    sizes.windows(2).try_fold(0usize, |count, pair| {
+        pair[0].checked_mul(pair[1])?.checked_add(pair[1])?.checked_add(count)
+    })
+}
+
+fn create_network<T>(sizes: &[usize]) -> Network<T>
+where
+    T: num::Float + num::Signed + Default + fmt::Debug
+{
+    let mut network = Network::new(sizes.len());
+    for (i, size) in sizes.iter().enumerate() {
+        network.resize(i, *size);
+    }
+    network
+}
+
+pub struct NetworkSerializer { }
+
+impl NetworkSerializer {
+    pub fn write_text<T, W>(network: &Network<T>, writer: &mut W) -> Result<(), SerializationError>
+    where
+        T: num::Float + Default + fmt::Debug,
+        W: Write
+    {
+        writeln!(writer, "{}", TEXT_HEADER)?;
+        writeln!(writer, "version {}", FORMAT_VERSION)?;
+        let mut sizes = vec![network.get_weights_dimension(0).rows];
+        for i in 0..network.get_num_bias_layers() {
+            sizes.push(network.get_biases_dimension(i).cols);
+        }
+        let sizes: Vec<String> = sizes.iter().map(|size| size.to_string()).collect();
+        writeln!(writer, "layers {}", sizes.join(" "))?;
+        for i in 0..network.get_num_activation_layers() {
+            writeln!(writer, "activation {} {}", i, activation_to_text(network.get_activation(i)))?;
+        }
+        for layer in 0..network.get_num_weight_layers() {
+            writeln!(writer, "weights {}", layer)?;
+            let dimensions = network.get_weights_dimension(layer);
+            for row in 0..dimensions.rows {
+                let values: Vec<String> = (0..dimensions.cols)
+                    .map(|col| format!("{:?}", network.get_weight(layer, row, col).to_f64().unwrap()))
+                    .collect();
+                writeln!(writer, "{}", values.join(" "))?;
+            }
+        }
+        for layer in 0..network.get_num_bias_layers() {
+            writeln!(writer, "biases {}", layer)?;
+            let dimensions = network.get_biases_dimension(layer);
+            let values: Vec<String> = (0..dimensions.cols)
+                .map(|col| format!("{:?}", network.get_bias(layer, col).to_f64().unwrap()))
+                .collect();
+            writeln!(writer, "{}", values.join(" "))?;
+        }
+        Ok(())
+    }
+
+    pub fn read_text<T, R>(reader: R) -> Result<Network<T>, SerializationError>
+    where
+        T: num::Float + num::Signed + Default + fmt::Debug,
+        R: BufRead
+    {
+        let mut lines = Vec::new();
+        for line in reader.lines() {
+            let line = line?;
+            if !line.trim().is_empty() {
+                lines.push(line);
+            }
+        }
+        // every token of a line which does not start with a keyword is a weight or bias
+        let keywords = [TEXT_HEADER, "version", "layers", "activation", "weights", "biases"];
+        let available_values = lines.iter()
+            .map(|line| line.split_whitespace().collect::<Vec<&str>>())
+            .filter(|tokens| !keywords.contains(&tokens[0]))
+            .map(|tokens| tokens.len())
+            .sum();
+        let mut lines = lines.iter().map(|line| line.split_whitespace().collect::<Vec<&str>>());
+        let mut next_line = |expected: &str| match lines.next() {
+            Some(tokens) => Ok(tokens),
+            None => invalid(format!("unexpected end of file, expected {}", expected))
+        };
+
+        if next_line("header")? != [TEXT_HEADER] {
+            return invalid("missing header");
+        }
+        let version = match next_line("version")?.as_slice() {
+            ["version", version] => parse_value::<u32>(version)?,
+            _ => return invalid("missing version")
+        };
+        if version != FORMAT_VERSION {
+            return Err(SerializationError::UnsupportedVersion(version));
+        }
+        let sizes = match next_line("layers")?.split_first() {
+            Some((&"layers", sizes)) => sizes.iter().map(|size| parse_value::<usize>(size)).collect::<Result<Vec<usize>, _>>()?,
+            _ => return invalid("missing layer sizes")
+        };
+        validate_layer_sizes(&sizes, available_values)?;
+        let mut network = create_network::<T>(&sizes);
+
+        for i in 0..network.get_num_activation_layers() {
+            let tokens = next_line("activation")?;
+            if tokens.len() < 3 || tokens[0] != "activation" || parse_value::<usize>(tokens[1])? != i {
+                return invalid(format!("expected activation {}", i));
+            }
+            network.set_activation(i, activation_from_text(&tokens[2..])?);
+        }
+        for layer in 0..network.get_num_weight_layers() {
+            if next_line("weights")? != ["weights", layer.to_string().as_str()] {
+                return invalid(format!("expected weights {}", layer));
+            }
+            let dimensions = network.get_weights_dimension(layer);
+            for row in 0..dimensions.rows {
+                let tokens = next_line("weight row")?;
+                if tokens.len() != dimensions.cols {
+                    return invalid(format!("weights {} row {} has {} values instead of {}", layer, row, tokens.len(), dimensions.cols));
+                }
+                for (col, token) in tokens.iter().enumerate() {
+                    network.set_weight(layer, row, col, T::from(parse_value::<f64>(token)?).unwrap());
+                }
+            }
+        }
+        for layer in 0..network.get_num_bias_layers() {
+            if next_line("biases")? != ["biases", layer.to_string().as_str()] {
+                return invalid(format!("expected biases {}", layer));
+            }
+            let dimensions = network.get_biases_dimension(layer);
+            let tokens = next_line("bias row")?;
+            if tokens.len() != dimensions.cols {
+                return invalid(format!("biases {} has {} values instead of {}", layer, tokens.len(), dimensions.cols));
+            }
+            for (col, token) in tokens.iter().enumerate() {
+                network.set_bias(layer, col, T::from(parse_value::<f64>(token)?).unwrap());
+            }
+        }
+        if lines.next().is_some() {
+            return invalid("unexpected data after the last bias layer");
+        }
+        Ok(network)
+    }
+
+    pub fn to_bytes<T>(network: &Network<T>) -> Vec<u8>
+    where
+        T: num::Float + Default + fmt::Debug
+    {
+        let width = std::mem::size_of::<T>();
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(BINARY_MAGIC);
+        bytes.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+        bytes.push(width as u8);
+        let mut sizes = vec![network.get_weights_dimension(0).rows];
+        for i in 0..network.get_num_bias_layers() {
+            sizes.push(network.get_biases_dimension(i).cols);
+        }
+        bytes.extend_from_slice(&(sizes.len() as u32).to_le_bytes());
+        for size in sizes {
+            bytes.extend_from_slice(&(size as u32).to_le_bytes());
+        }
+        for i in 0..network.get_num_activation_layers() {
+            let (tag, parameter) = activation_to_tag(network.get_activation(i));
+            bytes.push(tag);
+            bytes.extend_from_slice(&parameter.to_le_bytes());
+        }
+        let mut push_value = |value: T| {
+            if width == 4 {
+                bytes.extend_from_slice(&value.to_f32().unwrap().to_le_bytes());
+            } else {
+                bytes.extend_from_slice(&value.to_f64().unwrap().to_le_bytes());
+            }
+        };
+        for layer in 0..network.get_num_weight_layers() {
+            let dimensions = network.get_weights_dimension(layer);
+            for row in 0..dimensions.rows {
+                for col in 0..dimensions.cols {
+                    push_value(network.get_weight(layer, row, col));
+                }
+            }
+        }
+        for layer in 0..network.get_num_bias_layers() {
+            for col in 0..network.get_biases_dimension(layer).cols {
+                push_value(network.get_bias(layer, col));
+            }
+        }
+        bytes
+    }
+
+    pub fn from_bytes<T>(bytes: &[u8]) -> Result<Network<T>, SerializationError>
+    where
+        T: num::Float + num::Signed + Default + fmt::Debug
+    {
+        let mut offset = 0;
+        let mut take = |n: usize| -> Result<&[u8], SerializationError> {
+            if offset + n > bytes.len() {
+                return invalid("unexpected end of data");
+            }
+            offset += n;
+            Ok(&bytes[offset - n..offset])
+        };
+        fn to_u32(b: &[u8]) -> u32 { u32::from_le_bytes(b.try_into().unwrap()) }
+        fn to_f64(b: &[u8]) -> f64 { f64::from_le_bytes(b.try_into().unwrap()) }
+        fn to_f32(b: &[u8]) -> f32 { f32::from_le_bytes(b.try_into().unwrap()) }
+
+        if take(4)? != BINARY_MAGIC {
+            return invalid("missing magic number");
+        }
+        let version = to_u32(take(4)?);
+        if version != FORMAT_VERSION {
+            return Err(SerializationError::UnsupportedVersion(version));
+        }
+        let width = take(1)?[0] as usize;
+        if width != 4 && width != 8 {
+            return invalid(format!("unsupported scalar width {}", width));
+        }
+        let num_layers = to_u32(take(4)?) as usize;
+        // the sizes of the activation and value sections below rely on at least 3 layers
+        validate_num_layers(num_layers)?;
+        let mut sizes = Vec::new();
+        for _ in 0..num_layers {
+            sizes.push(to_u32(take(4)?) as usize);
+        }
+        // what is left after the header and the activations are the values, width bytes each
+        let activation_bytes = (sizes.len() - 1).saturating_mul(9);
+        let header_bytes = 4 + 4 + 1 + 4 + 4 * sizes.len();
+        let value_bytes = bytes.len().saturating_sub(header_bytes + activation_bytes);
+        if value_bytes % width != 0 {
+            return invalid("incomplete value at the end of the data");
+        }
+        validate_layer_sizes(&sizes, value_bytes / width)?;
+        let mut network = create_network::<T>(&sizes);
+        for i in 0..network.get_num_activation_layers() {
+            let tag = take(1)?[0];
+            let parameter = to_f64(take(8)?);
+            network.set_activation(i, activation_from_tag(tag, parameter)?);
+        }
+        let mut next_value = || -> Result<T, SerializationError> {
+            let value = if width == 4 { to_f32(take(4)?) as f64 } else { to_f64(take(8)?) };
+            Ok(T::from(value).unwrap())
+        };
+        for layer in 0..network.get_num_weight_layers() {
+            let dimensions = network.get_weights_dimension(layer);
+            for row in 0..dimensions.rows {
+                for col in 0..dimensions.cols {
+                    let value = next_value()?;
+                    network.set_weight(layer, row, col, value);
+                }
+            }
+        }
+        for layer in 0..network.get_num_bias_layers() {
+            for col in 0..network.get_biases_dimension(layer).cols {
+                let value = next_value()?;
+                network.set_bias(layer, col, value);
+            }
+        }
+        if offset != bytes.len() {
+            return invalid("unexpected data after the last bias layer");
+        }
+        Ok(network)
+    }
+
+    pub fn save_text<T, P>(network: &Network<T>, path: P) -> Result<(), SerializationError>
+    where
+        T: num::Float + Default + fmt::Debug,
+        P: AsRef<Path>
+    {
+        let mut writer = BufWriter::new(File::create(path)?);
+        Self::write_text(network, &mut writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    pub fn load_text<T, P>(path: P) -> Result<Network<T>, SerializationError>
+    where
+        T: num::Float + num::Signed + Default + fmt::Debug,
+        P: AsRef<Path>
+    {
+        Self::read_text(BufReader::new(File::open(path)?))
+    }
+
+    pub fn save_binary<T, P>(network: &Network<T>, path: P) -> Result<(), SerializationError>
+    where
+        T: num::Float + Default + fmt::Debug,
+        P: AsRef<Path>
+    {
+        let mut file = File::create(path)?;
+        file.write_all(&Self::to_bytes(network))?;
+        Ok(())
+    }
+
+    pub fn load_binary<T, P>(path: P) -> Result<Network<T>, SerializationError>
+    where
+        T: num::Float + num::Signed + Default + fmt::Debug,
+        P: AsRef<Path>
+    {
+        let mut bytes = Vec::new();
+        File::open(path)?.read_to_end(&mut bytes)?;
+        Self::from_bytes(&bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::network_initializer::NetworkInitializer;
+
+    fn create_test_network<T>(seed: u64) -> Network<T>
+    where
+        T: num::Float + num::Signed + Default + fmt::Debug + rand::distr::uniform::SampleUniform
+    {
+        let mut network = create_network::<T>(&[4, 6, 3, 2]);
+        let mut initializer = NetworkInitializer::new(seed, T::from(-1.0).unwrap(), T::from(1.0).unwrap());
+        initializer.initialize(&mut network);
+        network.set_activation(0, Activation::LeakyReLU(0.01));
+        network.set_activation(1, Activation::GELU);
+        network.set_activation(2, Activation::Softmax);
+        network
+    }
+
+    fn to_text<T: num::Float + Default + fmt::Debug>(network: &Network<T>) -> String {
+        let mut buffer = Vec::new();
+        NetworkSerializer::write_text(network, &mut buffer).unwrap();
+        String::from_utf8(buffer).unwrap()
+    }
+
+    #[test]
+    fn test_text_round_trip() {
+        let network = create_test_network::<f64>(3);
+        let text = to_text(&network);
+        let loaded = NetworkSerializer::read_text::<f64, _>(text.as_bytes()).unwrap();
+        assert!(network == loaded);
+        let network = create_test_network::<f32>(4);
+        let loaded = NetworkSerializer::read_text::<f32, _>(to_text(&network).as_bytes()).unwrap();
+        assert!(network == loaded);
+    }
+
+    #[test]
+    fn test_binary_round_trip() {
+        let network = create_test_network::<f64>(5);
+        let loaded = NetworkSerializer::from_bytes::<f64>(&NetworkSerializer::to_bytes(&network)).unwrap();
+        assert!(network == loaded);
+        let network = create_test_network::<f32>(6);
+        let bytes = NetworkSerializer::to_bytes(&network);
+        let loaded = NetworkSerializer::from_bytes::<f32>(&bytes).unwrap();
+        assert!(network == loaded);
+        // f32 networks are stored with 4 bytes per value
+        let values = 4 * 6 + 6 * 3 + 3 * 2 + 6 + 3 + 2;
+        assert_eq!(bytes.len(), 4 + 4 + 1 + 4 + 4 * 4 + 3 * 9 + values * 4);
+    }
+
+    #[test]
+    fn test_file_round_trip() {
+        let network = create_test_network::<f32>(7);
+        let directory = std::env::temp_dir();
+        let text_path = directory.join(format!("brain_network_test_{}.txt", std::process::id()));
+        let binary_path = directory.join(format!("brain_network_test_{}.bin", std::process::id()));
+        NetworkSerializer::save_text(&network, &text_path).unwrap();
+        NetworkSerializer::save_binary(&network, &binary_path).unwrap();
+        assert!(network == NetworkSerializer::load_text::<f32, _>(&text_path).unwrap());
+        assert!(network == NetworkSerializer::load_binary::<f32, _>(&binary_path).unwrap());
+        std::fs::remove_file(text_path).unwrap();
+        std::fs::remove_file(binary_path).unwrap();
+    }
+
+    #[test]
+    fn test_missing_file() {
+        let result = NetworkSerializer::load_text::<f32, _>("/this/file/does/not/exist");
+        assert!(matches!(result, Err(SerializationError::Io(_))));
+    }
+
+    #[test]
+    fn test_text_errors() {
+        let text = to_text(&create_test_network::<f64>(8));
+        let read = |text: &str| NetworkSerializer::read_text::<f64, _>(text.as_bytes());
+        assert!(matches!(read(""), Err(SerializationError::InvalidFormat(_))));
+        assert!(matches!(read(&text.replace("version 1", "version 2")), Err(SerializationError::UnsupportedVersion(2))));
+        assert!(matches!(read(&text.replace("layers 4 6 3 2", "layers 4 2")), Err(SerializationError::InvalidFormat(_))));
+        assert!(matches!(read(&text.replace("layers 4 6 3 2", "layers 4 0 3 2")), Err(SerializationError::InvalidFormat(_))));
+        assert!(matches!(read(&text.replace("GELU", "Swish")), Err(SerializationError::InvalidFormat(_))));
+        // truncated file
+        let truncated: String = text.lines().take(10).collect::<Vec<&str>>().join("\n");
+        assert!(matches!(read(&truncated), Err(SerializationError::InvalidFormat(_))));
+        // not a number
+        let mut lines: Vec<String> = text.lines().map(String::from).collect();
+        lines[7] = lines[7].replacen(|c: char| c.is_ascii_digit(), "x", 1);
+        assert!(matches!(read(&lines.join("\n")), Err(SerializationError::InvalidFormat(_))));
+        // trailing data
+        assert!(matches!(read(&format!("{}0.5\n", text)), Err(SerializationError::InvalidFormat(_))));
+    }
+
+    #[test]
+    fn test_binary_errors() {
+        let bytes = NetworkSerializer::to_bytes(&create_test_network::<f64>(9));
+        let read = |bytes: &[u8]| NetworkSerializer::from_bytes::<f64>(bytes);
+        assert!(matches!(read(&[]), Err(SerializationError::InvalidFormat(_))));
+        assert!(matches!(read(&bytes[..bytes.len() - 1]), Err(SerializationError::InvalidFormat(_))));
+        let mut wrong_magic = bytes.clone();
+        wrong_magic[0] = b'X';
+        assert!(matches!(read(&wrong_magic), Err(SerializationError::InvalidFormat(_))));
+        let mut wrong_version = bytes.clone();
+        wrong_version[4] = 9;
+        assert!(matches!(read(&wrong_version), Err(SerializationError::UnsupportedVersion(9))));
+        let mut wrong_width = bytes.clone();
+        wrong_width[8] = 2;
+        assert!(matches!(read(&wrong_width), Err(SerializationError::InvalidFormat(_))));
+        let mut extra = bytes.clone();
+        extra.push(0);
+        assert!(matches!(read(&extra), Err(SerializationError::InvalidFormat(_))));
+    }
+
+    #[test]
+    fn test_oversized_layers() {
+        // the claimed sizes would need billions of values, the data only has a few
+        let text = to_text(&create_test_network::<f64>(10)).replace("layers 4 6 3 2", "layers 65535 65535 65535");
+        assert!(matches!(NetworkSerializer::read_text::<f64, _>(text.as_bytes()), Err(SerializationError::InvalidFormat(_))));
+        let mut bytes = NetworkSerializer::to_bytes(&create_test_network::<f64>(11));
+        for layer in 0..4 {
+            let start = 13 + 4 * layer;
+            bytes[start..start + 4].copy_from_slice(&65535u32.to_le_bytes());
+        }
+        assert!(matches!(NetworkSerializer::from_bytes::<f64>(&bytes), Err(SerializationError::InvalidFormat(_))));
+        // too few layers are rejected before the section sizes are computed from them
+        for num_layers in [0u32, 1] {
+            let mut bytes = NetworkSerializer::to_bytes(&create_test_network::<f64>(12));
+            bytes[9..13].copy_from_slice(&num_layers.to_le_bytes());
+            assert!(matches!(NetworkSerializer::from_bytes::<f64>(&bytes), Err(SerializationError::InvalidFormat(_))));
+            assert!(matches!(NetworkSerializer::from_bytes::<f64>(&bytes[..17]), Err(SerializationError::InvalidFormat(_))));
+        }
+        // sizes whose product does not even fit into usize
+        assert_eq!(count_parameters(&[usize::MAX, 2, 1]), None);
+        assert_eq!(count_parameters(&[4, 6, 3, 2]), Some(4 * 6 + 6 * 3 + 3 * 2 + 6 + 3 + 2));
+    }
+
+    #[test]
+    fn test_error_display() {
+        let error = SerializationError::UnsupportedVersion(3);
+        assert_eq!(format!("{}", error), "unsupported format version: 3");
+    }
+}
@@ -0,0 +1,97 @@
+use crate::math::matrix::Matrix;
+use crate::network::network::Network;
+use crate::error::{Error, OrPanic};
+
+/*
+    gradient of the loss with respect to every weight and bias of a network
+    the matrices have the same shapes as the weights and biases of the network
+*/
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gradients<T> {
+    pub weights: Vec<Matrix<T>>,
+    pub biases: Vec<Matrix<T>>
+}
+
+impl<T> Gradients<T>
+where
+    T: num::Float + Default
+{
+    // zero gradients shaped like the network
+    pub fn zeros(network: &Network<T>) -> Self {
+        let weights = (0..network.get_num_weight_layers()).map(|i| {
+            let dimensions = network.get_weights_dimension(i);
+            Matrix::new(dimensions.rows, dimensions.cols)
+        }).collect();
+        let biases = (0..network.get_num_bias_layers()).map(|i| {
+            let dimensions = network.get_biases_dimension(i);
+            Matrix::new(dimensions.rows, dimensions.cols)
+        }).collect();
+        Self { weights, biases }
+    }
+
+    pub fn has_same_shape(&self, other: &Gradients<T>) -> bool {
+        self.weights.len() == other.weights.len() && self.biases.len() == other.biases.len()
+            && self.weights.iter().zip(&other.weights).all(|(a, b)| a.get_dimensions() == b.get_dimensions())
+            && self.biases.iter().zip(&other.biases).all(|(a, b)| a.get_dimensions() == b.get_dimensions())
+    }
+
+    // true if the gradients have the shapes of the weights and biases of the network
+    pub fn fits(&self, network: &Network<T>) -> bool {
+        self.weights.len() == network.get_num_weight_layers() && self.biases.len() == network.get_num_bias_layers()
+            && self.weights.iter().enumerate().all(|(i, w)| w.get_dimensions() == network.get_weights_dimension(i))
+            && self.biases.iter().enumerate().all(|(i, b)| b.get_dimensions() == network.get_biases_dimension(i))
+    }
+
+    // element-wise sum, used to accumulate the gradients of multiple samples
+    pub fn accumulate(&mut self, other: &Gradients<T>) {
+        self.try_accumulate(other).or_panic()
+    }
+
+    pub fn try_accumulate(&mut self, other: &Gradients<T>) -> Result<(), Error> {
+        if !self.has_same_shape(other) {
+            return Err(Error::GradientShapeMismatch);
+        }
+        for (a, b) in self.weights.iter_mut().zip(&other.weights) {
+            *a = &*a + b;
+        }
+        for (a, b) in self.biases.iter_mut().zip(&other.biases) {
+            *a = &*a + b;
+        }
+        Ok(())
+    }
+
+    pub fn scale(&mut self, factor: T) {
+        for w in self.weights.iter_mut() {
+            w.apply(|x| x * factor);
+        }
+        for b in self.biases.iter_mut() {
+            b.apply(|x| x * factor);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accumulate() {
+        let mut network = Network::<f64>::new(3);
+        network.resize(0, 2);
+        network.resize(1, 2);
+        network.resize(2, 1);
+        let mut sum = Gradients::zeros(&network);
+        let mut ones = Gradients::zeros(&network);
+        ones.weights[1].apply(|_| 1.0);
+        sum.accumulate(&ones);
+        sum.accumulate(&ones);
+        assert_eq!(sum.weights[1][1][0], 2.0);
+        assert!(sum.fits(&network));
+        network.resize(1, 3);
+        let other = Gradients::zeros(&network);
+        assert!(!sum.fits(&network));
+        assert_eq!(sum.try_accumulate(&other), Err(Error::GradientShapeMismatch));
+        assert_eq!(sum.weights[1][1][0], 2.0);
+    }
+}
@@ -0,0 +1,116 @@
+use crate::math::matrix::Matrix;
+use crate::error::{Error, OrPanic};
+
+/*
+    loss functions comparing the output of the network with the expected output
+    both the loss and its gradient are averaged over the rows (samples) of the matrices
+*/
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Loss {
+    MeanSquaredError,
+    CrossEntropy // expects probabilities as output, e.g. from softmax or sigmoid
+}
+
+impl Loss {
+    // small value to avoid taking the logarithm of (or dividing by) zero
+    fn epsilon<T: num::Float>() -> T {
+        T::from(1e-12).unwrap()
+    }
+
+    pub fn compute<T: num::Float>(&self, output: &Matrix<T>, target: &Matrix<T>) -> T {
+        self.try_compute(output, target).or_panic()
+    }
+
+    pub fn try_compute<T: num::Float>(&self, output: &Matrix<T>, target: &Matrix<T>) -> Result<T, Error> {
+        Self::check_dimensions(output, target)?;
+        let mut sum = T::zero();
+        for row in 0..output.rows() {
+            for col in 0..output.cols() {
+                let (o, t) = (output[row][col], target[row][col]);
+                sum = sum + match self {
+                    Loss::MeanSquaredError => (o - t) * (o - t) / T::from(output.cols()).unwrap(),
+                    Loss::CrossEntropy => -t * (o + Self::epsilon()).ln()
+                };
+            }
+        }
+        Ok(sum / T::from(output.rows()).unwrap())
+    }
+
+    // gradient of the loss with respect to the output of the network
+    pub fn gradient<T: num::Float + Default>(&self, output: &Matrix<T>, target: &Matrix<T>) -> Matrix<T> {
+        self.try_gradient(output, target).or_panic()
+    }
+
+    pub fn try_gradient<T: num::Float + Default>(&self, output: &Matrix<T>, target: &Matrix<T>) -> Result<Matrix<T>, Error> {
+        Self::check_dimensions(output, target)?;
+        let mut result = Matrix::new(output.rows(), output.cols());
+        let rows = T::from(output.rows()).unwrap();
+        let two = T::from(2.0).unwrap();
+        for row in 0..output.rows() {
+            for col in 0..output.cols() {
+                let (o, t) = (output[row][col], target[row][col]);
+                result[row][col] = match self {
+                    Loss::MeanSquaredError => two * (o - t) / T::from(output.cols()).unwrap(),
+                    Loss::CrossEntropy => -t / (o + Self::epsilon())
+                } / rows;
+            }
+        }
+        Ok(result)
+    }
+
+    fn check_dimensions<T: Copy>(output: &Matrix<T>, target: &Matrix<T>) -> Result<(), Error> {
+        if output.get_dimensions() != target.get_dimensions() {
+            return Err(Error::DimensionMismatch { operation: "loss computation", left: output.get_dimensions(), right: target.get_dimensions() });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(values: &[f64]) -> Matrix<f64> {
+        let mut m = Matrix::new(1, values.len());
+        for (i, v) in values.iter().enumerate() {
+            m[0][i] = *v;
+        }
+        m
+    }
+
+    #[test]
+    fn test_mean_squared_error() {
+        let output = row(&[1.0, 2.0, 3.0]);
+        let target = row(&[1.0, 0.0, 4.0]);
+        assert!((Loss::MeanSquaredError.compute(&output, &target) - 5.0 / 3.0).abs() < 1e-12);
+        let gradient = Loss::MeanSquaredError.gradient(&output, &target);
+        assert!((gradient[0][0] - 0.0).abs() < 1e-12);
+        assert!((gradient[0][1] - 4.0 / 3.0).abs() < 1e-12);
+        assert!((gradient[0][2] - (-2.0 / 3.0)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_cross_entropy() {
+        let output = row(&[0.2, 0.7, 0.1]);
+        let target = row(&[0.0, 1.0, 0.0]);
+        assert!((Loss::CrossEntropy.compute(&output, &target) - (-(0.7_f64).ln())).abs() < 1e-9);
+        let gradient = Loss::CrossEntropy.gradient(&output, &target);
+        assert_eq!(gradient[0][0], 0.0);
+        assert!((gradient[0][1] - (-1.0 / 0.7)).abs() < 1e-9);
+        assert_eq!(gradient[0][2], 0.0);
+    }
+
+    #[test]
+    fn test_perfect_prediction() {
+        let output = row(&[0.0, 1.0]);
+        assert_eq!(Loss::MeanSquaredError.compute(&output, &output), 0.0);
+        assert!(Loss::CrossEntropy.compute(&output, &output).abs() < 1e-9);
+    }
+
+    #[test]
+    #[should_panic(expected = "matrix dimensions must match for loss computation")]
+    fn test_dimension_mismatch() {
+        let _ = Loss::MeanSquaredError.compute(&row(&[1.0, 2.0]), &row(&[1.0]));
+    }
+}
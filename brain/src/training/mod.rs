@@ -0,0 +1,4 @@
+pub mod loss;
+pub mod gradients;
+pub mod optimizer;
+pub mod trainer;
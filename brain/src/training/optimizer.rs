@@ -0,0 +1,289 @@
+use std::fmt;
+use crate::math::matrix::Matrix;
+use crate::network::network::Network;
+use crate::training::gradients::Gradients;
+use crate::error::{Error, OrPanic};
+
+/*
+    optimizers update the weights and biases of a network based on the gradients of the loss
+*/
+
+pub trait Optimizer<T> {
+    fn step(&mut self, network: &mut Network<T>, gradients: &Gradients<T>) {
+        self.try_step(network, gradients).or_panic()
+    }
+
+    // fails without touching the network or the optimizer state if the gradients do not fit the network
+    fn try_step(&mut self, network: &mut Network<T>, gradients: &Gradients<T>) -> Result<(), Error>;
+}
+
+// stochastic gradient descent with (optional) momentum
+//     v = momentum * v + g
+//     p = p - learning_rate * v
+pub struct Sgd<T> {
+    learning_rate: T,
+    momentum: T,
+    velocity: Option<Gradients<T>>
+}
+
+impl<T> Sgd<T>
+where
+    T: num::Float + num::Signed + Default + fmt::Debug
+{
+    pub fn new(learning_rate: T, momentum: T) -> Self {
+        Self::try_new(learning_rate, momentum).or_panic()
+    }
+
+    pub fn try_new(learning_rate: T, momentum: T) -> Result<Self, Error> {
+        if learning_rate <= T::zero() {
+            return Err(Error::InvalidParameter("learning rate must be positive"));
+        }
+        if momentum < T::zero() || momentum >= T::one() {
+            return Err(Error::InvalidParameter("momentum must be in [0, 1)"));
+        }
+        Ok(Self {
+            learning_rate,
+            momentum,
+            velocity: None
+        })
+    }
+
+    fn update(parameters: &mut Matrix<T>, velocity: &mut Matrix<T>, gradient: &Matrix<T>, learning_rate: T, momentum: T) {
+        for row in 0..parameters.rows() {
+            for col in 0..parameters.cols() {
+                velocity[row][col] = momentum * velocity[row][col] + gradient[row][col];
+                parameters[row][col] = parameters[row][col] - learning_rate * velocity[row][col];
+            }
+        }
+    }
+}
+
+impl<T> Optimizer<T> for Sgd<T>
+where
+    T: num::Float + num::Signed + Default + fmt::Debug
+{
+    fn try_step(&mut self, network: &mut Network<T>, gradients: &Gradients<T>) -> Result<(), Error> {
+        if !gradients.fits(network) {
+            return Err(Error::GradientShapeMismatch);
+        }
+        // the state is (re)created whenever the shape of the network changes
+        let mut velocity = match self.velocity.take() {
+            Some(velocity) if velocity.has_same_shape(gradients) => velocity,
+            _ => Gradients::zeros(network)
+        };
+        for i in 0..gradients.weights.len() {
+            Self::update(network.get_weights_mut(i), &mut velocity.weights[i], &gradients.weights[i], self.learning_rate, self.momentum);
+        }
+        for i in 0..gradients.biases.len() {
+            Self::update(network.get_biases_mut(i), &mut velocity.biases[i], &gradients.biases[i], self.learning_rate, self.momentum);
+        }
+        self.velocity = Some(velocity);
+        Ok(())
+    }
+}
+
+// adaptive moment estimation (Kingma & Ba)
+//     m = beta_1 * m + (1 - beta_1) * g
+//     v = beta_2 * v + (1 - beta_2) * g^2
+//     p = p - learning_rate * m_hat / (sqrt(v_hat) + epsilon)
+pub struct Adam<T> {
+    learning_rate: T,
+    beta_1: T,
+    beta_2: T,
+    epsilon: T,
+    time_step: i32,
+    first_moment: Option<Gradients<T>>,
+    second_moment: Option<Gradients<T>>
+}
+
+impl<T> Adam<T>
+where
+    T: num::Float + num::Signed + Default + fmt::Debug
+{
+    // default parameters from the paper
+    pub fn new(learning_rate: T) -> Self {
+        Self::with_parameters(learning_rate, T::from(0.9).unwrap(), T::from(0.999).unwrap(), T::from(1e-8).unwrap())
+    }
+
+    pub fn with_parameters(learning_rate: T, beta_1: T, beta_2: T, epsilon: T) -> Self {
+        Self::try_with_parameters(learning_rate, beta_1, beta_2, epsilon).or_panic()
+    }
+
+    pub fn try_with_parameters(learning_rate: T, beta_1: T, beta_2: T, epsilon: T) -> Result<Self, Error> {
+        if learning_rate <= T::zero() {
+            return Err(Error::InvalidParameter("learning rate must be positive"));
+        }
+        if beta_1 < T::zero() || beta_1 >= T::one() || beta_2 < T::zero() || beta_2 >= T::one() {
+            return Err(Error::InvalidParameter("beta parameters must be in [0, 1)"));
+        }
+        if epsilon <= T::zero() {
+            return Err(Error::InvalidParameter("epsilon must be positive"));
+        }
+        Ok(Self {
+            learning_rate,
+            beta_1,
+            beta_2,
+            epsilon,
+            time_step: 0,
+            first_moment: None,
+            second_moment: None
+        })
+    }
+
+    fn update(&self, parameters: &mut Matrix<T>, m: &mut Matrix<T>, v: &mut Matrix<T>, gradient: &Matrix<T>) {
+        let m_correction = T::one() - self.beta_1.powi(self.time_step);
+        let v_correction = T::one() - self.beta_2.powi(self.time_step);
+        for row in 0..parameters.rows() {
+            for col in 0..parameters.cols() {
+                let g = gradient[row][col];
+                m[row][col] = self.beta_1 * m[row][col] + (T::one() - self.beta_1) * g;
+                v[row][col] = self.beta_2 * v[row][col] + (T::one() - self.beta_2) * g * g;
+                let m_hat = m[row][col] / m_correction;
+                let v_hat = v[row][col] / v_correction;
+                parameters[row][col] = parameters[row][col] - self.learning_rate * m_hat / (v_hat.sqrt() + self.epsilon);
+            }
+        }
+    }
+}
+
+impl<T> Optimizer<T> for Adam<T>
+where
+    T: num::Float + num::Signed + Default + fmt::Debug
+{
+    fn try_step(&mut self, network: &mut Network<T>, gradients: &Gradients<T>) -> Result<(), Error> {
+        if !gradients.fits(network) {
+            return Err(Error::GradientShapeMismatch);
+        }
+        let (mut m, mut v) = match (self.first_moment.take(), self.second_moment.take()) {
+            (Some(m), Some(v)) if m.has_same_shape(gradients) => (m, v),
+            _ => {
+                self.time_step = 0;
+                (Gradients::zeros(network), Gradients::zeros(network))
+            }
+        };
+        self.time_step += 1;
+        for i in 0..gradients.weights.len() {
+            self.update(network.get_weights_mut(i), &mut m.weights[i], &mut v.weights[i], &gradients.weights[i]);
+        }
+        for i in 0..gradients.biases.len() {
+            self.update(network.get_biases_mut(i), &mut m.biases[i], &mut v.biases[i], &gradients.biases[i]);
+        }
+        self.first_moment = Some(m);
+        self.second_moment = Some(v);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_test_network() -> Network<f64> {
+        let mut network = Network::new(3);
+        network.resize(0, 2);
+        network.resize(1, 2);
+        network.resize(2, 1);
+        network
+    }
+
+    fn filled_gradients(network: &Network<f64>, value: f64) -> Gradients<f64> {
+        let mut gradients = Gradients::zeros(network);
+        for w in gradients.weights.iter_mut() {
+            w.apply(|_| value);
+        }
+        for b in gradients.biases.iter_mut() {
+            b.apply(|_| value);
+        }
+        gradients
+    }
+
+    #[test]
+    fn test_sgd_step() {
+        let mut network = create_test_network();
+        let gradients = filled_gradients(&network, 1.0);
+        let mut sgd = Sgd::new(0.1, 0.0);
+        sgd.step(&mut network, &gradients);
+        assert!((network.get_weight(0, 0, 0) - (-0.1)).abs() < 1e-12);
+        assert!((network.get_bias(1, 0) - (-0.1)).abs() < 1e-12);
+        sgd.step(&mut network, &gradients);
+        assert!((network.get_weight(0, 0, 0) - (-0.2)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_sgd_momentum() {
+        let mut network = create_test_network();
+        let gradients = filled_gradients(&network, 1.0);
+        let mut sgd = Sgd::new(0.1, 0.5);
+        sgd.step(&mut network, &gradients);
+        assert!((network.get_weight(0, 1, 1) - (-0.1)).abs() < 1e-12);
+        // velocity = 0.5 * 1 + 1 = 1.5
+        sgd.step(&mut network, &gradients);
+        assert!((network.get_weight(0, 1, 1) - (-0.25)).abs() < 1e-12);
+    }
+
+    #[test]
+    #[should_panic(expected = "momentum must be in [0, 1)")]
+    fn test_sgd_invalid_momentum() {
+        let _ = Sgd::<f64>::new(0.1, 1.0);
+    }
+
+    #[test]
+    fn test_try_new() {
+        assert!(Sgd::<f64>::try_new(0.0, 0.5).is_err());
+        assert!(Sgd::<f64>::try_new(0.1, -0.5).is_err());
+        assert!(Sgd::<f64>::try_new(0.1, 0.5).is_ok());
+        assert!(Adam::<f64>::try_with_parameters(0.1, 1.0, 0.999, 1e-8).is_err());
+        assert!(Adam::<f64>::try_with_parameters(-0.1, 0.9, 0.999, 1e-8).is_err());
+        assert!(Adam::<f64>::try_with_parameters(0.1, 0.9, 0.999, 0.0).is_err());
+        assert!(Adam::<f64>::try_with_parameters(0.1, 0.9, 0.999, 1e-8).is_ok());
+    }
+
+    #[test]
+    fn test_try_step_shape_mismatch() {
+        let mut network = create_test_network();
+        let mut other = create_test_network();
+        other.resize(1, 3);
+        let gradients = filled_gradients(&other, 1.0);
+        let mut fewer_layers = filled_gradients(&network, 1.0);
+        fewer_layers.weights.pop();
+        let optimizers: [Box<dyn Optimizer<f64>>; 2] = [Box::new(Sgd::new(0.1, 0.5)), Box::new(Adam::new(0.01))];
+        for mut optimizer in optimizers {
+            assert_eq!(optimizer.try_step(&mut network, &gradients), Err(Error::GradientShapeMismatch));
+            assert_eq!(optimizer.try_step(&mut network, &fewer_layers), Err(Error::GradientShapeMismatch));
+            assert_eq!(network.get_weight(0, 0, 0), 0.0);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Gradient shapes must match the weights and biases of the network")]
+    fn test_step_shape_mismatch() {
+        let mut network = create_test_network();
+        let gradients = filled_gradients(&network, 1.0);
+        network.resize(1, 3);
+        Sgd::new(0.1, 0.0).step(&mut network, &gradients);
+    }
+
+    #[test]
+    fn test_adam_step() {
+        let mut network = create_test_network();
+        let gradients = filled_gradients(&network, 3.0);
+        let mut adam = Adam::new(0.01);
+        // the first bias corrected step has the size of the learning rate regardless of the gradient
+        adam.step(&mut network, &gradients);
+        assert!((network.get_weight(1, 0, 0) - (-0.01)).abs() < 1e-9);
+        assert!((network.get_bias(0, 1) - (-0.01)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_adam_reset_on_reshape() {
+        let mut network = create_test_network();
+        let mut adam = Adam::new(0.01);
+        let gradients = filled_gradients(&network, 1.0);
+        adam.step(&mut network, &gradients);
+        network.resize(1, 3);
+        network.resize(2, 1);
+        let gradients = filled_gradients(&network, 1.0);
+        adam.step(&mut network, &gradients);
+        assert!((network.get_weight(1, 2, 0) - (-0.01)).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,242 @@
+use std::fmt;
+use std::marker::PhantomData;
+use crate::math::matrix::Matrix;
+use crate::network::network::Network;
+use crate::training::loss::Loss;
+use crate::training::optimizer::Optimizer;
+use crate::error::{Error, OrPanic};
+
+/*
+    supervised training of a network on labelled samples
+    every input is a 1xn matrix and every target a 1xo matrix
+*/
+
+pub struct Trainer<T, O> {
+    optimizer: O,
+    loss: Loss,
+    _marker: PhantomData<T>
+}
+
+impl<T, O> Trainer<T, O>
+where
+    T: num::Float + num::Signed + Default + fmt::Debug,
+    O: Optimizer<T>
+{
+    pub fn new(optimizer: O, loss: Loss) -> Self {
+        Self {
+            optimizer,
+            loss,
+            _marker: PhantomData
+        }
+    }
+
+    // one optimizer step with the gradients averaged over the batch, returns the average loss of the batch
+    pub fn train_batch(&mut self, network: &mut Network<T>, inputs: &[Matrix<T>], targets: &[Matrix<T>]) -> T {
+        self.try_train_batch(network, inputs, targets).or_panic()
+    }
+
+    pub fn try_train_batch(&mut self, network: &mut Network<T>, inputs: &[Matrix<T>], targets: &[Matrix<T>]) -> Result<T, Error> {
+        if inputs.len() != targets.len() {
+            return Err(Error::InvalidParameter("number of inputs and targets must match"));
+        }
+        if inputs.is_empty() {
+            return Err(Error::InvalidParameter("cannot train on an empty batch"));
+        }
+        // the whole batch goes through the network at once, the loss already averages over the rows
+        let input = Self::stack(inputs)?;
+        let target = Self::stack(targets)?;
+        let output = network.try_forward_batch(&input)?;
+        let loss = self.loss.try_compute(&output, &target)?;
+        let gradients = network.try_backward(&target, self.loss)?;
+        self.optimizer.try_step(network, &gradients)?;
+        Ok(loss)
+    }
+
+    // stacks 1xn rows into an mxn matrix
+    fn stack(rows: &[Matrix<T>]) -> Result<Matrix<T>, Error> {
+        let mut result = Matrix::try_new(rows.len(), rows[0].cols())?;
+        for (i, row) in rows.iter().enumerate() {
+            if row.rows() != 1 || row.cols() != result.cols() {
+                return Err(Error::InvalidParameter("every sample must be a 1xn matrix of the same size"));
+            }
+            result[i].copy_from_slice(&row[0]);
+        }
+        Ok(result)
+    }
+
+    // runs through the samples in order for the given number of epochs, returns the average loss of the last epoch
+    pub fn train(&mut self, network: &mut Network<T>, inputs: &[Matrix<T>], targets: &[Matrix<T>], batch_size: usize, epochs: usize) -> T {
+        self.try_train(network, inputs, targets, batch_size, epochs).or_panic()
+    }
+
+    pub fn try_train(&mut self, network: &mut Network<T>, inputs: &[Matrix<T>], targets: &[Matrix<T>], batch_size: usize, epochs: usize) -> Result<T, Error> {
+        if batch_size == 0 {
+            return Err(Error::InvalidParameter("batch size cannot be zero"));
+        }
+        if inputs.len() != targets.len() {
+            return Err(Error::InvalidParameter("number of inputs and targets must match"));
+        }
+        if inputs.is_empty() {
+            return Err(Error::InvalidParameter("cannot train on an empty set of samples"));
+        }
+        let mut epoch_loss = T::zero();
+        for _epoch in 0..epochs {
+            epoch_loss = T::zero();
+            for (input_batch, target_batch) in inputs.chunks(batch_size).zip(targets.chunks(batch_size)) {
+                let batch_loss = self.try_train_batch(network, input_batch, target_batch)?;
+                epoch_loss = epoch_loss + batch_loss * T::from(input_batch.len()).unwrap();
+            }
+            epoch_loss = epoch_loss / T::from(inputs.len()).unwrap();
+        }
+        Ok(epoch_loss)
+    }
+
+    pub fn get_loss(&self) -> Loss {
+        self.loss
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::activation::Activation;
+    use crate::network::network_initializer::NetworkInitializer;
+    use crate::training::optimizer::{Sgd, Adam};
+
+    fn row(values: &[f64]) -> Matrix<f64> {
+        let mut m = Matrix::new(1, values.len());
+        for (i, v) in values.iter().enumerate() {
+            m[0][i] = *v;
+        }
+        m
+    }
+
+    fn create_network(sizes: &[usize], seed: u64) -> Network<f64> {
+        let mut network = Network::new(sizes.len());
+        for (i, size) in sizes.iter().enumerate() {
+            network.resize(i, *size);
+        }
+        let mut initializer = NetworkInitializer::new(seed, -1.0, 1.0);
+        initializer.initialize(&mut network);
+        network
+    }
+
+    fn xor_data() -> (Vec<Matrix<f64>>, Vec<Matrix<f64>>) {
+        let inputs = vec![row(&[0.0, 0.0]), row(&[0.0, 1.0]), row(&[1.0, 0.0]), row(&[1.0, 1.0])];
+        let targets = vec![row(&[0.0]), row(&[1.0]), row(&[1.0]), row(&[0.0])];
+        (inputs, targets)
+    }
+
+    // numerical gradient of the loss with respect to a single weight
+    fn numeric_weight_gradient(network: &Network<f64>, input: &Matrix<f64>, target: &Matrix<f64>, loss: Loss, layer: usize, row: usize, col: usize) -> f64 {
+        let h = 1e-6;
+        let mut plus = network.clone();
+        plus.set_weight(layer, row, col, network.get_weight(layer, row, col) + h);
+        let mut minus = network.clone();
+        minus.set_weight(layer, row, col, network.get_weight(layer, row, col) - h);
+        (loss.compute(&plus.forward(input), target) - loss.compute(&minus.forward(input), target)) / (2.0 * h)
+    }
+
+    fn numeric_bias_gradient(network: &Network<f64>, input: &Matrix<f64>, target: &Matrix<f64>, loss: Loss, layer: usize, col: usize) -> f64 {
+        let h = 1e-6;
+        let mut plus = network.clone();
+        plus.set_bias(layer, col, network.get_bias(layer, col) + h);
+        let mut minus = network.clone();
+        minus.set_bias(layer, col, network.get_bias(layer, col) - h);
+        (loss.compute(&plus.forward(input), target) - loss.compute(&minus.forward(input), target)) / (2.0 * h)
+    }
+
+    fn check_gradients(network: &mut Network<f64>, input: &Matrix<f64>, target: &Matrix<f64>, loss: Loss) {
+        network.forward_cached(input);
+        let gradients = network.backward(target, loss);
+        for layer in 0..network.get_num_weight_layers() {
+            let dimensions = network.get_weights_dimension(layer);
+            for row in 0..dimensions.rows {
+                for col in 0..dimensions.cols {
+                    let numeric = numeric_weight_gradient(network, input, target, loss, layer, row, col);
+                    assert!((gradients.weights[layer][row][col] - numeric).abs() < 1e-6, "weight {} {} {}", layer, row, col);
+                }
+            }
+            for col in 0..network.get_biases_dimension(layer).cols {
+                let numeric = numeric_bias_gradient(network, input, target, loss, layer, col);
+                assert!((gradients.biases[layer][0][col] - numeric).abs() < 1e-6, "bias {} {}", layer, col);
+            }
+        }
+    }
+
+    #[test]
+    fn test_backward_mean_squared_error() {
+        let activations = [Activation::Tanh, Activation::Sigmoid, Activation::GELU, Activation::LeakyReLU(0.1), Activation::Identity];
+        for activation in activations.iter() {
+            let mut network = create_network(&[3, 5, 4, 2], 11);
+            network.set_activations(*activation);
+            check_gradients(&mut network, &row(&[0.3, -0.7, 0.9]), &row(&[0.5, -0.2]), Loss::MeanSquaredError);
+        }
+    }
+
+    #[test]
+    fn test_backward_cross_entropy_softmax() {
+        let mut network = create_network(&[3, 6, 4], 5);
+        network.set_activation(0, Activation::Tanh);
+        network.set_activation(1, Activation::Softmax);
+        check_gradients(&mut network, &row(&[0.1, 0.4, -0.8]), &row(&[0.0, 0.0, 1.0, 0.0]), Loss::CrossEntropy);
+    }
+
+    #[test]
+    #[should_panic(expected = "Target size mismatch with output layer")]
+    fn test_backward_invalid_target() {
+        let mut network = create_network(&[2, 3, 1], 1);
+        network.forward_cached(&row(&[0.0, 1.0]));
+        network.backward(&row(&[0.0, 1.0]), Loss::MeanSquaredError);
+    }
+
+    #[test]
+    fn test_train_xor_sgd() {
+        let (inputs, targets) = xor_data();
+        let mut network = create_network(&[2, 8, 1], 3);
+        network.set_activation(0, Activation::Tanh);
+        network.set_activation(1, Activation::Sigmoid);
+        let mut trainer = Trainer::new(Sgd::new(0.5, 0.9), Loss::MeanSquaredError);
+        let initial_loss = trainer.train(&mut network, &inputs, &targets, 4, 1);
+        let final_loss = trainer.train(&mut network, &inputs, &targets, 4, 2000);
+        assert!(final_loss < initial_loss);
+        assert!(final_loss < 0.01, "loss: {}", final_loss);
+    }
+
+    #[test]
+    fn test_train_xor_adam() {
+        let (inputs, targets) = xor_data();
+        let mut network = create_network(&[2, 8, 1], 3);
+        network.set_activation(0, Activation::Tanh);
+        network.set_activation(1, Activation::Sigmoid);
+        let mut trainer = Trainer::new(Adam::new(0.05), Loss::MeanSquaredError);
+        let final_loss = trainer.train(&mut network, &inputs, &targets, 2, 1000);
+        assert!(final_loss < 0.01, "loss: {}", final_loss);
+        for (input, target) in inputs.iter().zip(&targets) {
+            let output = network.forward(input);
+            assert!((output[0][0] - target[0][0]).abs() < 0.2);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "number of inputs and targets must match")]
+    fn test_train_mismatched_samples() {
+        let mut network = create_network(&[2, 3, 1], 1);
+        let mut trainer = Trainer::new(Sgd::new(0.1, 0.0), Loss::MeanSquaredError);
+        trainer.train_batch(&mut network, &[row(&[0.0, 1.0])], &[]);
+    }
+
+    #[test]
+    fn test_try_train() {
+        let mut network = create_network(&[2, 3, 1], 1);
+        let mut trainer = Trainer::new(Sgd::new(0.1, 0.0), Loss::MeanSquaredError);
+        assert!(matches!(trainer.try_train_batch(&mut network, &[], &[]), Err(Error::InvalidParameter(_))));
+        assert!(matches!(trainer.try_train(&mut network, &[row(&[0.0, 1.0])], &[row(&[1.0])], 0, 1), Err(Error::InvalidParameter(_))));
+        // no samples would average the loss over zero rows
+        let before = network.clone();
+        assert!(matches!(trainer.try_train(&mut network, &[], &[], 1, 1), Err(Error::InvalidParameter(_))));
+        assert!(network == before);
+        assert!(matches!(trainer.try_train_batch(&mut network, &[row(&[0.0, 1.0, 2.0])], &[row(&[1.0])]), Err(Error::InputSizeMismatch { .. })));
+        assert!(trainer.try_train_batch(&mut network, &[row(&[0.0, 1.0])], &[row(&[1.0])]).is_ok());
+    }
+}
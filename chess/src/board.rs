@@ -15,6 +15,8 @@
 use crate::pieces::{Pieces, Piece};
 use crate::coordinate::Coordinate;
 use crate::rules::Rules;
+use crate::movement::{Movement, Promotion};
+use crate::error::MoveError;
 
 macro_rules! debug_log {
     ($($arg:tt)*) => {
@@ -25,15 +27,20 @@ macro_rules! debug_log {
     };
 }
 
-#[derive(PartialEq)]
-enum Turn {
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Turn {
     White,
     Black
 }
 
+#[derive(Clone)]
 pub struct Board {
     pieces: [Piece; 64],
-    turn: Turn
+    turn: Turn,
+    // the square a pawn skipped with its double step in the last move
+    en_passant: Option<Coordinate>,
+    // half moves since the last capture or pawn move
+    halfmove_clock: u32
 }
 
 impl Board {
@@ -105,7 +112,9 @@ impl Board {
                 Piece::new(Pieces::BlackKnight, 6, 7),
                 Piece::new(Pieces::BlackRook, 7, 7)
             ],
-            turn: Turn::White
+            turn: Turn::White,
+            en_passant: None,
+            halfmove_clock: 0
         }
     }
 
@@ -113,6 +122,28 @@ impl Board {
         &self.pieces
     }
 
+    pub fn get_turn (&self) -> Turn {
+        self.turn
+    }
+
+    pub fn get_en_passant (&self) -> Option<Coordinate> {
+        self.en_passant
+    }
+
+    // the piece on to, or the pawn next to it if a pawn takes en passant
+    pub fn get_taken_piece (&self, from: &Coordinate, to: &Coordinate) -> Option<Piece> {
+        let to_piece = self.pieces[to.as_index()];
+        if !to_piece.is_empty() { return Some(to_piece); }
+        if !Rules::is_en_passant(self, from, to) { return None; }
+        let (to_x, _) = to.as_x_y();
+        let (_, from_y) = from.as_x_y();
+        Some(self.pieces[Coordinate::convert_coordinates_to_index(to_x, from_y)])
+    }
+
+    pub fn get_halfmove_clock (&self) -> u32 {
+        self.halfmove_clock
+    }
+
     pub fn draw (&self) {
         for i in 0..8 {
             print!("{} ", 8 - i);
@@ -124,9 +155,9 @@ impl Board {
                     print!("\x1b[100;30m{} \x1b[0m",  self.pieces[i * 8 + e].symbol());
                 }
             }
-            print!("\n");
+            println!();
         }
-        print!("  a b c d e f g h\n\n");
+        println!("  a b c d e f g h\n");
     }
 
     pub fn teleport (&mut self, from_col: char, from_row: u8, to_col: char, to_row: u8) {
@@ -137,10 +168,25 @@ impl Board {
         self.pieces[to_index].moved = true;
         self.pieces[from_index].piece_type = Pieces::Empty;
         self.pieces[from_index].moved = false;
-        // do not flip turn
+        // do not flip turn, but the setup starts over without en passant or move clock
+        self.reset_clocks();
     }
 
-    pub fn make_a_move (&mut self, from_col: char, from_row: u8, to_col: char, to_row: u8) -> bool {
+    pub fn remove (&mut self, col: char, row: u8) {
+        let (_, _, index) = Coordinate::convert_coordinates(col, row);
+        self.pieces[index].piece_type = Pieces::Empty;
+        self.pieces[index].moved = false;
+        self.reset_clocks();
+    }
+
+    // plays the move if it is legal for the side to move and returns it, pawns reaching the last row become queens
+    pub fn make_a_move (&mut self, from_col: char, from_row: u8, to_col: char, to_row: u8) -> Result<Movement, MoveError> {
+        self.make_a_move_with_promotion(from_col, from_row, to_col, to_row, None)
+    }
+
+    // like make_a_move, a pawn reaching the last row turns into the chosen piece (a queen if there is no choice)
+    pub fn make_a_move_with_promotion (&mut self, from_col: char, from_row: u8, to_col: char, to_row: u8,
+                                       promotion: Option<Promotion>) -> Result<Movement, MoveError> {
         let from_coordinate = Coordinate::from_row_col(from_col, from_row);
         let to_coordinate = Coordinate::from_row_col(to_col, to_row);
         let from_index = from_coordinate.as_index();
@@ -148,36 +194,130 @@ impl Board {
         // moving a piece to the same place is invalid
         if from_index == to_index {
             debug_log!("cannot move to the same place");
-            return false;
+            return Err(MoveError::SamePlace);
         }
         let from_piece = &self.pieces[from_index];
-        let to_piece = &self.pieces[to_index];
         // an empty piece cannot be moved
-        if from_piece.is_empty() { return false; }
+        if from_piece.is_empty() { return Err(MoveError::EmptySquare); }
         // consider the turn, e.g. white piece cannot be moved in a black turn
         if (self.turn == Turn::White) && from_piece.is_black() {
             debug_log!("cannot move black piece in white turn");
-            return false;
+            return Err(MoveError::WrongTurn);
         }
         if (self.turn == Turn::Black) && from_piece.is_white() {
             debug_log!("cannot move white piece in black turn");
-            return false;
+            return Err(MoveError::WrongTurn);
         }
         let (from_x, from_y) = from_coordinate.as_x_y();
-        let (to_x, to_y) = from_coordinate.as_x_y();
+        let (to_x, to_y) = to_coordinate.as_x_y();
         debug_log!("moving from [{};{}] to [{};{}]", from_x, from_y, to_x, to_y);
-        // check if the basic movement is valid
-        if !Rules::is_basic_movement_valid(self, &from_coordinate, &to_coordinate) { return false; }
-        if !Rules::is_castling_valid(self, &from_coordinate, &to_coordinate) { return false; }
+        // either a basic movement, which may take a piece, or castling
+        let movement = if Rules::is_basic_movement_valid(self, &from_coordinate, &to_coordinate) {
+            Movement::new(from_index, to_index, self.get_taken_piece(&from_coordinate, &to_coordinate))
+        }
+        else if Rules::is_castling_valid(self, &from_coordinate, &to_coordinate) {
+            Movement::new(from_index, to_index, None)
+        }
+        else {
+            return Err(MoveError::InvalidMovement);
+        };
+        // only pawns reaching the last row may (and have to) be promoted
+        let movement = if Rules::is_promotion(self, &from_coordinate, &to_coordinate) {
+            movement.with_promotion(promotion.unwrap_or(Promotion::Queen))
+        }
+        else if promotion.is_some() {
+            debug_log!("cannot promote this piece here");
+            return Err(MoveError::InvalidPromotion);
+        }
+        else {
+            movement
+        };
+        // simulate the move, the own king must not be attacked afterwards
+        if Rules::leaves_king_in_check(self, &movement) {
+            if Rules::is_king_under_attack(self, from_piece.is_black()) {
+                debug_log!("king stays in check");
+                return Err(MoveError::KingStaysInCheck);
+            }
+            debug_log!("king would be in check");
+            return Err(MoveError::KingInCheck);
+        }
+
+        // everything checks out -> make the move, this also flips the turn
+        self.apply_movement(&movement);
+        Ok(movement)
+    }
+
+    // plays the movement without checking it and flips the turn,
+    // a movement between a king and its own rook is castling
+    pub fn apply_movement (&mut self, movement: &Movement) {
+        let from_index = movement.from.as_index();
+        let to_index = movement.to.as_index();
+        let from_piece = self.pieces[from_index];
+        let to_piece = self.pieces[to_index];
+        let (from_x, from_y) = movement.from.as_x_y();
+        let (to_x, to_y) = movement.to.as_x_y();
+        let mut en_passant = None;
+        if Rules::is_king_and_rook(&from_piece, &to_piece) {
+            self.castle(from_index, to_index);
+            self.halfmove_clock += 1;
+        }
+        else {
+            let pawn = from_piece.piece_type == Pieces::WhitePawn || from_piece.piece_type == Pieces::BlackPawn;
+            // a pawn stepping diagonally onto an empty square takes en passant
+            if pawn && from_x != to_x && to_piece.is_empty() {
+                let taken_index = Coordinate::convert_coordinates_to_index(to_x, from_y);
+                self.pieces[taken_index].piece_type = Pieces::Empty;
+                self.pieces[taken_index].moved = false;
+            }
+            if pawn && from_y.abs_diff(to_y) == 2 {
+                en_passant = Some(Coordinate::from_x_y(from_x, (from_y + to_y) / 2));
+            }
+            self.move_piece(from_index, to_index);
+            // a pawn on the last row gets promoted, a queen unless the movement says otherwise
+            if pawn && (to_y == 0 || to_y == 7) {
+                let promotion = movement.promotion.unwrap_or(Promotion::Queen);
+                self.pieces[to_index].piece_type = promotion.piece_type(from_piece.is_black());
+            }
+            if pawn || !to_piece.is_empty() { self.halfmove_clock = 0; }
+            else { self.halfmove_clock += 1; }
+        }
+        self.en_passant = en_passant;
+        self.flip_turn();
+    }
+
+    fn reset_clocks (&mut self) {
+        self.en_passant = None;
+        self.halfmove_clock = 0;
+    }
 
-        // everything checks out -> make the move
+    fn move_piece (&mut self, from_index: usize, to_index: usize) {
         self.pieces[to_index].piece_type = self.pieces[from_index].piece_type;
         self.pieces[to_index].moved = true;
         self.pieces[from_index].piece_type = Pieces::Empty;
         self.pieces[from_index].moved = false;
-        // flip the turn -> assume correct usage (move was checked with 'can_move_to' beforehand
-        self.flip_turn();
-        return true;
+    }
+
+    // the king goes two squares towards the rook, the rook lands on the square the king crossed
+    fn castle (&mut self, from_index: usize, to_index: usize) {
+        let (king_index, rook_index) = match self.pieces[from_index].piece_type {
+            Pieces::WhiteKing | Pieces::BlackKing => (from_index, to_index),
+            _ => (to_index, from_index)
+        };
+        let (king_x, y) = Coordinate::convert_index_to_coordinates(king_index);
+        let (rook_x, _) = Coordinate::convert_index_to_coordinates(rook_index);
+        let (king_to_x, rook_to_x) = if rook_x < king_x { (2, 3) } else { (6, 5) };
+        let king_type = self.pieces[king_index].piece_type;
+        let rook_type = self.pieces[rook_index].piece_type;
+        for index in [king_index, rook_index] {
+            self.pieces[index].piece_type = Pieces::Empty;
+            self.pieces[index].moved = false;
+        }
+        let king_to = Coordinate::convert_coordinates_to_index(king_to_x, y);
+        let rook_to = Coordinate::convert_coordinates_to_index(rook_to_x, y);
+        self.pieces[king_to].piece_type = king_type;
+        self.pieces[king_to].moved = true;
+        self.pieces[rook_to].piece_type = rook_type;
+        self.pieces[rook_to].moved = true;
     }
 
     fn flip_turn (&mut self) {
@@ -185,4 +325,182 @@ impl Board {
         else { self.turn = Turn::White; }
     }
 
-}
\ No newline at end of file
+}
+
+impl Default for Board {
+    fn default () -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn piece_at (board: &Board, col: char, row: u8) -> Pieces {
+        board.get_current_setup()[Coordinate::from_row_col(col, row).as_index()].piece_type
+    }
+
+    #[test]
+    fn make_a_move_test () {
+        let mut board = Board::new();
+        let movement = board.make_a_move('e', 7, 'e', 5).unwrap();
+        assert_eq!(movement.from, Coordinate::from_row_col('e', 7));
+        assert_eq!(movement.to, Coordinate::from_row_col('e', 5));
+        assert!(movement.taken.is_none());
+        assert_eq!(board.get_turn(), Turn::Black);
+        assert_eq!(board.make_a_move('d', 7, 'd', 5), Err(MoveError::WrongTurn));
+        assert_eq!(board.make_a_move('d', 4, 'd', 5), Err(MoveError::EmptySquare));
+        assert_eq!(board.make_a_move('d', 2, 'd', 2), Err(MoveError::SamePlace));
+        assert_eq!(board.make_a_move('d', 2, 'd', 5), Err(MoveError::InvalidMovement));
+        assert!(board.make_a_move('d', 2, 'd', 4).is_ok());
+        let movement = board.make_a_move('e', 5, 'd', 4).unwrap();
+        assert_eq!(movement.taken.unwrap().piece_type, Pieces::BlackPawn);
+        assert_eq!(piece_at(&board, 'd', 4), Pieces::WhitePawn);
+    }
+
+    #[test]
+    fn pinned_piece_test () {
+        let mut board = Board::new();
+        //8 ♖ ♘ ♗ ♕ ♔ ♗   ♖
+        //7 ♙ ♙ ♙ ♙ ♘ ♙ ♙ ♙
+        //6
+        //5         ♛
+        //4
+        //3
+        //2 ♟ ♟ ♟ ♟ ♟ ♟ ♟ ♟
+        //1 ♜ ♞ ♝   ♚ ♝ ♞ ♜
+        //  a b c d e f g h
+        board.teleport('g', 8, 'e', 7);
+        board.teleport('d', 1, 'e', 5);
+        assert_eq!(board.make_a_move('e', 7, 'c', 6), Err(MoveError::KingInCheck));
+        // nothing changed
+        assert_eq!(piece_at(&board, 'e', 7), Pieces::WhiteKnight);
+        assert!(board.get_current_setup()[Coordinate::from_row_col('c', 6).as_index()].is_empty());
+        assert_eq!(board.get_turn(), Turn::White);
+    }
+
+    #[test]
+    fn walking_into_check_test () {
+        let mut board = Board::new();
+        //8 ♖ ♘ ♗ ♕   ♗ ♘ ♖
+        //7 ♙ ♙ ♙ ♙ ♙ ♙ ♙ ♙
+        //6         ♔
+        //5
+        //4       ♟
+        //3
+        //2 ♟ ♟ ♟   ♟ ♟ ♟ ♟
+        //1 ♜ ♞ ♝ ♛ ♚ ♝ ♞ ♜
+        //  a b c d e f g h
+        board.teleport('e', 8, 'e', 6);
+        board.teleport('d', 2, 'd', 4);
+        assert_eq!(board.make_a_move('e', 6, 'e', 5), Err(MoveError::KingInCheck));
+        assert!(board.make_a_move('e', 6, 'd', 6).is_ok());
+    }
+
+    #[test]
+    fn ignoring_check_test () {
+        let mut board = Board::new();
+        //8 ♖ ♘ ♗ ♕ ♔ ♗ ♘ ♖
+        //7 ♙ ♙ ♙ ♙ ♙ ♙ ♙ ♙
+        //6       ♞
+        //5
+        //4
+        //3
+        //2 ♟ ♟ ♟ ♟ ♟ ♟ ♟ ♟
+        //1 ♜ ♞ ♝ ♛ ♚ ♝   ♜
+        //  a b c d e f g h
+        board.teleport('g', 1, 'd', 6);
+        assert_eq!(board.make_a_move('a', 7, 'a', 6), Err(MoveError::KingStaysInCheck));
+        let movement = board.make_a_move('c', 7, 'd', 6).unwrap();
+        assert_eq!(movement.taken.unwrap().piece_type, Pieces::BlackKnight);
+    }
+
+    #[test]
+    fn castling_move_test () {
+        let mut board = Board::new();
+        //8 ♖ ♘ ♗ ♕ ♔     ♖
+        //7 ♙ ♙ ♙ ♙ ♙ ♙ ♙ ♙
+        //6           ♗ ♘
+        //5
+        //4
+        //3
+        //2 ♟ ♟ ♟ ♟ ♟ ♟ ♟ ♟
+        //1 ♜ ♞ ♝ ♛ ♚ ♝ ♞ ♜
+        //  a b c d e f g h
+        board.teleport('f', 8, 'f', 6);
+        board.teleport('g', 8, 'g', 6);
+        assert!(board.make_a_move('h', 8, 'e', 8).is_ok());
+        assert_eq!(piece_at(&board, 'g', 8), Pieces::WhiteKing);
+        assert_eq!(piece_at(&board, 'f', 8), Pieces::WhiteRook);
+        assert_eq!(piece_at(&board, 'e', 8), Pieces::Empty);
+        assert_eq!(piece_at(&board, 'h', 8), Pieces::Empty);
+    }
+
+    #[test]
+    fn en_passant_test () {
+        let mut board = Board::new();
+        board.make_a_move('e', 7, 'e', 5).unwrap();
+        board.make_a_move('a', 2, 'a', 3).unwrap();
+        board.make_a_move('e', 5, 'e', 4).unwrap();
+        assert_eq!(board.get_en_passant(), None);
+        board.make_a_move('d', 2, 'd', 4).unwrap();
+        assert_eq!(board.get_en_passant(), Some(Coordinate::from_row_col('d', 3)));
+        //8 ♖ ♘ ♗ ♕ ♔ ♗ ♘ ♖
+        //7 ♙ ♙ ♙ ♙   ♙ ♙ ♙
+        //6
+        //5
+        //4       ♟ ♙
+        //3 ♟
+        //2   ♟ ♟   ♟ ♟ ♟ ♟
+        //1 ♜ ♞ ♝ ♛ ♚ ♝ ♞ ♜
+        //  a b c d e f g h
+        let movement = board.make_a_move('e', 4, 'd', 3).unwrap();
+        assert_eq!(movement.taken.unwrap().piece_type, Pieces::BlackPawn);
+        assert_eq!(piece_at(&board, 'd', 3), Pieces::WhitePawn);
+        assert_eq!(piece_at(&board, 'd', 4), Pieces::Empty);
+        assert_eq!(piece_at(&board, 'e', 4), Pieces::Empty);
+        assert_eq!(board.get_en_passant(), None);
+        assert_eq!(board.get_halfmove_clock(), 0);
+    }
+
+    #[test]
+    fn en_passant_only_right_away_test () {
+        let mut board = Board::new();
+        board.make_a_move('e', 7, 'e', 5).unwrap();
+        board.make_a_move('a', 2, 'a', 3).unwrap();
+        board.make_a_move('e', 5, 'e', 4).unwrap();
+        board.make_a_move('d', 2, 'd', 4).unwrap();
+        board.make_a_move('h', 7, 'h', 6).unwrap();
+        board.make_a_move('h', 2, 'h', 3).unwrap();
+        assert_eq!(board.make_a_move('e', 4, 'd', 3), Err(MoveError::InvalidMovement));
+    }
+
+    #[test]
+    fn promotion_test () {
+        let mut board = Board::new();
+        //8 ♖ ♘ ♗ ♕ ♔ ♗ ♘ ♖
+        //7 ♙ ♙ ♙   ♟ ♙ ♙ ♙
+        //6
+        //5
+        //4
+        //3
+        //2 ♟ ♟ ♟ ♟ ♙ ♟ ♟ ♟
+        //1 ♜ ♞ ♝ ♛ ♚ ♝ ♞ ♜
+        //  a b c d e f g h
+        board.remove('e', 7);
+        board.teleport('e', 2, 'e', 7);
+        board.teleport('d', 7, 'e', 2);
+        // white pawns promote on the first row, black pawns on the eighth
+        assert_eq!(board.make_a_move_with_promotion('b', 8, 'c', 6, Some(Promotion::Rook)), Err(MoveError::InvalidPromotion));
+        let movement = board.make_a_move_with_promotion('e', 2, 'd', 1, Some(Promotion::Knight)).unwrap();
+        assert_eq!(movement.promotion, Some(Promotion::Knight));
+        assert_eq!(movement.taken.unwrap().piece_type, Pieces::BlackQueen);
+        assert_eq!(piece_at(&board, 'd', 1), Pieces::WhiteKnight);
+        // without a choice the pawn becomes a queen
+        let movement = board.make_a_move('e', 7, 'f', 8).unwrap();
+        assert_eq!(movement.promotion, Some(Promotion::Queen));
+        assert_eq!(piece_at(&board, 'f', 8), Pieces::BlackQueen);
+        assert_eq!(piece_at(&board, 'e', 7), Pieces::Empty);
+    }
+}
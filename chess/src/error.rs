@@ -0,0 +1,32 @@
+use std::fmt;
+
+/*
+    reasons why the board refuses a move
+*/
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MoveError {
+    SamePlace,
+    EmptySquare,
+    WrongTurn,
+    InvalidMovement,
+    KingInCheck,
+    KingStaysInCheck,
+    InvalidPromotion
+}
+
+impl fmt::Display for MoveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MoveError::SamePlace => write!(f, "cannot move to the same place"),
+            MoveError::EmptySquare => write!(f, "there is no piece to move"),
+            MoveError::WrongTurn => write!(f, "it is the other side's turn"),
+            MoveError::InvalidMovement => write!(f, "the piece cannot move like that"),
+            MoveError::KingInCheck => write!(f, "the move would put your own king in check"),
+            MoveError::KingStaysInCheck => write!(f, "your king is in check and stays in check"),
+            MoveError::InvalidPromotion => write!(f, "only a pawn reaching the last row can be promoted")
+        }
+    }
+}
+
+impl std::error::Error for MoveError { }
@@ -0,0 +1,143 @@
+use crate::board::{Board, Turn};
+use crate::pieces::Pieces;
+use crate::coordinate::Coordinate;
+use crate::rules::Rules;
+use crate::movement::{Movement, Promotion};
+use crate::error::MoveError;
+
+/*
+    a game of chess: the board and the positions the repetition rule compares
+
+    the board is copied for every simulated move, so it does not carry the record itself
+    captures, pawn moves and lost castling rights cannot be undone, no position before them
+    can occur again, so the record only goes back to the last of these moves
+*/
+
+// what makes two positions the same for the repetition rule
+#[derive(Clone, PartialEq)]
+struct Position {
+    pieces: [Pieces; 64],
+    turn: Turn,
+    // kings and rooks which have not moved yet, they decide about castling
+    unmoved: [bool; 64],
+    // only set if a pawn can actually take en passant
+    en_passant: Option<Coordinate>
+}
+
+impl Position {
+    fn of (board: &Board) -> Self {
+        let mut position = Self {
+            pieces: [Pieces::Empty; 64],
+            turn: board.get_turn(),
+            unmoved: [false; 64],
+            en_passant: board.get_en_passant().filter(|target| {
+                (0..64).any(|index| Rules::is_en_passant(board, &Coordinate::from_index(index), target))
+            })
+        };
+        for (index, piece) in board.get_current_setup().iter().enumerate() {
+            position.pieces[index] = piece.piece_type;
+            position.unmoved[index] = !piece.moved && matches!(piece.piece_type,
+                Pieces::WhiteKing | Pieces::WhiteRook | Pieces::BlackKing | Pieces::BlackRook);
+        }
+        position
+    }
+}
+
+pub struct Game {
+    board: Board,
+    // the positions since the last irreversible move, the current one last
+    positions: Vec<Position>
+}
+
+impl Game {
+    pub fn new () -> Self {
+        Self::from_board(Board::new())
+    }
+
+    // starts the record from the given setup
+    pub fn from_board (board: Board) -> Self {
+        let positions = vec![Position::of(&board)];
+        Self { board, positions }
+    }
+
+    pub fn get_board (&self) -> &Board {
+        &self.board
+    }
+
+    // how often the current position occurred since the last irreversible move, including now
+    pub fn count_repetitions (&self) -> usize {
+        let current = self.positions.last().expect("no position recorded");
+        self.positions.iter().filter(|position| *position == current).count()
+    }
+
+    pub fn make_a_move (&mut self, from_col: char, from_row: u8, to_col: char, to_row: u8) -> Result<Movement, MoveError> {
+        self.make_a_move_with_promotion(from_col, from_row, to_col, to_row, None)
+    }
+
+    // plays the move on the board (see Board::make_a_move_with_promotion) and records the new position
+    pub fn make_a_move_with_promotion (&mut self, from_col: char, from_row: u8, to_col: char, to_row: u8,
+                                       promotion: Option<Promotion>) -> Result<Movement, MoveError> {
+        let movement = self.board.make_a_move_with_promotion(from_col, from_row, to_col, to_row, promotion)?;
+        let position = Position::of(&self.board);
+        let last = self.positions.last().expect("no position recorded");
+        if self.board.get_halfmove_clock() == 0 || position.unmoved != last.unmoved {
+            self.positions.clear();
+        }
+        self.positions.push(position);
+        Ok(movement)
+    }
+
+    // teleport and remove change the setup by hand, the record starts over
+    pub fn teleport (&mut self, from_col: char, from_row: u8, to_col: char, to_row: u8) {
+        self.board.teleport(from_col, from_row, to_col, to_row);
+        self.restart_record();
+    }
+
+    pub fn remove (&mut self, col: char, row: u8) {
+        self.board.remove(col, row);
+        self.restart_record();
+    }
+
+    fn restart_record (&mut self) {
+        self.positions.clear();
+        self.positions.push(Position::of(&self.board));
+    }
+}
+
+impl Default for Game {
+    fn default () -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn record_test () {
+        let mut game = Game::new();
+        assert_eq!(game.count_repetitions(), 1);
+        game.make_a_move('b', 8, 'c', 6).unwrap();
+        game.make_a_move('b', 1, 'c', 3).unwrap();
+        game.make_a_move('c', 6, 'b', 8).unwrap();
+        game.make_a_move('c', 3, 'b', 1).unwrap();
+        assert_eq!(game.positions.len(), 5);
+        assert_eq!(game.count_repetitions(), 2);
+        // a pawn move cannot be undone, the positions before it are dropped
+        game.make_a_move('e', 7, 'e', 5).unwrap();
+        assert_eq!(game.positions.len(), 1);
+        assert_eq!(game.count_repetitions(), 1);
+        // moving the king loses the castling rights, which cannot be undone either
+        game.make_a_move('e', 2, 'e', 4).unwrap();
+        game.make_a_move('b', 8, 'c', 6).unwrap();
+        assert_eq!(game.positions.len(), 2);
+        game.make_a_move('e', 1, 'e', 2).unwrap();
+        assert_eq!(game.positions.len(), 1);
+        // an invalid move records nothing
+        assert!(game.make_a_move('e', 7, 'e', 5).is_err());
+        assert_eq!(game.positions.len(), 1);
+        game.teleport('c', 6, 'c', 5);
+        assert_eq!(game.positions.len(), 1);
+    }
+}
@@ -0,0 +1,34 @@
+use std::fmt;
+use crate::board::Turn;
+
+/*
+    the ways a game can end
+*/
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GameResult {
+    Checkmate { winner: Turn },
+    Stalemate,
+    ThreefoldRepetition,
+    FiftyMoveRule,
+    InsufficientMaterial
+}
+
+impl GameResult {
+    pub fn is_draw (&self) -> bool {
+        !matches!(self, GameResult::Checkmate { .. })
+    }
+}
+
+impl fmt::Display for GameResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GameResult::Checkmate { winner: Turn::White } => write!(f, "checkmate, white wins"),
+            GameResult::Checkmate { winner: Turn::Black } => write!(f, "checkmate, black wins"),
+            GameResult::Stalemate => write!(f, "stalemate, the game is drawn"),
+            GameResult::ThreefoldRepetition => write!(f, "the same position occurred three times, the game is drawn"),
+            GameResult::FiftyMoveRule => write!(f, "fifty moves without a capture or pawn move, the game is drawn"),
+            GameResult::InsufficientMaterial => write!(f, "neither side can checkmate anymore, the game is drawn")
+        }
+    }
+}
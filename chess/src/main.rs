@@ -4,17 +4,21 @@ pub mod coordinate;
 pub mod movement;
 pub mod history;
 pub mod rules;
+pub mod move_generator;
+pub mod error;
+pub mod game_result;
+pub mod game;
 
 use std::io::{self, Write};
 use regex::Regex;
 
 fn main() {
-    let mut chessboard = board::Board::new();
-    chessboard.draw();
+    let mut game = game::Game::new();
+    game.get_board().draw();
 
-    let re = Regex::new(r"^([a-h])\s?([1-8])\s+to\s+([a-h])\s?([1-8])$").unwrap();
+    let re = Regex::new(r"^([a-h])\s?([1-8])\s+to\s+([a-h])\s?([1-8])(?:\s*=\s*(\S))?$").unwrap();
     loop {
-        print!("enter move (e.g. 'a 2 to b 3'): ");
+        print!("enter move (e.g. 'a 2 to b 3' or 'e 7 to e 8 =N'): ");
         io::stdout().flush().unwrap(); // flush before input
         let mut input = String::new();
         io::stdin().read_line(&mut input).unwrap();
@@ -24,13 +28,28 @@ fn main() {
             let from_row: u8 = caps[2].parse().unwrap();
             let to_col = caps[3].chars().next().unwrap();
             let to_row: u8 = caps[4].parse().unwrap();
+            // promotion is optional and defaults to a queen
+            let promotion = match caps.get(5) {
+                Some(letter) => match movement::Promotion::from_char(letter.as_str().chars().next().unwrap()) {
+                    Some(promotion) => Some(promotion),
+                    None => {
+                        println!("invalid promotion, choose one of Q, R, B or N");
+                        continue;
+                    }
+                },
+                None => None
+            };
 
             //println!("parsed move: from {}{} to {}{}", from_col, from_row, to_col, to_row);
-            if !chessboard.make_a_move(from_col, from_row, to_col, to_row) {
-                println!("invalid move");
+            if let Err(error) = game.make_a_move_with_promotion(from_col, from_row, to_col, to_row, promotion) {
+                println!("invalid move: {}", error);
                 continue;
             }
-            chessboard.draw();
+            game.get_board().draw();
+            if let Some(result) = rules::Rules::get_game_result(&game) {
+                println!("{}", result);
+                break;
+            }
         }
         else {
             println!("invalid input");
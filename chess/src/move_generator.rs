@@ -0,0 +1,347 @@
+/*
+           x
+   +-------->
+   |   (0)
+   |  8 ♖ ♘ ♗ ♕ ♔ ♗ ♘ ♖
+   |  7 ♙ ♙ ♙ ♙ ♙ ♙ ♙ ♙
+   |  6 . . . . . . . .
+   |  5 . . . . . . . .
+ y V  4 . . . . . . . .
+      3 . . . . . . . .
+      2 ♟ ♟ ♟ ♟ ♟ ♟ ♟ ♟
+      1 ♜ ♞ ♝ ♛ ♚ ♝ ♞ ♜ (63)
+        a b c d e f g h
+
+*/
+
+use crate::pieces::Pieces;
+use crate::coordinate::Coordinate;
+use crate::board::{Board, Turn};
+use crate::movement::{Movement, Promotion};
+use crate::rules::Rules;
+
+const KNIGHT_JUMPS: [(i8, i8); 8] = [(1, 2), (2, 1), (2, -1), (1, -2), (-1, -2), (-2, -1), (-2, 1), (-1, 2)];
+const KING_STEPS: [(i8, i8); 8] = [(1, 0), (1, 1), (0, 1), (-1, 1), (-1, 0), (-1, -1), (0, -1), (1, -1)];
+const STRAIGHT_DIRECTIONS: [(i8, i8); 4] = [(1, 0), (0, 1), (-1, 0), (0, -1)];
+const DIAGONAL_DIRECTIONS: [(i8, i8); 4] = [(1, 1), (-1, 1), (-1, -1), (1, -1)];
+
+pub struct MoveGenerator { }
+
+impl MoveGenerator {
+
+    // every move the side to move can make without leaving its own king in check
+    pub fn legal_moves (board: &Board) -> Vec<Movement> {
+        let mut moves = MoveGenerator::pseudo_legal_moves(board);
+        moves.retain(|movement| !Rules::leaves_king_in_check(board, movement));
+        moves
+    }
+
+    // the legal moves of the piece on from, empty if it does not belong to the side to move
+    pub fn legal_moves_from (board: &Board, from: &Coordinate) -> Vec<Movement> {
+        let mut moves = Vec::new();
+        MoveGenerator::add_piece_moves(board, from, &mut moves);
+        moves.retain(|movement| !Rules::leaves_king_in_check(board, movement));
+        moves
+    }
+
+    // every move following the movement rules of the pieces, the own king may still be in check afterwards
+    pub fn pseudo_legal_moves (board: &Board) -> Vec<Movement> {
+        let mut moves = Vec::new();
+        for index in 0..64 {
+            MoveGenerator::add_piece_moves(board, &Coordinate::from_index(index), &mut moves);
+        }
+        moves
+    }
+
+    fn add_piece_moves (board: &Board, from: &Coordinate, moves: &mut Vec<Movement>) {
+        let setup = board.get_current_setup();
+        let piece = &setup[from.as_index()];
+        let own = match board.get_turn() {
+            Turn::White => piece.is_white(),
+            Turn::Black => piece.is_black()
+        };
+        if !own { return; }
+        let (x, y) = from.as_x_y();
+        let targets = match piece.piece_type {
+            // white pawns walk down the board, black pawns up
+            Pieces::WhitePawn => MoveGenerator::pawn_targets(x, y, 1),
+            Pieces::BlackPawn => MoveGenerator::pawn_targets(x, y, -1),
+            Pieces::WhiteKnight | Pieces::BlackKnight => MoveGenerator::step_targets(x, y, &KNIGHT_JUMPS),
+            Pieces::WhiteBishop | Pieces::BlackBishop => MoveGenerator::ray_targets(board, x, y, &DIAGONAL_DIRECTIONS),
+            Pieces::WhiteRook | Pieces::BlackRook => MoveGenerator::ray_targets(board, x, y, &STRAIGHT_DIRECTIONS),
+            Pieces::WhiteQueen | Pieces::BlackQueen => {
+                let mut targets = MoveGenerator::ray_targets(board, x, y, &STRAIGHT_DIRECTIONS);
+                targets.extend(MoveGenerator::ray_targets(board, x, y, &DIAGONAL_DIRECTIONS));
+                targets
+            },
+            Pieces::WhiteKing | Pieces::BlackKing => MoveGenerator::step_targets(x, y, &KING_STEPS),
+            Pieces::Empty => Vec::new()
+        };
+        // the rules have the last word, the targets only save trying every square
+        for to in targets {
+            if Rules::is_basic_movement_valid(board, from, &to) {
+                let movement = Movement::new(from.as_index(), to.as_index(), board.get_taken_piece(from, &to));
+                // one movement for every piece a pawn can turn into
+                if Rules::is_promotion(board, from, &to) {
+                    moves.extend(Promotion::ALL.iter().map(|promotion| movement.with_promotion(*promotion)));
+                }
+                else {
+                    moves.push(movement);
+                }
+            }
+        }
+        // castling is a movement from the king onto one of its rooks
+        if piece.piece_type == Pieces::WhiteKing || piece.piece_type == Pieces::BlackKing {
+            for rook_x in [0, 7] {
+                let rook = Coordinate::from_x_y(rook_x, y);
+                if Rules::is_castling_valid(board, from, &rook) {
+                    moves.push(Movement::new(from.as_index(), rook.as_index(), None));
+                }
+            }
+        }
+    }
+
+    fn offset (x: u8, y: u8, dx: i8, dy: i8) -> Option<Coordinate> {
+        let x = x as i8 + dx;
+        let y = y as i8 + dy;
+        if !(0..8).contains(&x) || !(0..8).contains(&y) { return None; }
+        Some(Coordinate::from_x_y(x as u8, y as u8))
+    }
+
+    fn pawn_targets (x: u8, y: u8, dy: i8) -> Vec<Coordinate> {
+        [(0, dy), (0, 2 * dy), (-1, dy), (1, dy)].iter()
+            .filter_map(|(dx, dy)| MoveGenerator::offset(x, y, *dx, *dy))
+            .collect()
+    }
+
+    fn step_targets (x: u8, y: u8, steps: &[(i8, i8)]) -> Vec<Coordinate> {
+        steps.iter()
+            .filter_map(|(dx, dy)| MoveGenerator::offset(x, y, *dx, *dy))
+            .collect()
+    }
+
+    // every square along the directions up to and including the first occupied one
+    fn ray_targets (board: &Board, x: u8, y: u8, directions: &[(i8, i8)]) -> Vec<Coordinate> {
+        let setup = board.get_current_setup();
+        let mut targets = Vec::new();
+        for (dx, dy) in directions {
+            let (mut current_x, mut current_y) = (x, y);
+            while let Some(to) = MoveGenerator::offset(current_x, current_y, *dx, *dy) {
+                targets.push(to);
+                if !setup[to.as_index()].is_empty() { break; }
+                (current_x, current_y) = to.as_x_y();
+            }
+        }
+        targets
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn perft (board: &Board, depth: u32) -> usize {
+        if depth == 0 { return 1; }
+        MoveGenerator::legal_moves(board).iter()
+            .map(|movement| {
+                let mut next = board.clone();
+                next.apply_movement(movement);
+                perft(&next, depth - 1)
+            })
+            .sum()
+    }
+
+    fn contains_move (moves: &[Movement], from: Coordinate, to: Coordinate) -> bool {
+        moves.iter().any(|movement| movement.from == from && movement.to == to)
+    }
+
+    #[test]
+    fn initial_position_test () {
+        let mut board = Board::new();
+        let moves = MoveGenerator::legal_moves(&board);
+        assert_eq!(moves.len(), 20);
+        assert!(moves.iter().all(|movement| movement.taken.is_none()));
+        assert!(contains_move(&moves, Coordinate::from_row_col('e', 7), Coordinate::from_row_col('e', 5)));
+        assert!(contains_move(&moves, Coordinate::from_row_col('b', 8), Coordinate::from_row_col('c', 6)));
+        // black to move
+        board.apply_movement(&moves[0]);
+        let moves = MoveGenerator::legal_moves(&board);
+        assert_eq!(moves.len(), 20);
+        assert!(moves.iter().all(|movement| board.get_current_setup()[movement.from.as_index()].is_black()));
+    }
+
+    #[test]
+    fn perft_test () {
+        let board = Board::new();
+        assert_eq!(perft(&board, 1), 20);
+        assert_eq!(perft(&board, 2), 400);
+        assert_eq!(perft(&board, 3), 8902);
+    }
+
+    #[test]
+    fn matches_basic_rules_test () {
+        let mut board = Board::new();
+        //8 ♖   ♗   ♔ ♗ ♘ ♖
+        //7 ♙ ♙ ♙   ♙ ♙ ♙ ♙
+        //6
+        //5       ♙ ♟
+        //4   ♝   ♕
+        //3     ♘
+        //2 ♟ ♟ ♟ ♟   ♟ ♟ ♟
+        //1 ♜ ♞   ♛ ♚ ♝ ♞ ♜
+        //  a b c d e f g h
+        board.teleport('d', 7, 'd', 5);
+        board.teleport('d', 8, 'd', 4);
+        board.teleport('b', 8, 'c', 3);
+        board.teleport('e', 2, 'e', 5);
+        board.teleport('c', 1, 'b', 4);
+        let moves = MoveGenerator::pseudo_legal_moves(&board);
+        let mut expected = 0;
+        for from in 0..64 {
+            if !board.get_current_setup()[from].is_white() { continue; }
+            for to in 0..64 {
+                let valid = Rules::is_basic_movement_valid(&board, &Coordinate::from_index(from), &Coordinate::from_index(to));
+                assert_eq!(contains_move(&moves, Coordinate::from_index(from), Coordinate::from_index(to)), valid);
+                if valid { expected += 1; }
+            }
+        }
+        assert_eq!(moves.len(), expected);
+        // captures record the taken piece
+        let capture = moves.iter()
+            .find(|movement| movement.from == Coordinate::from_row_col('d', 4) && movement.to == Coordinate::from_row_col('e', 5))
+            .unwrap();
+        assert_eq!(capture.taken.unwrap().piece_type, Pieces::BlackPawn);
+    }
+
+    #[test]
+    fn pinned_piece_test () {
+        let mut board = Board::new();
+        //8 ♖ ♘ ♗ ♕ ♔ ♗   ♖
+        //7 ♙ ♙ ♙ ♙ ♘ ♙ ♙ ♙
+        //6
+        //5         ♛
+        //4
+        //3
+        //2 ♟ ♟ ♟ ♟ ♟ ♟ ♟ ♟
+        //1 ♜ ♞ ♝   ♚ ♝ ♞ ♜
+        //  a b c d e f g h
+        board.teleport('g', 8, 'e', 7);
+        board.teleport('d', 1, 'e', 5);
+        let from_coord = Coordinate::from_row_col('e', 7);
+        assert!(!Rules::is_king_under_attack(&board, false));
+        assert!(MoveGenerator::legal_moves_from(&board, &from_coord).is_empty());
+        assert!(!MoveGenerator::pseudo_legal_moves(&board).iter().all(|movement| movement.from != from_coord));
+        assert!(MoveGenerator::legal_moves(&board).iter().all(|movement| movement.from != from_coord));
+    }
+
+    #[test]
+    fn check_evasion_test () {
+        let mut board = Board::new();
+        //8 ♖ ♘ ♗ ♕ ♔ ♗ ♘ ♖
+        //7 ♙ ♙ ♙ ♙ ♙ ♙ ♙ ♙
+        //6       ♞
+        //5
+        //4
+        //3
+        //2 ♟ ♟ ♟ ♟ ♟ ♟ ♟ ♟
+        //1 ♜ ♞ ♝ ♛ ♚ ♝   ♜
+        //  a b c d e f g h
+        board.teleport('g', 1, 'd', 6);
+        assert!(Rules::is_king_under_attack(&board, false));
+        let moves = MoveGenerator::legal_moves(&board);
+        assert_eq!(moves.len(), 2);
+        assert!(contains_move(&moves, Coordinate::from_row_col('c', 7), Coordinate::from_row_col('d', 6)));
+        assert!(contains_move(&moves, Coordinate::from_row_col('e', 7), Coordinate::from_row_col('d', 6)));
+        assert!(moves.iter().all(|movement| movement.taken.unwrap().piece_type == Pieces::BlackKnight));
+    }
+
+    #[test]
+    fn castling_generation_test () {
+        let mut board = Board::new();
+        //8 ♖       ♔     ♖
+        //7 ♙ ♙ ♙ ♙ ♙ ♙ ♙ ♙
+        //6 ♗ ♘ ♗ ♕   ♘
+        //5
+        //4
+        //3
+        //2 ♟ ♟ ♟ ♟ ♟ ♟ ♟ ♟
+        //1 ♜ ♞ ♝ ♛ ♚ ♝ ♞ ♜
+        //  a b c d e f g h
+        board.teleport('b', 8, 'b', 6);
+        board.teleport('c', 8, 'c', 6);
+        board.teleport('d', 8, 'd', 6);
+        board.teleport('f', 8, 'a', 6);
+        board.teleport('g', 8, 'f', 6);
+        let king = Coordinate::from_row_col('e', 8);
+        let moves = MoveGenerator::legal_moves_from(&board, &king);
+        assert!(contains_move(&moves, king, Coordinate::from_row_col('a', 8)));
+        assert!(contains_move(&moves, king, Coordinate::from_row_col('h', 8)));
+        // castling moves the king two squares and the rook next to it
+        let mut castled = board.clone();
+        castled.apply_movement(&Movement::new(king.as_index(), Coordinate::from_row_col('h', 8).as_index(), None));
+        let setup = castled.get_current_setup();
+        assert_eq!(setup[Coordinate::from_row_col('g', 8).as_index()].piece_type, Pieces::WhiteKing);
+        assert_eq!(setup[Coordinate::from_row_col('f', 8).as_index()].piece_type, Pieces::WhiteRook);
+        assert!(setup[Coordinate::from_row_col('e', 8).as_index()].is_empty());
+        assert!(setup[Coordinate::from_row_col('h', 8).as_index()].is_empty());
+        // the black knight attacks f8, which the king would have to cross
+        board.teleport('g', 1, 'g', 6);
+        let moves = MoveGenerator::legal_moves_from(&board, &king);
+        assert!(contains_move(&moves, king, Coordinate::from_row_col('a', 8)));
+        assert!(!contains_move(&moves, king, Coordinate::from_row_col('h', 8)));
+    }
+
+    #[test]
+    fn en_passant_generation_test () {
+        let mut board = Board::new();
+        //8 ♖ ♘ ♗ ♕ ♔ ♗ ♘ ♖
+        //7 ♙ ♙ ♙ ♙   ♙ ♙ ♙
+        //6
+        //5
+        //4       ♟ ♙ ♟
+        //3
+        //2 ♟ ♟ ♟     ♟ ♟ ♟
+        //1 ♜ ♞ ♝ ♛ ♚ ♝ ♞ ♜
+        //  a b c d e f g h
+        board.teleport('e', 7, 'e', 4);
+        board.teleport('e', 2, 'f', 4);
+        board.make_a_move('h', 7, 'h', 6).unwrap();
+        board.make_a_move('d', 2, 'd', 4).unwrap();
+        let from = Coordinate::from_row_col('e', 4);
+        let moves = MoveGenerator::legal_moves_from(&board, &from);
+        // only the pawn on d4 just made a double step, the one on f4 cannot be taken en passant
+        assert_eq!(moves.len(), 2);
+        let en_passant = moves.iter().find(|movement| movement.to == Coordinate::from_row_col('d', 3)).unwrap();
+        assert_eq!(en_passant.taken.unwrap().piece_type, Pieces::BlackPawn);
+        assert!(contains_move(&moves, from, Coordinate::from_row_col('e', 3)));
+    }
+
+    #[test]
+    fn promotion_generation_test () {
+        let mut board = Board::new();
+        //8 ♖ ♘ ♗ ♕ ♔ ♗ ♘ ♖
+        //7 ♙ ♙ ♙ ♙ ♙ ♙ ♙ ♙
+        //6
+        //5
+        //4
+        //3
+        //2 ♟ ♟ ♟ ♟ ♙ ♟ ♟ ♟
+        //1 ♜ ♞ ♝ ♛ ♚ ♝ ♞ ♜
+        //  a b c d e f g h
+        board.teleport('a', 7, 'e', 2);
+        let from = Coordinate::from_row_col('e', 2);
+        let moves = MoveGenerator::legal_moves_from(&board, &from);
+        // two captures, each with every possible promotion
+        assert_eq!(moves.len(), 8);
+        for promotion in Promotion::ALL {
+            for to in [Coordinate::from_row_col('d', 1), Coordinate::from_row_col('f', 1)] {
+                assert!(moves.iter().any(|movement| movement.to == to && movement.promotion == Some(promotion)));
+            }
+        }
+        let mut promoted = board.clone();
+        let knight = moves.iter().find(|movement| movement.promotion == Some(Promotion::Knight)).unwrap();
+        promoted.apply_movement(knight);
+        assert_eq!(promoted.get_current_setup()[knight.to.as_index()].piece_type, Pieces::WhiteKnight);
+    }
+}
@@ -1,10 +1,49 @@
-use crate::pieces::Piece;
+use crate::pieces::{Pieces, Piece};
 use crate::coordinate::Coordinate;
 
+// what a pawn reaching the last row turns into
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Promotion {
+    Queen,
+    Rook,
+    Bishop,
+    Knight
+}
+
+impl Promotion {
+    pub const ALL: [Promotion; 4] = [Promotion::Queen, Promotion::Rook, Promotion::Bishop, Promotion::Knight];
+
+    // the letter used in the move notation, e.g. 'N' for a knight
+    pub fn from_char (letter: char) -> Option<Self> {
+        match letter.to_ascii_uppercase() {
+            'Q' => Some(Promotion::Queen),
+            'R' => Some(Promotion::Rook),
+            'B' => Some(Promotion::Bishop),
+            'N' => Some(Promotion::Knight),
+            _ => None
+        }
+    }
+
+    pub fn piece_type (&self, black: bool) -> Pieces {
+        match (self, black) {
+            (Promotion::Queen, false) => Pieces::WhiteQueen,
+            (Promotion::Rook, false) => Pieces::WhiteRook,
+            (Promotion::Bishop, false) => Pieces::WhiteBishop,
+            (Promotion::Knight, false) => Pieces::WhiteKnight,
+            (Promotion::Queen, true) => Pieces::BlackQueen,
+            (Promotion::Rook, true) => Pieces::BlackRook,
+            (Promotion::Bishop, true) => Pieces::BlackBishop,
+            (Promotion::Knight, true) => Pieces::BlackKnight
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Movement {
     pub from: Coordinate,
     pub to: Coordinate,
-    pub taken: Option<Piece>
+    pub taken: Option<Piece>,
+    pub promotion: Option<Promotion>
 }
 
 impl Movement {
@@ -12,7 +51,13 @@ impl Movement {
         Self {
             from: Coordinate::from_index(from_index),
             to: Coordinate::from_index(to_index),
-            taken
+            taken,
+            promotion: None
         }
     }
+
+    pub fn with_promotion (mut self, promotion: Promotion) -> Self {
+        self.promotion = Some(promotion);
+        self
+    }
 }
\ No newline at end of file
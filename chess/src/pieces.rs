@@ -84,7 +84,7 @@ impl Pieces {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Piece {
     pub piece_type: Pieces,
     pub moved: bool,
@@ -14,9 +14,13 @@
 
 */
 
-use crate::pieces::Pieces;
+use crate::pieces::{Pieces, Piece};
 use crate::coordinate::Coordinate;
-use crate::board::Board;
+use crate::board::{Board, Turn};
+use crate::game::Game;
+use crate::movement::Movement;
+use crate::move_generator::MoveGenerator;
+use crate::game_result::GameResult;
 
 pub struct Rules { }
 
@@ -38,6 +42,7 @@ impl Rules {
                     // white pawn can move to an empty place:
                     //     - if it is in the same column and in the next row
                     //     - if it is in the same column and in row 3, the pawn is in row 1, and the place in between is empty
+                    if Rules::is_en_passant(board, from, to) { return true; }
                     if from_x != to_x { return false; } // cannot step onto an empty field not in the same column
                     if from_y >= to_y { return false; } // can only step to a row with larger index
                     if from_y + 1 == to_y { return true; }
@@ -95,6 +100,7 @@ impl Rules {
                     // black pawn can move to an empty place:
                     //     - if it is in the same column and in the next row
                     //     - if it is in the same column and in row 4, the pawn is in row 6, and the place in between is empty
+                    if Rules::is_en_passant(board, from, to) { return true; }
                     if from_x != to_x { return false; } // cannot step onto an empty field not in the same column
                     if from_y <= to_y { return false; } // can only step to a row with smaller index
                     if from_y - 1 == to_y { return true; }
@@ -151,6 +157,31 @@ impl Rules {
         }
     }
 
+    // a pawn stepping onto the last row in front of it, white pawns walk down and black pawns up
+    pub fn is_promotion (board: &Board, from: &Coordinate, to: &Coordinate) -> bool {
+        let (_, to_y) = to.as_x_y();
+        match board.get_current_setup()[from.as_index()].piece_type {
+            Pieces::WhitePawn => to_y == 7,
+            Pieces::BlackPawn => to_y == 0,
+            _ => false
+        }
+    }
+
+    // a pawn taking the pawn which just stepped over to, as if it had only moved one square
+    pub fn is_en_passant (board: &Board, from: &Coordinate, to: &Coordinate) -> bool {
+        if board.get_en_passant() != Some(*to) { return false; }
+        let setup = board.get_current_setup();
+        let (from_x, from_y) = from.as_x_y();
+        let (to_x, to_y) = to.as_x_y();
+        if from_x.abs_diff(to_x) != 1 { return false; }
+        let taken = &setup[Coordinate::convert_coordinates_to_index(to_x, from_y)];
+        match setup[from.as_index()].piece_type {
+            Pieces::WhitePawn => (to_y == from_y + 1) && (taken.piece_type == Pieces::BlackPawn),
+            Pieces::BlackPawn => (to_y + 1 == from_y) && (taken.piece_type == Pieces::WhitePawn),
+            _ => false
+        }
+    }
+
     fn is_clear_horizontal(board: &Board, y: u8, x1: u8, x2: u8) -> bool {
         let setup = board.get_current_setup();
         let (start, end) = if x1 < x2 { (x1 + 1, x2) } else { (x2 + 1, x1) };
@@ -213,19 +244,33 @@ impl Rules {
         // if the king is currently under attack, castling is not possible
         if Rules::is_king_under_attack(board, from_piece.is_black()) { return false; }
         // castling only works between a king and a rook
-        if (from_piece.piece_type == Pieces::BlackKing) && (to_piece.piece_type == Pieces::BlackRook) {
-            return true;
-        }
-        else if (from_piece.piece_type == Pieces::BlackRook) && (to_piece.piece_type == Pieces::BlackKing) {
-            return true;
-        }
-        else if (from_piece.piece_type == Pieces::WhiteKing) && (to_piece.piece_type == Pieces::WhiteRook) {
-            return true;
-        }
-        else if (from_piece.piece_type == Pieces::WhiteRook) && (to_piece.piece_type == Pieces::WhiteKing) {
-            return true;
+        if !Rules::is_king_and_rook(from_piece, to_piece) { return false; }
+        // the king may not cross a square which is under attack
+        let (king_index, king_x, rook_x) = if from_piece.piece_type == Pieces::WhiteKing || from_piece.piece_type == Pieces::BlackKing {
+            (from_index, from_x, to_x)
         }
-        false
+        else {
+            (to_index, to_x, from_x)
+        };
+        let crossed_x = if rook_x < king_x { king_x - 1 } else { king_x + 1 };
+        let mut crossed = board.clone();
+        crossed.apply_movement(&Movement::new(king_index, Coordinate::convert_coordinates_to_index(crossed_x, from_y), None));
+        !Rules::is_king_under_attack(&crossed, from_piece.is_black())
+    }
+
+    // a king and a rook of the same colour, in any order
+    pub fn is_king_and_rook (from: &Piece, to: &Piece) -> bool {
+        matches!((from.piece_type, to.piece_type),
+            (Pieces::BlackKing, Pieces::BlackRook) | (Pieces::BlackRook, Pieces::BlackKing) |
+            (Pieces::WhiteKing, Pieces::WhiteRook) | (Pieces::WhiteRook, Pieces::WhiteKing))
+    }
+
+    // plays the movement on a copy of the board and checks the king of the moving side
+    pub fn leaves_king_in_check (board: &Board, movement: &Movement) -> bool {
+        let black = board.get_current_setup()[movement.from.as_index()].is_black();
+        let mut after = board.clone();
+        after.apply_movement(movement);
+        Rules::is_king_under_attack(&after, black)
     }
 
     pub fn is_king_under_attack (board: &Board, black: bool) -> bool {
@@ -239,6 +284,43 @@ impl Rules {
         false
     }
 
+    // the result if the game is over for the side to move, None while it goes on
+    pub fn get_game_result (game: &Game) -> Option<GameResult> {
+        let board = game.get_board();
+        if MoveGenerator::legal_moves(board).is_empty() {
+            let black = board.get_turn() == Turn::Black;
+            if Rules::is_king_under_attack(board, black) {
+                let winner = if black { Turn::White } else { Turn::Black };
+                return Some(GameResult::Checkmate { winner });
+            }
+            return Some(GameResult::Stalemate);
+        }
+        if Rules::is_insufficient_material(board) { return Some(GameResult::InsufficientMaterial); }
+        if board.get_halfmove_clock() >= 100 { return Some(GameResult::FiftyMoveRule); }
+        if game.count_repetitions() >= 3 { return Some(GameResult::ThreefoldRepetition); }
+        None
+    }
+
+    // true for king against king, king and a single minor piece against king,
+    // and kings with bishops which all stand on squares of the same colour
+    pub fn is_insufficient_material (board: &Board) -> bool {
+        let mut knights = 0;
+        let mut bishop_squares = Vec::new();
+        for (index, piece) in board.get_current_setup().iter().enumerate() {
+            match piece.piece_type {
+                Pieces::WhiteKing | Pieces::BlackKing | Pieces::Empty => {},
+                Pieces::WhiteKnight | Pieces::BlackKnight => knights += 1,
+                Pieces::WhiteBishop | Pieces::BlackBishop => {
+                    let (x, y) = Coordinate::convert_index_to_coordinates(index);
+                    bishop_squares.push((x + y) % 2);
+                },
+                _ => return false
+            }
+        }
+        if knights + bishop_squares.len() <= 1 { return true; }
+        knights == 0 && bishop_squares.iter().all(|square| *square == bishop_squares[0])
+    }
+
 }
 
 #[cfg(test)]
@@ -250,10 +332,10 @@ mod tests {
             for row in 1..8 {
                 let to_coord = Coordinate::from_row_col(col, row);
                 if tos.contains(&to_coord) {
-                    if !Rules::is_basic_movement_valid(board, &from, &to_coord) { return false; }
+                    if !Rules::is_basic_movement_valid(board, from, &to_coord) { return false; }
                 }
                 else {
-                    if Rules::is_basic_movement_valid(board, &from, &to_coord) { return false; }
+                    if Rules::is_basic_movement_valid(board, from, &to_coord) { return false; }
                 }
             }
         }
@@ -265,7 +347,7 @@ mod tests {
         for col in "abcdefgh".chars() {
             for row in 1..8 {
                 let to_coord = Coordinate::from_row_col(col, row);
-                if !Rules::is_basic_movement_valid(board, &from, &to_coord) { return false; }
+                if !Rules::is_basic_movement_valid(board, from, &to_coord) { return false; }
             }
         }
         true
@@ -275,7 +357,7 @@ mod tests {
         for col in "abcdefgh".chars() {
             for row in 1..8 {
                 let to_coord = Coordinate::from_row_col(col, row);
-                if Rules::is_basic_movement_valid(board, &from, &to_coord) { return false; }
+                if Rules::is_basic_movement_valid(board, from, &to_coord) { return false; }
             }
         }
         true
@@ -572,4 +654,100 @@ mod tests {
         let to_coord = Coordinate::from_row_col('e', 8);
         assert!(!Rules::is_castling_valid(&board, &from_coord, &to_coord));
     }
+
+    // removes every piece except the ones on the given squares
+    fn keep_only (game: &mut Game, squares: &[(char, u8)]) {
+        for col in "abcdefgh".chars() {
+            for row in 1..=8 {
+                if !squares.contains(&(col, row)) { game.remove(col, row); }
+            }
+        }
+    }
+
+    #[test]
+    fn checkmate_test () {
+        let mut game = Game::new();
+        assert_eq!(Rules::get_game_result(&game), None);
+        game.make_a_move('f', 7, 'f', 6).unwrap();
+        game.make_a_move('e', 2, 'e', 4).unwrap();
+        game.make_a_move('g', 7, 'g', 5).unwrap();
+        assert_eq!(Rules::get_game_result(&game), None);
+        game.make_a_move('d', 1, 'h', 5).unwrap();
+        let result = Rules::get_game_result(&game).unwrap();
+        assert_eq!(result, GameResult::Checkmate { winner: Turn::Black });
+        assert!(!result.is_draw());
+    }
+
+    #[test]
+    fn stalemate_test () {
+        let mut game = Game::new();
+        //8 ♔
+        //7     ♚
+        //6   ♛
+        //5
+        //4
+        //3
+        //2
+        //1
+        //  a b c d e f g h
+        keep_only(&mut game, &[('e', 8), ('d', 1), ('e', 1)]);
+        game.teleport('e', 8, 'a', 8);
+        game.teleport('d', 1, 'b', 6);
+        game.teleport('e', 1, 'c', 7);
+        let result = Rules::get_game_result(&game).unwrap();
+        assert_eq!(result, GameResult::Stalemate);
+        assert!(result.is_draw());
+    }
+
+    #[test]
+    fn insufficient_material_test () {
+        let mut game = Game::new();
+        assert!(!Rules::is_insufficient_material(game.get_board()));
+        keep_only(&mut game, &[('e', 8), ('e', 1), ('c', 8), ('f', 1), ('b', 8), ('a', 1)]);
+        assert!(!Rules::is_insufficient_material(game.get_board()));
+        // bishops on c8 and f1 stand on squares of the same colour
+        game.remove('a', 1);
+        assert!(!Rules::is_insufficient_material(game.get_board()));
+        game.remove('b', 8);
+        assert!(Rules::is_insufficient_material(game.get_board()));
+        assert_eq!(Rules::get_game_result(&game), Some(GameResult::InsufficientMaterial));
+        game.teleport('f', 1, 'f', 2);
+        assert!(!Rules::is_insufficient_material(game.get_board()));
+        game.remove('f', 2);
+        assert!(Rules::is_insufficient_material(game.get_board()));
+        game.remove('c', 8);
+        assert!(Rules::is_insufficient_material(game.get_board()));
+    }
+
+    #[test]
+    fn threefold_repetition_test () {
+        let mut game = Game::new();
+        for _ in 0..2 {
+            assert_eq!(Rules::get_game_result(&game), None);
+            game.make_a_move('b', 8, 'c', 6).unwrap();
+            game.make_a_move('b', 1, 'c', 3).unwrap();
+            game.make_a_move('c', 6, 'b', 8).unwrap();
+            game.make_a_move('c', 3, 'b', 1).unwrap();
+        }
+        assert_eq!(game.count_repetitions(), 3);
+        assert_eq!(Rules::get_game_result(&game), Some(GameResult::ThreefoldRepetition));
+    }
+
+    #[test]
+    fn fifty_move_rule_test () {
+        let mut game = Game::new();
+        game.make_a_move('e', 7, 'e', 5).unwrap();
+        assert_eq!(game.get_board().get_halfmove_clock(), 0);
+        for _ in 0..25 {
+            game.make_a_move('b', 1, 'c', 3).unwrap();
+            game.make_a_move('b', 8, 'c', 6).unwrap();
+            game.make_a_move('c', 3, 'b', 1).unwrap();
+            game.make_a_move('c', 6, 'b', 8).unwrap();
+        }
+        assert_eq!(game.get_board().get_halfmove_clock(), 100);
+        assert_eq!(Rules::get_game_result(&game), Some(GameResult::FiftyMoveRule));
+        // a pawn move resets the clock
+        game.make_a_move('e', 2, 'e', 3).unwrap();
+        assert_eq!(game.get_board().get_halfmove_clock(), 0);
+    }
 }
@@ -1,35 +1,29 @@
 use game::bounding_box::BoundingBox;
-use brain::network::network::Network;
-use brain::math::matrix::Matrix;
+use brain::brain::Brain;
 
 #[derive(Clone)]
 pub struct Bird {
     pub bounding_box: BoundingBox,
-    pub brain: Network<f32>,
+    pub brain: Box<dyn Brain<f32>>,
     velocity: f32,
     score: u32,
     alive: bool
 }
 
 impl Bird {
-    pub fn new (x: f32, y: f32, w: f32, h: f32, network: Network<f32>) -> Self {
+    pub fn new (x: f32, y: f32, w: f32, h: f32, brain: Box<dyn Brain<f32>>) -> Self {
         Self {
             bounding_box: BoundingBox::new(x, y, w, h),
-            brain: network,
+            brain,
             velocity: 0.0,
             score: 0,
             alive: true
         }
     }
 
-    pub fn wanna_jump (&mut self, x_distance: f32, y_distance_1: f32, y_distance_2: f32, velocity: f32) -> bool {
-        let mut input = Matrix::<f32>::new(1, 4);
-        input[0][0] = x_distance;
-        input[0][1] = y_distance_1;
-        input[0][2] = y_distance_2;
-        input[0][3] = velocity;
-        let output = self.brain.forward(&input);
-        output[0][0] > 0.55
+    pub fn wanna_jump (&self, x_distance: f32, y_distance_1: f32, y_distance_2: f32, velocity: f32) -> bool {
+        let output = self.brain.forward(&[x_distance, y_distance_1, y_distance_2, velocity]);
+        output[0] > 0.55
     }
 
     pub fn reset (&mut self, x: f32, y: f32) {
@@ -60,7 +54,7 @@ impl Bird {
     }
 
     pub fn collides_with (&self, bb: &BoundingBox) -> bool {
-        return self.bounding_box.is_colliding_with(bb)
+        self.bounding_box.is_colliding_with(bb)
     }
 
     pub fn increase_score (&mut self) {
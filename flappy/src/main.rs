@@ -1,11 +1,19 @@
 pub mod bird;
 pub mod obstacle;
+pub mod optimizer;
 
 use bird::Bird;
 use obstacle::Obstacles;
+use optimizer::Optimizer;
 use brain::math::random::RandomGenerator;
 use brain::network::network_creator::NetworkCreator;
 use brain::network::network_mutator::{NetworkMutatorConfig, NetworkMutator};
+use brain::evolution::population::{Population, PopulationConfig};
+use brain::evolution::selection::Selection;
+use brain::evolution::evolution_strategy::{EvolutionStrategy, EvolutionStrategyConfig};
+use brain::evolution::cma_es::{CmaEs, CmaEsConfig};
+use brain::evolution::parallel::parallel_rounds;
+use brain::network::network_serializer::NetworkSerializer;
 use std::io::{self, Write};
 use std::fs::File;
 
@@ -26,6 +34,7 @@ const BIRD_W : f32 =  2.0;
 const BIRD_H : f32 =  2.0;
 
 const NUM_FITTEST : usize = 10;
+const NUM_FRESH : usize = NUM_BIRDS / 2;
 const MIN_HIDDEN_LAYERS : usize = 1;
 const MAX_HIDDEN_LAYERS : usize = 4;
 
@@ -36,6 +45,9 @@ const OBSTACLE_VELOCITY : f32 = 1.0;
 const JUMP_FREQUENCY : u32 = 5;
 const GOAL : u32 = 1000000;
 const SEED : u64 = 50;
+// threads for the birds, 0 uses every core
+const NUM_THREADS : usize = 0;
+const BEST_BRAIN_FILE : &str = "best_brain.txt";
 
 fn main() -> io::Result<()> {
     // random number generator for in-game random numbers, such as obstacle positions
@@ -44,21 +56,25 @@ fn main() -> io::Result<()> {
     let mut birds: Vec<Bird> = Vec::<Bird>::new();
     // network initializer and mutator
     let mut initializer = NetworkCreator::<f32>::new(SEED, -1.0, 1.0, SEED+5, MIN_HIDDEN_LAYERS, MAX_HIDDEN_LAYERS, SEED+10, 1, 8);
-    let mutator_config = NetworkMutatorConfig {
-        random_generator_seed: SEED,
-        weight_mutation_probability: 0.5,
-        weight_change_upper_limit: 0.5,
-        weight_change_lower_limit: -0.5
+    // usage: flappy [ga|es|cma], the evolution strategies optimize the weights of a single random topology
+    let mut optimizer = match std::env::args().nth(1).as_deref() {
+        Some("es") => {
+            let config = EvolutionStrategyConfig { seed: SEED, pairs: NUM_BIRDS / 2, sigma: 0.5, learning_rate: 0.1, weight_decay: 0.0 };
+            Optimizer::EvolutionStrategy(Box::new(EvolutionStrategy::new(config, initializer.create(4, 1))))
+        },
+        Some("cma") => {
+            let config = CmaEsConfig { seed: SEED, population_size: Some(NUM_BIRDS), sigma: 0.5 };
+            Optimizer::CmaEs(Box::new(CmaEs::new(config, initializer.create(4, 1))))
+        },
+        _ => Optimizer::Genetic(Box::new(create_population(initializer)))
     };
-    let mut mutator = NetworkMutator::new(mutator_config);
     // populate birds
-    for _i in 0..NUM_BIRDS {
-        birds.push(Bird::new(BIRD_X, BIRD_Y, BIRD_W, BIRD_H, initializer.create(4, 1)));
+    for network in optimizer.networks() {
+        birds.push(Bird::new(BIRD_X, BIRD_Y, BIRD_W, BIRD_H, Box::new(network)));
     }
 
     let mut generation : u32 = 0;
     let mut best_score : u32 = 0;
-    let mut best_brain = birds[0].brain.clone(); // store the "best brain" for later
 
     // write to file:
     let mut gameplay = File::create("gameplay.yaml")?;
@@ -80,7 +96,7 @@ fn main() -> io::Result<()> {
     // infinite loop for the game
     loop {
         // adjust the factor based on the current best score relative to the goal
-        mutator.set_factor(1.0 - ((best_score as f32) / (GOAL as f32)));
+        optimizer.set_factor(1.0 - ((best_score as f32) / (GOAL as f32)));
         generation += 1;
         // create obstacles
         let mut obstacles: [Obstacles; NUM_OBSTACLES] = [
@@ -93,106 +109,132 @@ fn main() -> io::Result<()> {
         // variables to follow
         let mut current_score : u32 = 0;
         let mut obstacles_passed : u32 = 0;
-        // inner loop for the game runs
-        loop {
-            current_score += 1;
-            print_game_stats(current_score, obstacles_passed, best_score, generation);
-            writeln!(gameplay, "  - current_score: {}", current_score)?;
-            writeln!(gameplay, "    best_score: {}", best_score)?;
-            writeln!(gameplay, "    birds:")?;
-            for bird in birds.iter_mut() {
-                if bird.is_alive() {
-                    bird.fall(GRAVITY);
-                    bird.apply_physics();
-                    writeln!(gameplay, "      - y: {}", bird.bounding_box.origin.y)?;
-                }
-            }
-            writeln!(gameplay, "    obstacles:")?;
-            for obstacle in obstacles.iter_mut() {
-                obstacle.change_position(-OBSTACLE_VELOCITY, 0.0);
-                // did it move outside the scene?
-                if obstacle.x() < -OBSTACLE_W {
-                    let new_x = obstacle.x() + ((NUM_OBSTACLES) as f32) * OBSTACLE_GAP_X;
-                    *obstacle = Obstacles::new(SCENE_H, new_x, OBSTACLE_W, rand.generate(UPPER_OBSTACKLE_H_MIN, UPPER_OBSTACKLE_H_MAX), OBSTACLE_GAP_Y);
+        // inner loop for the game runs, the birds are split among the threads once per generation
+        // and think in parallel on every tick
+        parallel_rounds(&mut birds, NUM_THREADS, step_bird, |rounds| -> io::Result<()> {
+            loop {
+                current_score += 1;
+                print_game_stats(current_score, obstacles_passed, best_score, generation);
+                writeln!(gameplay, "  - current_score: {}", current_score)?;
+                writeln!(gameplay, "    best_score: {}", best_score)?;
+                for obstacle in obstacles.iter_mut() {
+                    obstacle.change_position(-OBSTACLE_VELOCITY, 0.0);
+                    // did it move outside the scene?
+                    if obstacle.x() < -OBSTACLE_W {
+                        let new_x = obstacle.x() + ((NUM_OBSTACLES) as f32) * OBSTACLE_GAP_X;
+                        *obstacle = Obstacles::new(SCENE_H, new_x, OBSTACLE_W, rand.generate(UPPER_OBSTACKLE_H_MIN, UPPER_OBSTACKLE_H_MAX), OBSTACLE_GAP_Y);
+                    }
                 }
-                writeln!(gameplay, "      - x: {}", obstacle.x())?;
-                writeln!(gameplay, "        h: {}", obstacle.upper.dimensions.h)?;
-                writeln!(gameplay, "        active: false")?;
-            }
-            // collision detection and jump prediction
-            let mut someone_is_alive = false;
-            for bird in birds.iter_mut() {
-                if !bird.is_alive() { continue; }
-                let current_obstacles : &mut Obstacles = &mut obstacles[current_obstacle_index];
-                // does bird collide with the obstacles?
-                if bird.collides_with(&current_obstacles.upper) || bird.collides_with(&current_obstacles.lower) {
-                    bird.kill();
-                    continue;
+                // physics, collision detection and jump prediction
+                let steps = rounds.run(Tick { obstacles: obstacles[current_obstacle_index].clone(), score: current_score });
+                writeln!(gameplay, "    birds:")?;
+                for (y, _) in steps.iter().flatten() {
+                    writeln!(gameplay, "      - y: {}", y)?;
                 }
-                // does bird collide with floor or ceiling?
-                if bird.is_outside(0.0, SCENE_H) {
-                    bird.kill();
-                    continue;
+                writeln!(gameplay, "    obstacles:")?;
+                for obstacle in obstacles.iter() {
+                    writeln!(gameplay, "      - x: {}", obstacle.x())?;
+                    writeln!(gameplay, "        h: {}", obstacle.upper.dimensions.h)?;
+                    writeln!(gameplay, "        active: false")?;
                 }
-                bird.increase_score();
-                someone_is_alive = true;
-                let x_dist = (current_obstacles.x() - bird.bounding_box.origin.x) / SCENE_W;
-                let y_dist_1 = (current_obstacles.upper.origin.y - bird.bounding_box.origin.y) / SCENE_H;
-                let y_dist_2 = (current_obstacles.lower.origin.y - bird.bounding_box.origin.y) / SCENE_H;
-                let velocity = bird.get_velocity() / 10.0;
-                if (current_score % JUMP_FREQUENCY) == 0 {
-                    if bird.wanna_jump(x_dist, y_dist_1, y_dist_2, velocity) {
-                        bird.jump(JUMP_BOOST);
+                let someone_is_alive = steps.iter().flatten().any(|(_, alive)| *alive);
+                // update the current obstacle
+                if obstacles[current_obstacle_index].x() < (BIRD_X - OBSTACLE_W) {
+                    current_obstacle_index += 1;
+                    obstacles_passed += 1;
+                    if current_obstacle_index == NUM_OBSTACLES {
+                        current_obstacle_index = 0;
                     }
                 }
-            }
-            // update the current obstacle
-            if obstacles[current_obstacle_index].x() < (BIRD_X - OBSTACLE_W) {
-                current_obstacle_index += 1;
-                obstacles_passed += 1;
-                if current_obstacle_index == NUM_OBSTACLES {
-                    current_obstacle_index = 0;
-                }
-            }
 
-            // loop breaking conditions -> goal reached or everyone dead
-            if !someone_is_alive { break; }
-            if current_score > GOAL { break; }
-        }
+                // loop breaking conditions -> goal reached or everyone dead
+                if !someone_is_alive { return Ok(()); }
+                if current_score > GOAL { return Ok(()); }
+            }
+        })?;
 
-        // round is over, sort birds based on score and select the fittest ones for darwinian evolution
-        birds.sort_by(|a, b| b.get_score().cmp(&a.get_score()));
-        if birds[0].get_score() > best_score {
-            best_score = birds[0].get_score();
-            best_brain = birds[0].brain.clone();
-        }
-        for i in 0..NUM_BIRDS {
-            birds[i].reset(BIRD_X, BIRD_Y); // reset the game parameters
-            // the fittest ones get to try again -> don't mutate them
-            if i > NUM_FITTEST {
-                // mutate the first third, re-initialize the second third, create new for the last third
-                // first part -> mutation of the all-time best
-                // second part -> mutation of the top N
-                // third part -> newly initialized networks
-                if i < (NUM_BIRDS / 5) {
-                    birds[i].brain = best_brain.clone();
-                    mutator.mutate(&mut birds[i].brain);
-                }
-                else if i < (NUM_BIRDS / 2) {
-                    birds[i].brain = birds[i % NUM_FITTEST].brain.clone();
-                    mutator.mutate(&mut birds[i].brain);
-                }
-                else {
-                    initializer.initialize(&mut birds[i].brain);
-                }
+        // round is over, the scores decide who gets to reproduce (darwinian evolution)
+        let scores: Vec<f64> = birds.iter().map(|bird| bird.get_score() as f64).collect();
+        let stats = optimizer.evolve(&scores);
+        if stats.best as u32 > best_score {
+            best_score = stats.best as u32;
+            // persist the best brain so it survives the (otherwise endless) run
+            if let Some(best) = optimizer.get_best()
+                && let Err(e) = NetworkSerializer::save_text(&best.network, BEST_BRAIN_FILE) {
+                eprintln!("\ncould not save the best brain: {}", e);
             }
         }
+        for (bird, network) in birds.iter_mut().zip(optimizer.networks()) {
+            bird.reset(BIRD_X, BIRD_Y); // reset the game parameters
+            bird.brain = Box::new(network);
+        }
         if best_score > GOAL { break; }
     }
     print!("well done...");
     Ok(())
 }
 
+// the fittest ones get to try again, the top N are mutated and the rest is newly created
+fn create_population(initializer: NetworkCreator<f32>) -> Population<f32> {
+    let mutator_config = NetworkMutatorConfig {
+        random_generator_seed: SEED,
+        weight_mutation_probability: 0.5,
+        weight_change_upper_limit: 0.5,
+        weight_change_lower_limit: -0.5,
+        ..Default::default()
+    };
+    let mutator = NetworkMutator::new(mutator_config);
+    let population_config = PopulationConfig {
+        size: NUM_BIRDS,
+        elitism: NUM_FITTEST,
+        fresh: NUM_FRESH,
+        selection: Selection::Truncation(NUM_FITTEST),
+        seed: SEED,
+        input_size: 4,
+        output_size: 1
+    };
+    Population::new(population_config, initializer, mutator)
+}
+
+// what every bird needs to know about the current tick
+struct Tick {
+    obstacles: Obstacles,
+    score: u32
+}
+
+// lets a living bird fall and think, returns its height after the fall and whether it is (still) alive
+fn step_bird(bird: &mut Bird, tick: &Tick) -> Option<(f32, bool)> {
+    if !bird.is_alive() { return None; }
+    bird.fall(GRAVITY);
+    bird.apply_physics();
+    let y = bird.bounding_box.origin.y;
+    Some((y, update_bird(bird, &tick.obstacles, tick.score)))
+}
+
+// collision detection and jump prediction of a living bird, returns whether it is (still) alive
+fn update_bird(bird: &mut Bird, current_obstacles: &Obstacles, current_score: u32) -> bool {
+    if !bird.is_alive() { return false; }
+    // does bird collide with the obstacles?
+    if bird.collides_with(&current_obstacles.upper) || bird.collides_with(&current_obstacles.lower) {
+        bird.kill();
+        return false;
+    }
+    // does bird collide with floor or ceiling?
+    if bird.is_outside(0.0, SCENE_H) {
+        bird.kill();
+        return false;
+    }
+    bird.increase_score();
+    let x_dist = (current_obstacles.x() - bird.bounding_box.origin.x) / SCENE_W;
+    let y_dist_1 = (current_obstacles.upper.origin.y - bird.bounding_box.origin.y) / SCENE_H;
+    let y_dist_2 = (current_obstacles.lower.origin.y - bird.bounding_box.origin.y) / SCENE_H;
+    let velocity = bird.get_velocity() / 10.0;
+    if current_score.is_multiple_of(JUMP_FREQUENCY) && bird.wanna_jump(x_dist, y_dist_1, y_dist_2, velocity) {
+        bird.jump(JUMP_BOOST);
+    }
+    true
+}
+
 fn print_game_stats(current_score: u32, obstacles_passed: u32, best_score: u32, generation: u32) {
     print!("\r"); // move the cursor to the beginning of the line
     print!(
@@ -0,0 +1,56 @@
+use brain::network::network::Network;
+use brain::evolution::population::{Population, Individual};
+use brain::evolution::evolution_strategy::EvolutionStrategy;
+use brain::evolution::cma_es::CmaEs;
+use brain::evolution::statistics::GenerationStats;
+
+/*
+    the ways to evolve the birds, all of them play a whole generation at once:
+    the game asks for the networks of the round and reports one score per network afterwards
+*/
+
+pub enum Optimizer {
+    Genetic(Box<Population<f32>>),
+    EvolutionStrategy(Box<EvolutionStrategy<f32>>),
+    CmaEs(Box<CmaEs<f32>>)
+}
+
+impl Optimizer {
+    // the networks playing the next round, one per bird
+    pub fn networks(&mut self) -> Vec<Network<f32>> {
+        match self {
+            Optimizer::Genetic(population) => population.individuals().iter().map(|i| i.network.clone()).collect(),
+            Optimizer::EvolutionStrategy(strategy) => strategy.ask().to_vec(),
+            Optimizer::CmaEs(cma) => cma.ask().to_vec()
+        }
+    }
+
+    // scores in the order of networks
+    pub fn evolve(&mut self, scores: &[f64]) -> GenerationStats {
+        match self {
+            Optimizer::Genetic(population) => {
+                for (i, score) in scores.iter().enumerate() {
+                    population.set_fitness(i, *score);
+                }
+                population.evolve()
+            },
+            Optimizer::EvolutionStrategy(strategy) => strategy.tell(scores),
+            Optimizer::CmaEs(cma) => cma.tell(scores)
+        }
+    }
+
+    pub fn get_best(&self) -> Option<&Individual<f32>> {
+        match self {
+            Optimizer::Genetic(population) => population.get_best(),
+            Optimizer::EvolutionStrategy(strategy) => strategy.get_best(),
+            Optimizer::CmaEs(cma) => cma.get_best()
+        }
+    }
+
+    // only the mutation of the genetic algorithm can be scaled
+    pub fn set_factor(&mut self, factor: f32) {
+        if let Optimizer::Genetic(population) = self {
+            population.get_mutator_mut().set_factor(factor);
+        }
+    }
+}
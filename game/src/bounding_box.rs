@@ -15,18 +15,6 @@ impl BoundingBox {
         }
     }
 
-    fn is_inside (&self, x: f32, y: f32) -> bool {
-        if x < self.origin.x { return false; }
-        if y < self.origin.y { return false; }
-        if x > self.origin.x + self.dimensions.w { return false; }
-        if y > self.origin.y + self.dimensions.h { return false; }
-        true
-    }
-
-    fn is_position_inside (&self, pos: Position) -> bool {
-        self.is_inside(pos.x, pos.y)
-    }
-
     pub fn is_colliding_with (&self, other: &BoundingBox) -> bool {
         // rather than checking if they collide, check if they are separated and negate the result
         !((( self.origin.x +  self.dimensions.w) < other.origin.x) || // other is to the right
@@ -51,17 +39,6 @@ mod tests {
     // import everything from the parent module, the parent module being activation
     use super::*;
 
-    #[test]
-    fn test_is_inside() {
-        let bb = BoundingBox::new(0.0, 0.0, 10.0, 10.0);
-        assert!(bb.is_inside(5.0, 5.0));
-        assert!(bb.is_inside(0.1, 9.9));
-        assert!(!bb.is_inside(-1.0, 5.0));
-        assert!(!bb.is_inside(11.0, 5.0));
-        assert!(!bb.is_inside(5.0, -1.0));
-        assert!(!bb.is_inside(5.0, 11.0));
-    }
-
     #[test]
     fn test_collision() {
         let bb = BoundingBox::new(0.0, 0.0, 10.0, 10.0);